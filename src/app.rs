@@ -1,39 +1,284 @@
 use std::{
     cmp::min,
     collections::{BTreeMap, HashSet},
+    io::{Read, Write},
     path::PathBuf,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
 use anyhow::{Error, Result};
-use colored::Colorize;
+use bytes::Bytes;
+use chrono::FixedOffset;
+use colored::{ColoredString, Colorize};
 use log::{debug, error, info, warn};
+use serde_json::{json, Value};
+use strum::{Display, EnumString};
 
 use crate::{
-    factory::DatafileFactory,
+    factory::{DatafileFactory, UndoSegInfo, SDI_META_INFO_MIN_VER},
     ibd::{
         page::{
-            BasePage, FileSpaceHeaderPageBody, FlstBaseNode, INodeEntry, INodePageBody,
-            IndexPageBody, PageNumber, PageTypes, RSegArrayPageBody, RSegHeaderPageBody,
-            SdiPageBody, SpaceId, TrxSysPageBody, UndoLogPageBody, XDesPageBody, EXTENT_PAGE_NUM,
-            FSP_DICT_HDR_PAGE_NO, FSP_TRX_SYS_PAGE_NO, UNIV_PAGE_SIZE, XDES_ENTRY_MAX_COUNT,
+            BasePage, CompressionTypes, DictHeaderPageBody, FilePageHeader, FilePageTrailer,
+            FileSpaceHeaderPageBody, FlstBaseNode, INodeEntry, INodePageBody, IbufFreeListPageBody,
+            IndexPageBody, LobDataPageBody, LobFirstPageBody, LobIndexPageBody,
+            PageCompressionHeader, PageNumber, PageTypes, RSegArrayPageBody, RSegHeaderPageBody,
+            RtreePageBody, SdiPageBody, SpaceId, TrxSysPageBody, UndoLogPageBody, UndoPageStates,
+            XDesPageBody, EXTENT_PAGE_NUM, FSP_DICT_HDR_PAGE_NO, FSP_IBUF_HEADER_PAGE_NO,
+            FSP_TRX_SYS_PAGE_NO, RECORD_HEADER_SIZE, UNIV_PAGE_SIZE, XDES_ENTRY_MAX_COUNT,
             XDES_PAGE_COUNT,
         },
-        record::DataValue,
-        redo::{Blocks, LogFile, LogRecordTypes, RedoRecordPayloads},
-        undo::UndoRecord,
+        record::{DataValue, ResultSet},
+        redo::{Blocks, LogFile, LogTypeFilter, RedoRecordPayloads},
+        undo::{UndoRecord, UndoRecordPayloads},
     },
-    util::{colored_extent_number, colored_page_number},
+    meta::{
+        cst::{coll_all, coll_by_charset},
+        def::TableDef,
+    },
+    util::{self, colored_extent_number, colored_page_number},
     Commands,
 };
 
 /// number of element per line
 const N_ELE_PER_LINE: usize = 8;
 
+/// legend of the page map printed by `List --page`
+const PAGE_MAP_LEGEND: [&str; 5] = [
+    "Page: H:FSP_HDR, X:XDES, I:INode, D:Index, G:RTree, S:SDI",
+    "      Y:SYS, T:TRX_SYS, R:RSEG_ARRAY, U:UNDO_LOG",
+    "      B:IBUF_BITMAP, b:BLOB, z:ZBLOB, s:SDI_BLOB",
+    "      F:LOB_FIRST, L:LOB_DATA, i:LOB_INDEX, C:COMPRESSED",
+    "      E:ENCRYPTED, A:Allocated, ?:Unknown",
+];
+
+/// the glyph of the page type in the page map, see PAGE_MAP_LEGEND
+fn page_glyph(page_type: &PageTypes) -> ColoredString {
+    match page_type {
+        PageTypes::FSP_HDR => "H".on_purple(),
+        PageTypes::XDES => "X".on_purple(),
+        PageTypes::SYS => "Y".on_purple(),
+        PageTypes::TRX_SYS => "T".on_purple(),
+        PageTypes::RSEG_ARRAY => "R".on_yellow(),
+        PageTypes::INODE => "I".on_blue(),
+        PageTypes::INDEX => "D".on_cyan(),
+        PageTypes::RTREE => "G".on_cyan(),
+        PageTypes::SDI => "S".on_purple(),
+        PageTypes::IBUF_BITMAP => "B".on_blue(),
+        PageTypes::UNDO_LOG => "U".on_cyan(),
+        PageTypes::BLOB => "b".on_yellow(),
+        PageTypes::ZBLOB | PageTypes::ZBLOB2 => "z".on_yellow(),
+        PageTypes::SDI_BLOB | PageTypes::SDI_ZBLOB => "s".on_purple(),
+        PageTypes::LOB_FIRST => "F".on_yellow(),
+        PageTypes::LOB_DATA => "L".on_yellow(),
+        PageTypes::LOB_INDEX => "i".on_yellow(),
+        PageTypes::COMPRESSED => "C".on_bright_black(),
+        PageTypes::ENCRYPTED | PageTypes::COMPRESSED_AND_ENCRYPTED | PageTypes::ENCRYPTED_RTREE => {
+            "E".on_bright_red()
+        }
+        PageTypes::ALLOCATED => "A".on_green(),
+        _ => "?".on_red(),
+    }
+}
+
+/// dump output formats
+#[derive(Debug, Display, Default, Eq, PartialEq, Clone, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum DumpFormats {
+    /// human readable text
+    #[default]
+    TEXT,
+    /// INSERT statements
+    SQL,
+    /// one JSON object per line
+    JSONL,
+    /// comma separated values
+    CSV,
+}
+
+/// options for dumping index records
+#[derive(Debug)]
+pub struct DumpOptions {
+    /// limit the total row in the dump
+    pub limit: usize,
+
+    /// dump the garbage list
+    pub garbage: bool,
+
+    /// print more information
+    pub verbose: bool,
+
+    /// timezone offset applied on TIMESTAMP values
+    pub tz: Option<FixedOffset>,
+
+    /// only dump the given columns
+    pub columns: Vec<String>,
+
+    /// render unknown type columns as raw hex bytes
+    pub raw_bytes: bool,
+
+    /// output format
+    pub format: DumpFormats,
+
+    /// only dump the records modified by the transaction
+    pub trx_id: Option<u64>,
+
+    /// field delimiter of CSV format
+    pub csv_delim: char,
+
+    /// omit the header line of CSV format
+    pub no_header: bool,
+
+    /// print the raw record body bytes under the decoded fields
+    pub with_raw: bool,
+
+    /// print the field offset table under the decoded fields
+    pub layout: bool,
+
+    /// number of threads decoding the leaf pages concurrently
+    pub jobs: usize,
+
+    /// token rendered for NULL values
+    pub null_as: String,
+
+    /// right-trim the space padding of CHAR columns
+    pub trim: bool,
+}
+
+/// the same defaults as the command line
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self {
+            limit: 0,
+            garbage: false,
+            verbose: false,
+            tz: None,
+            columns: vec![],
+            raw_bytes: false,
+            format: DumpFormats::default(),
+            trx_id: None,
+            csv_delim: ',',
+            no_header: false,
+            with_raw: false,
+            layout: false,
+            jobs: 1,
+            null_as: "NULL".into(),
+            trim: false,
+        }
+    }
+}
+
+impl DumpOptions {
+    /// render the value in TEXT format, NULL is rendered as null_as
+    fn render_value(&self, val: &DataValue) -> String {
+        match val {
+            DataValue::Null => self.null_as.clone(),
+            _ if self.verbose => format!("{:?}", val),
+            _ => val.render(self.raw_bytes),
+        }
+    }
+}
+
+/// B+ tree statistics, accumulated while traversing
+#[derive(Debug, Default)]
+pub struct BTreeStat {
+    /// tree height, max page_level + 1
+    pub height: usize,
+
+    /// page count in each level, map[page_level] => n_pages
+    pub level_pages: BTreeMap<u16, usize>,
+
+    /// total record count of leaf pages
+    pub n_leaf_recs: usize,
+}
+
+/// statistics of dumping the whole leaf chain
+#[derive(Debug, Default)]
+pub struct FullDumpStat {
+    /// number of rows printed
+    pub n_rows: usize,
+
+    /// number of leaf pages decoded
+    pub n_pages: usize,
+}
+
+/// leaf pages decoded by each worker in one batch of the parallel dump
+const DUMP_BATCH_PAGES_PER_JOB: usize = 8;
+
+/// default maximum depth of the B+ tree traversal
+pub const BTREE_MAX_DEPTH: usize = 64;
+
+/// guard of the B+ tree traversal against cyclic node pointers in a corrupted
+/// tree, which would recurse until stack overflow
+#[derive(Debug)]
+pub struct TraverseGuard {
+    /// maximum depth, the root is at depth 0
+    pub max_depth: usize,
+
+    /// visited pages
+    pub visited: HashSet<usize>,
+}
+
+impl TraverseGuard {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            visited: HashSet::new(),
+        }
+    }
+
+    /// check the page before visiting it
+    fn enter(&mut self, page_no: usize, depth: usize) -> Result<(), Error> {
+        if depth >= self.max_depth {
+            return Err(Error::msg(format!(
+                "B+ 树遍历超过最大深度: page_no={}, max_depth={}",
+                page_no, self.max_depth
+            )));
+        }
+        if !self.visited.insert(page_no) {
+            return Err(Error::msg(format!(
+                "B+ 树存在环, 页面被重复访问: page_no={}, depth={}",
+                page_no, depth
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// leaf chain fragmentation of an index
+#[derive(Debug, Default)]
+pub struct FragStat {
+    /// number of leaf pages
+    pub n_leaves: usize,
+
+    /// number of consecutive leaves which are not physically adjacent
+    pub n_scattered: usize,
+}
+
+impl FragStat {
+    pub fn from_chain(chain: &[usize]) -> Self {
+        Self {
+            n_leaves: chain.len(),
+            n_scattered: chain.windows(2).filter(|w| w[1] != w[0] + 1).count(),
+        }
+    }
+
+    /// fragmentation percentage, scattered links of all leaf links
+    pub fn ratio(&self) -> f64 {
+        if self.n_leaves < 2 {
+            return 0.0;
+        }
+        self.n_scattered as f64 * 100.0 / (self.n_leaves - 1) as f64
+    }
+}
+
 #[derive(Debug)]
 pub struct App {
     pub timer: Instant,
     pub input: PathBuf,
+
+    /// the input is the system tablespace, the SDI is not loaded
+    pub system: bool,
 }
 
 impl App {
@@ -41,6 +286,7 @@ impl App {
         Self {
             timer: Instant::now(),
             input,
+            system: false,
         }
     }
 
@@ -52,7 +298,7 @@ impl App {
         debug!("{:?}, {:?}", command, self);
 
         match command {
-            Commands::Info => self.do_info()?,
+            Commands::Info { json } => self.do_info(json)?,
             Commands::List {
                 index,
                 segment,
@@ -87,29 +333,174 @@ impl App {
                 if show_meta {
                     self.do_list_metadata(&mut fact, limit)?
                 }
+                info!("cache stats {:?}", fact.cache_stats());
+            }
+            Commands::Space => self.do_space()?,
+            Commands::Version => {
+                self.do_version()?;
+            }
+            Commands::TrxSys { dblwr } => self.do_trx_sys(dblwr)?,
+            Commands::Rseg { page_no } => self.do_rseg(page_no)?,
+            Commands::UndoSpace => {
+                self.do_undo_space()?;
+            }
+            Commands::DictHeader => self.do_dict_header()?,
+            Commands::Desc {
+                columns_only,
+                verbose,
+            } => self.do_desc(columns_only, verbose)?,
+            Commands::Collation { charset } => self.do_collation(charset)?,
+            Commands::Verify { strict_trailer } => self.do_verify(strict_trailer)?,
+            Commands::Audit => self.do_audit()?,
+            Commands::Frag { root } => {
+                self.do_frag(root)?;
+            }
+            Commands::Lsn => {
+                self.do_lsn()?;
+            }
+            Commands::IndexPages => {
+                self.do_index_pages()?;
+            }
+            Commands::Diff { other } => self.do_diff(other)?,
+            Commands::Hash { logical } => {
+                self.do_hash(logical)?;
             }
-            Commands::Desc => self.do_desc()?,
             Commands::Sdi {
                 table_define,
                 root_segments,
-            } => self.do_sdi_print(table_define, root_segments)?,
-            Commands::View { page_no } => self.do_view_page(page_no)?,
+                compact,
+                index,
+                raw_page,
+                raw_sdi_bytes,
+            } => {
+                if let Some(out) = raw_sdi_bytes {
+                    self.do_sdi_write_raw(out)?;
+                } else if raw_page {
+                    self.do_sdi_raw_page()?;
+                } else {
+                    self.do_sdi_print(table_define, root_segments, compact, index)?;
+                }
+            }
+            Commands::View {
+                page_no,
+                strict,
+                heap,
+                stdin,
+            } => self.do_view_page(page_no, strict, heap, stdin)?,
+            Commands::Record { page_no, heap_no } => self.do_view_record(page_no, heap_no)?,
+            Commands::Trailer => {
+                self.do_trailer()?;
+            }
+            Commands::Fil { page_no } => {
+                self.do_fil(page_no)?;
+            }
+            Commands::Offset { byte_offset } => {
+                self.do_offset(byte_offset)?;
+            }
+            Commands::Extract {
+                page_no,
+                out,
+                range,
+            } => self.do_extract(page_no, out, range)?,
+            Commands::Dump {
+                page_no,
+                btree_root,
+                count: true,
+                ..
+            } => {
+                self.do_dump_count(page_no.or(btree_root))?;
+            }
+            Commands::Dump {
+                page_no: None,
+                btree_root,
+                full: true,
+                limit,
+                garbage,
+                verbose,
+                tz,
+                columns,
+                raw_bytes,
+                format,
+                trx_id,
+                csv_delim,
+                no_header,
+                with_raw,
+                layout,
+                jobs,
+                null_as,
+                trim,
+                ..
+            } => {
+                let opts = DumpOptions {
+                    limit,
+                    garbage,
+                    verbose,
+                    tz,
+                    columns,
+                    raw_bytes,
+                    format,
+                    trx_id,
+                    csv_delim,
+                    no_header,
+                    with_raw,
+                    layout,
+                    jobs,
+                    null_as,
+                    trim,
+                };
+                self.do_dump_full(btree_root, &opts)?;
+            }
             Commands::Dump {
                 page_no,
                 limit,
                 garbage,
                 verbose,
                 btree_root: root,
+                tz,
+                columns,
+                raw_bytes,
+                format,
+                trx_id,
+                csv_delim,
+                no_header,
+                with_raw,
+                layout,
+                jobs,
+                null_as,
+                trim,
+                max_depth,
+                only_leaf,
+                only_internal,
+                ..
             } => match page_no {
-                Some(page_no) => self.do_dump_index_record(page_no, limit, garbage, verbose)?,
+                Some(page_no) => {
+                    let opts = DumpOptions {
+                        limit,
+                        garbage,
+                        verbose,
+                        tz,
+                        columns,
+                        raw_bytes,
+                        format,
+                        trx_id,
+                        csv_delim,
+                        no_header,
+                        with_raw,
+                        layout,
+                        jobs,
+                        null_as,
+                        trim,
+                    };
+                    self.do_dump_index_record(page_no, &opts)?;
+                }
                 None => match root {
                     Some(root_page_no) => {
                         debug!("root_page_no={:?}", root_page_no);
-                        self.do_dump_btree(root_page_no)?;
+                        self.do_dump_btree(root_page_no, max_depth)?;
                     }
                     None => {
                         debug!("dump all index header");
-                        self.do_dump_index_header()?
+                        self.do_dump_index_header(only_leaf, only_internal)?;
                     }
                 },
             },
@@ -118,17 +509,15 @@ impl App {
                 boffset,
                 n_uniq,
             } => {
-                let mut fact = DatafileFactory::from_file(self.input.clone())?;
-                let buf = fact.page_buffer(page_no)?;
-                let addr = 0;
-                let undo_rec = UndoRecord::read(addr, buf, boffset, n_uniq);
-                println!("{:#?}", undo_rec)
+                self.do_undo(page_no, boffset, n_uniq)?;
             }
             Commands::Redo {
                 block_no,
                 dump_log_type,
             } => match dump_log_type {
-                Some(log_type) => self.do_dump_log_records(log_type)?,
+                Some(filter) => {
+                    self.do_dump_log_records(&filter)?;
+                }
                 None => {
                     match block_no {
                         Some(block_no) => self.do_view_block(block_no)?,
@@ -141,20 +530,38 @@ impl App {
         Ok(())
     }
 
-    fn do_info(&self) -> Result<()> {
+    fn do_info(&self, json: bool) -> Result<()> {
         let mut fact = DatafileFactory::from_file(self.input.clone())?;
 
+        if json {
+            colored::control::set_override(false);
+            let info = self.do_info_json(&mut fact)?;
+            println!("{}", serde_json::to_string_pretty(&info)?);
+            return Ok(());
+        }
+
+        let (page_types, compression) = self.page_type_stats(&mut fact)?;
+
         // 基础信息
-        self.do_info_metadata(&mut fact)?;
+        self.do_info_metadata(&mut fact, &compression)?;
+
+        // 表空间信息, 系统表空间没有 SDI
+        if !self.system {
+            self.do_info_tablespace(&mut fact)?;
+        }
 
         // 页面类型统计
-        self.do_info_page_stat(&mut fact)?;
+        self.do_info_page_stat(&page_types);
 
         Ok(())
     }
 
     /// basic meta information
-    fn do_info_metadata(&self, fact: &mut DatafileFactory) -> Result<()> {
+    fn do_info_metadata(
+        &self,
+        fact: &mut DatafileFactory,
+        comp_stats: &BTreeMap<CompressionTypes, usize>,
+    ) -> Result<()> {
         let hdr0 = fact.read_fil_hdr(0)?;
 
         println!("Meta Information:");
@@ -175,28 +582,245 @@ impl App {
             &fact.page_count().to_string().blue()
         );
         println!(
-            "{:>12} => {}",
+            "{:>12} => {} ({})",
             "file_size".green(),
-            fact.file_size.to_string().blue()
+            fact.file_size.to_string().blue(),
+            util::humanize_bytes(fact.file_size).blue()
         );
+        let pages_size = fact.page_count() * UNIV_PAGE_SIZE;
+        if pages_size != fact.file_size {
+            warn!(
+                "文件大小不是页大小的整数倍: file_size={}, page_count*page_size={}",
+                fact.file_size, pages_size
+            );
+            println!(
+                "{:>12} => {} bytes in the partial last page",
+                "truncated".green(),
+                (fact.file_size - pages_size).to_string().red()
+            );
+        }
+
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
+        if fsp_page.page_body.fsp_hdr.fsp_flags.encryption {
+            let info = fsp_page.page_body.encryption_info();
+            if !info.is_valid() {
+                warn!("未知的加密信息 magic: {:?}", &info.magic);
+            }
+            println!(
+                "{:>12} => {}, magic={}, master_key_id={}, server_uuid={}",
+                "encryption".green(),
+                "Y".red(),
+                info.magic.blue(),
+                info.master_key_id.to_string().blue(),
+                info.server_uuid.blue()
+            );
+        } else {
+            println!("{:>12} => {}", "encryption".green(), "N".blue());
+        }
+
+        if !comp_stats.is_empty() {
+            let items = comp_stats
+                .iter()
+                .map(|(algo, n_pages)| format!("{}({} pages)", algo, n_pages))
+                .collect::<Vec<_>>();
+            println!(
+                "{:>12} => {}",
+                "compression".green(),
+                items.join(", ").blue()
+            );
+        }
         Ok(())
     }
 
-    /// page type statistic
-    fn do_info_page_stat(&self, fact: &mut DatafileFactory) -> Result<()> {
+    /// tablespace attributes in SDI
+    fn do_info_tablespace(&self, fact: &mut DatafileFactory) -> Result<()> {
+        let tbs = match fact.load_tablespace_object() {
+            Ok(tbs) => tbs,
+            Err(e) => {
+                warn!("无法加载表空间 SDI 对象: {}", e);
+                return Ok(());
+            }
+        };
+        let dd_object = &tbs.dd_object;
+
+        println!("Tablespace Information:");
+        println!("{:>12} => {}", "name".green(), &dd_object.name.blue());
+        println!("{:>12} => {}", "engine".green(), &dd_object.engine.blue());
+        for (title, strdata) in [
+            ("options", &dd_object.options),
+            ("se_private", &dd_object.se_private_data),
+        ] {
+            let kvs = util::conv_strdata_to_map(strdata);
+            let mut keys = kvs.keys().collect::<Vec<_>>();
+            keys.sort();
+            let items = keys
+                .iter()
+                .map(|k| format!("{}={}", k, kvs[*k]))
+                .collect::<Vec<_>>();
+            println!("{:>12} => {}", title.green(), items.join(", ").blue());
+        }
+        Ok(())
+    }
+
+    /// machine-readable basic information
+    fn do_info_json(&self, fact: &mut DatafileFactory) -> Result<Value> {
+        let hdr0 = fact.read_fil_hdr(0)?;
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
+        let (page_types, compression) = self.page_type_stats(fact)?;
+        let page_types: serde_json::Map<String, Value> = page_types
+            .iter()
+            .map(|(k, v)| (k.to_string(), json!(v)))
+            .collect();
+        let compression: serde_json::Map<String, Value> = compression
+            .iter()
+            .map(|(k, v)| (k.to_string(), json!(v)))
+            .collect();
+        Ok(json!({
+            "server_version": hdr0.server_version(),
+            "space_version": hdr0.space_version(),
+            "space_id": u32::from(&hdr0.space_id),
+            "page_count": fact.page_count(),
+            "file_size": fact.file_size,
+            "encryption": fsp_page.page_body.fsp_hdr.fsp_flags.encryption,
+            "compression": compression,
+            "page_types": page_types,
+        }))
+    }
+
+    /// count the pages by page type, and the page-level compressed pages by
+    /// the compression algorithm in the same scan
+    fn page_type_stats(
+        &self,
+        fact: &mut DatafileFactory,
+    ) -> Result<(
+        BTreeMap<PageTypes, usize>,
+        BTreeMap<CompressionTypes, usize>,
+    )> {
         let mut stats = BTreeMap::new();
+        let mut comp_stats = BTreeMap::new();
+        let pb = util::progress_bar(fact.page_count());
         for page_no in 0..fact.page_count() {
             let hdr = fact.read_fil_hdr(page_no)?;
+            if hdr.page_type == PageTypes::COMPRESSED {
+                let comp_hdr = PageCompressionHeader::new(0, hdr.buf.clone());
+                *comp_stats.entry(comp_hdr.algorithm).or_insert(0) += 1;
+            }
             *stats.entry(hdr.page_type).or_insert(0) += 1;
+            pb.inc(1);
         }
+        pb.finish_and_clear();
+        Ok((stats, comp_stats))
+    }
+
+    /// page type statistic
+    fn do_info_page_stat(&self, stats: &BTreeMap<PageTypes, usize>) {
         println!("PageTypes Statistics:");
-        for entry in &stats {
+        for entry in stats {
             println!(
                 "{:>12} => {}",
                 entry.0.to_string().yellow(),
                 entry.1.to_string().blue()
             );
         }
+    }
+
+    /// version and compatibility report, return the server version
+    fn do_version(&self) -> Result<u32> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
+        let fil_hdr = &fsp_page.fil_hdr;
+        let flags = &fsp_page.page_body.fsp_hdr.fsp_flags;
+        let server_version = fil_hdr.server_version();
+
+        let row_formats = if flags.zip_size() > 0 {
+            "Barracuda (COMPRESSED)"
+        } else if flags.atomic_blobs {
+            "Barracuda (DYNAMIC, COMPRESSED)"
+        } else {
+            "Antelope (REDUNDANT, COMPACT)"
+        };
+        let page_format = if flags.zip_size() > 0 {
+            format!(
+                "{} bytes, compressed to {} bytes",
+                flags.page_size(),
+                flags.zip_size()
+            )
+        } else {
+            format!("{} bytes", flags.page_size())
+        };
+
+        println!("Version Information:");
+        let rows = [
+            ("server", server_version.to_string()),
+            ("space", fil_hdr.space_version().to_string()),
+            (
+                "pre_sdi",
+                (server_version < SDI_META_INFO_MIN_VER).to_string(),
+            ),
+            ("sdi", flags.sdi.to_string()),
+            ("row_formats", row_formats.to_string()),
+            ("page_format", page_format),
+            ("encryption", flags.encryption.to_string()),
+            ("temporary", flags.temporary.to_string()),
+            ("shared", flags.shared.to_string()),
+        ];
+        for (name, value) in rows {
+            println!("{:>12} => {}", name.green(), value.blue());
+        }
+        Ok(server_version)
+    }
+
+    /// tablespace information, fsp_flags, fsp_size, etc.
+    fn do_space(&self) -> Result<()> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
+        let fsp_hdr = &fsp_page.page_body.fsp_hdr;
+        let flags = &fsp_hdr.fsp_flags;
+
+        println!("Space Information:");
+        println!(
+            "{:>16} => {}",
+            "space_id".green(),
+            &fsp_hdr.space_id.to_string().blue()
+        );
+        println!(
+            "{:>16} => {}",
+            "fsp_size".green(),
+            &fsp_hdr.fsp_size.to_string().blue()
+        );
+        println!(
+            "{:>16} => {}",
+            "free_limit".green(),
+            &fsp_hdr.free_limit.to_string().blue()
+        );
+        println!(
+            "{:>16} => {}",
+            "xdes_inited".green(),
+            &fsp_page.page_body.xdes_ent_inited.len().to_string().blue()
+        );
+
+        println!("FSP Flags: 0x{:08x}", fsp_hdr.fsp_flags_bytes);
+        let rows = [
+            ("post_antelope", flags.post_antelope.to_string()),
+            (
+                "zip_ssize",
+                format!("{} ({} bytes)", flags.zip_ssize, flags.zip_size()),
+            ),
+            ("atomic_blobs", flags.atomic_blobs.to_string()),
+            (
+                "page_ssize",
+                format!("{} ({} bytes)", flags.page_ssize, flags.page_size()),
+            ),
+            ("data_dir", flags.data_dir.to_string()),
+            ("shared", flags.shared.to_string()),
+            ("temporary", flags.temporary.to_string()),
+            ("encryption", flags.encryption.to_string()),
+            ("sdi", flags.sdi.to_string()),
+            ("unused", flags.unused.to_string()),
+        ];
+        for (name, value) in rows {
+            println!("{:>16} => {}", name.yellow(), value.blue());
+        }
 
         Ok(())
     }
@@ -285,15 +909,19 @@ impl App {
 
     fn do_list_inode(&self, fact: &mut DatafileFactory, inode: &INodeEntry) -> Result<()> {
         println!(
-            " iseq={}: fseg_id={}, free={}, not-full={}, full={}, frag={}, addr={}",
+            " iseq={}: fseg_id={}, free={}, not-full={}, full={}, frag={}, total={}, addr={}",
             inode.inode_seq.to_string().blue(),
             inode.fseg_id,
             inode.fseg_free.len,
             inode.fseg_not_full.len,
             inode.fseg_full.len,
             inode.fseg_frag_arr.len(),
+            inode.total_pages(),
             inode.addr,
         );
+        for err in inode.check_frag_pages(fact.page_count()) {
+            println!("  {}", err.red());
+        }
         if inode.fseg_free.len > 0 {
             println!("  {}", "fseg_free:".green());
             self.do_walk_xdes_flst(fact, &inode.fseg_free)?;
@@ -356,7 +984,7 @@ impl App {
                 println!();
             }
         }
-        if arr.len() % N_ELE_PER_LINE != 0 {
+        if !arr.len().is_multiple_of(N_ELE_PER_LINE) {
             println!();
         }
         Ok(())
@@ -371,23 +999,20 @@ impl App {
         Ok(())
     }
 
-    fn do_list_ext_free_map(&self, fact: &mut DatafileFactory) -> Result<()> {
+    fn do_list_ext_free_map(&self, fact: &mut DatafileFactory) -> Result<Vec<usize>> {
         println!(" free bitmap: F => free, X => non-free");
         let mut counter = (0, 0);
+        let mut xdes_nos = vec![];
 
-        let mut i = 0;
-        loop {
-            let xdes_page_no = i * EXTENT_PAGE_NUM;
-            if xdes_page_no > fact.page_count() {
-                break;
-            }
-
+        for xdes_page_no in fact.xdes_page_numbers()? {
+            let i = xdes_page_no / EXTENT_PAGE_NUM;
             let xdes_page: BasePage<XDesPageBody> = fact.read_page(xdes_page_no)?;
             let xdes_list = &xdes_page.page_body.xdes_ent_inited;
 
             for xdes in xdes_list {
                 let xdes_no = i * XDES_ENTRY_MAX_COUNT + xdes.xdes_seq;
                 print!(" {:>5} ", colored_extent_number(xdes_no));
+                xdes_nos.push(xdes_no);
 
                 for nth in 0..8 {
                     for shf in 0..8 {
@@ -407,8 +1032,6 @@ impl App {
 
                 println!();
             }
-
-            i += 1;
         }
 
         println!(
@@ -416,20 +1039,15 @@ impl App {
             counter.0, counter.1
         );
 
-        Ok(())
+        Ok(xdes_nos)
     }
 
     fn do_list_ext_clean_map(&self, fact: &mut DatafileFactory) -> Result<()> {
         println!(" clean bitmap: C => clean, D => dirty");
         let mut counter = (0, 0);
 
-        let mut i = 0;
-        loop {
-            let xdes_page_no = i * EXTENT_PAGE_NUM;
-            if xdes_page_no > fact.page_count() {
-                break;
-            }
-
+        for xdes_page_no in fact.xdes_page_numbers()? {
+            let i = xdes_page_no / EXTENT_PAGE_NUM;
             let xdes_page: BasePage<XDesPageBody> = fact.read_page(xdes_page_no)?;
             let xdes_list = &xdes_page.page_body.xdes_ent_inited;
 
@@ -456,8 +1074,6 @@ impl App {
 
                 println!();
             }
-
-            i += 1;
         }
 
         println!(
@@ -468,9 +1084,9 @@ impl App {
     }
 
     fn do_list_pages(&self, fact: &mut DatafileFactory, limit: usize) -> Result<()> {
-        println!("Page: H:FSH_HDR, X:XDES, I:INode, D:Index, S:SDI");
-        println!("      Y:SYS, T:TRX_SYS, R:RSEG_ARRAY, U:UNDO_LOG");
-        println!("      B:IBUF_BITMAP, A:Allocated, ?:Unknown");
+        for line in PAGE_MAP_LEGEND {
+            println!("{}", line);
+        }
 
         let mut page_types_vec = Vec::with_capacity(fact.page_count());
         for page_no in 0..fact.page_count() {
@@ -482,20 +1098,7 @@ impl App {
             if i >= limit {
                 break;
             }
-            let page_type_rept = match page_type {
-                PageTypes::FSP_HDR => "H".on_purple(),
-                PageTypes::XDES => "X".on_purple(),
-                PageTypes::SYS => "Y".on_purple(),
-                PageTypes::TRX_SYS => "T".on_purple(),
-                PageTypes::RSEG_ARRAY => "R".on_yellow(),
-                PageTypes::INODE => "I".on_blue(),
-                PageTypes::INDEX => "D".on_cyan(),
-                PageTypes::SDI => "S".on_purple(),
-                PageTypes::IBUF_BITMAP => "B".on_blue(),
-                PageTypes::UNDO_LOG => "U".on_cyan(),
-                PageTypes::ALLOCATED => "A".on_green(),
-                _ => "?".on_red(),
-            };
+            let page_type_rept = page_glyph(page_type);
             if i % XDES_PAGE_COUNT == 0 {
                 let xdes_no = i / XDES_PAGE_COUNT;
                 print!(" {:>5} ", colored_extent_number(xdes_no));
@@ -513,9 +1116,62 @@ impl App {
         Ok(())
     }
 
-    fn do_desc(&mut self) -> Result<()> {
+    fn do_collation(&self, charset: Option<String>) -> Result<()> {
+        let colls = match &charset {
+            Some(name) => coll_by_charset(name),
+            None => coll_all(),
+        };
+        if colls.is_empty() {
+            return Err(Error::msg(format!("未找到字符集: {:?}", charset)));
+        }
+        for coll in colls {
+            println!(
+                "{:>4}: name={}, charset={}, default={}",
+                coll.id.to_string().blue(),
+                coll.name.magenta(),
+                coll.charset.yellow(),
+                if coll.default {
+                    "Yes".green()
+                } else {
+                    "No".normal()
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn do_desc(&mut self, columns_only: bool, verbose: bool) -> Result<()> {
         let mut fact = DatafileFactory::from_file(self.input.clone())?;
-        let tabdef = fact.load_table_def()?;
+        let tabdef = if columns_only {
+            fact.load_table_columns()?
+        } else {
+            fact.load_table_def()?
+        };
+        let stats = tabdef.instant_stats();
+        println!(
+            "INSTANT: added={}, dropped={}, columns={}/{} (current/physical)",
+            stats.n_added.to_string().yellow(),
+            stats.n_dropped.to_string().red(),
+            stats.n_current.to_string().green(),
+            stats.n_physical.to_string().blue(),
+        );
+        match tabdef.primary_index() {
+            _ if columns_only => {}
+            Some(idx) if tabdef.has_explicit_pk() => {
+                let keys = idx
+                    .elements
+                    .iter()
+                    .filter(|e| !e.hidden)
+                    .map(|e| e.key_part())
+                    .collect::<Vec<_>>();
+                println!(
+                    "PK: {} ({})",
+                    idx.idx_name.magenta(),
+                    keys.join(", ").green()
+                );
+            }
+            _ => println!("PK: {}", "implicit PK (DB_ROW_ID)".yellow()),
+        }
         for col in &tabdef.col_defs {
             println!(
                 "COL{}: name={}, type={}, nullable={}, data_len={}, utf8_def={}",
@@ -526,6 +1182,20 @@ impl App {
                 col.data_len.to_string().cyan(),
                 col.utf8_def.green(),
             );
+            if col.version_added > 0 || col.version_dropped > 0 || col.defval.is_some() {
+                println!(
+                    "    instant: version_added={}, version_dropped={}, default={}",
+                    col.version_added.to_string().yellow(),
+                    col.version_dropped.to_string().red(),
+                    col.defval_str().unwrap_or("NULL".into()).cyan(),
+                );
+            }
+            if let Some(clause) = col.gen_clause() {
+                println!("    generated: {}", clause.yellow());
+            }
+            if !col.comment.is_empty() {
+                println!("    comment: '{}'", col.comment.green());
+            }
             info!("{:?}", col);
         }
 
@@ -539,6 +1209,9 @@ impl App {
                 idx.idx_root,
                 idx.algorithm.to_string().cyan(),
             );
+            if verbose {
+                println!("    se_private: {}", idx.se_private_str().yellow());
+            }
             for e in &idx.elements {
                 let ref_col = &tabdef.col_defs[e.column_opx];
                 println!(
@@ -546,7 +1219,7 @@ impl App {
                     idx.pos,
                     e.pos,
                     e.column_opx.to_string().green(),
-                    ref_col.col_name.magenta(),
+                    e.key_part().magenta(),
                     e.order.to_string().yellow(),
                     e.ele_len.to_string().blue(),
                     e.hidden.to_string().magenta(),
@@ -560,7 +1233,13 @@ impl App {
         Ok(())
     }
 
-    fn do_sdi_print(&self, table_define: bool, root_segments: bool) -> Result<()> {
+    fn do_sdi_print(
+        &self,
+        table_define: bool,
+        root_segments: bool,
+        compact: bool,
+        index: Option<usize>,
+    ) -> Result<()> {
         let mut fact = DatafileFactory::from_file(self.input.clone())?;
 
         if table_define {
@@ -589,43 +1268,859 @@ impl App {
             return Ok(());
         }
 
-        for e in fact.load_sdi_string()?.iter().enumerate() {
+        let sdi_strs = fact.load_sdi_string(compact)?;
+        if let Some(i) = index {
+            match sdi_strs.get(i) {
+                Some(s) => println!("{}", s),
+                None => {
+                    return Err(Error::msg(format!(
+                        "SDI 对象序号溢出: index={}, total={}",
+                        i,
+                        sdi_strs.len()
+                    )))
+                }
+            }
+            return Ok(());
+        }
+
+        for e in sdi_strs.iter().enumerate() {
             println!("[{}] = {}", e.0.to_string().yellow(), e.1);
         }
 
         Ok(())
     }
 
-    fn do_view_page(&self, page_no: usize) -> Result<(), Error> {
+    fn do_sdi_write_raw(&self, out: PathBuf) -> Result<usize> {
         let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let data = fact.load_sdi_raw_json()?;
+        std::fs::write(&out, &data)?;
+        println!(
+            "write raw SDI into {:?}, {} bytes",
+            out,
+            data.len().to_string().blue()
+        );
+        Ok(data.len())
+    }
 
-        let fil_hdr = fact.read_fil_hdr(page_no)?;
-        if !matches!(fil_hdr.page_type, PageTypes::ALLOCATED) {
-            let curr_page_no: usize = fil_hdr.page_no.into();
-            if curr_page_no != page_no {
-                panic!("输入的页码和文件头的页码不一致");
-            }
+    fn do_sdi_raw_page(&self) -> Result<()> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        for (i, (rec_hdr, sdi_hdr, is_extern)) in fact.read_sdi_headers()?.iter().enumerate() {
+            println!(
+                "[{}]: type={}, id={}, uncomp_len={}, comp_len={}, extern={}",
+                i.to_string().yellow(),
+                sdi_hdr.data_type.to_string().magenta(),
+                sdi_hdr.data_id.to_string().blue(),
+                sdi_hdr.uncomp_len.to_string().blue(),
+                sdi_hdr.comp_len.to_string().blue(),
+                if *is_extern { "Y".red() } else { "N".green() }
+            );
+            println!("     sdi_hdr : {:?}", sdi_hdr);
+            println!("     rec_hdr : {:?}", rec_hdr);
+        }
+        Ok(())
+    }
+
+    /// data dictionary header in the system tablespace
+    fn do_dict_header(&self) -> Result<()> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        if !self.system && !fact.is_system()? {
+            return Err(Error::msg("不是系统表空间, 没有数据字典头页"));
         }
+        let fil_hdr = fact.read_fil_hdr(FSP_DICT_HDR_PAGE_NO)?;
+        if fil_hdr.page_type != PageTypes::SYS {
+            return Err(Error::msg(format!(
+                "不是数据字典头页: page_no={}, page_type={}",
+                FSP_DICT_HDR_PAGE_NO, fil_hdr.page_type
+            )));
+        }
+        let dict_page: BasePage<DictHeaderPageBody> = fact.read_page(FSP_DICT_HDR_PAGE_NO)?;
+        println!("{:#?}", dict_page.page_body);
+        Ok(())
+    }
 
-        match fil_hdr.page_type {
-            PageTypes::ALLOCATED => {
-                println!("新分配未使用的页, fil_hdr = {:#?}", fil_hdr);
-            }
-            PageTypes::FSP_HDR => {
-                let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(page_no)?;
-                println!("{:#?}", fsp_page);
+    /// transaction system information in the system tablespace
+    fn do_trx_sys(&self, dblwr: bool) -> Result<()> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let fil_hdr = fact.read_fil_hdr(FSP_TRX_SYS_PAGE_NO)?;
+        if fil_hdr.page_type != PageTypes::TRX_SYS {
+            return Err(Error::msg(format!(
+                "不是 TRX_SYS 页: page_no={}, page_type={}",
+                FSP_TRX_SYS_PAGE_NO, fil_hdr.page_type
+            )));
+        }
+
+        let trx_sys_page: BasePage<TrxSysPageBody> = fact.read_page(FSP_TRX_SYS_PAGE_NO)?;
+        let body = &trx_sys_page.page_body;
+        println!("TRX_SYS: trx_id={}", body.trx_id.to_string().blue());
+
+        println!("Rollback Segment Slots:");
+        for (nth, rseg) in body.rseg_slots.iter().enumerate() {
+            println!(
+                "{:>4}: space_id={}, page_no={}",
+                nth.to_string().magenta(),
+                rseg.space_id.to_string().yellow(),
+                usize::from(rseg.page_no).to_string().cyan()
+            );
+        }
+
+        let log_info = &body.log_info_0;
+        println!(
+            "Binlog Info: log_name={}, log_offset={}",
+            log_info.log_name.green(),
+            log_info.log_offset.to_string().blue()
+        );
+
+        let dbw = &body.dbw_info;
+        println!(
+            "Doublewrite Buffer: blk1_page_no={}, blk2_page_no={}",
+            usize::from(dbw.a_blk1_page_no).to_string().cyan(),
+            usize::from(dbw.a_blk2_page_no).to_string().cyan()
+        );
+        if dblwr {
+            let rows = [
+                ("a_magic", format!("{}", dbw.a_magic_number)),
+                (
+                    "a_blk1_page_no",
+                    usize::from(dbw.a_blk1_page_no).to_string(),
+                ),
+                (
+                    "a_blk2_page_no",
+                    usize::from(dbw.a_blk2_page_no).to_string(),
+                ),
+                ("b_magic", format!("{}", dbw.b_magic_number)),
+                (
+                    "b_blk1_page_no",
+                    usize::from(dbw.b_blk1_page_no).to_string(),
+                ),
+                (
+                    "b_blk2_page_no",
+                    usize::from(dbw.b_blk2_page_no).to_string(),
+                ),
+                (
+                    "space_id_stored",
+                    format!("{}", dbw.space_id_stored_magic_number),
+                ),
+            ];
+            for (name, value) in rows {
+                println!("{:>16} => {}", name.green(), value.blue());
             }
-            PageTypes::XDES => {
-                let xdes_page: BasePage<XDesPageBody> = fact.read_page(page_no)?;
-                println!("{:#?}", xdes_page);
+            if dbw.is_valid() {
+                println!("{:>16} => {}", "magic".green(), "OK".green());
+            } else {
+                println!("{:>16} => {}", "magic".green(), "MISMATCH".red());
             }
-            PageTypes::INODE => {
+        }
+        Ok(())
+    }
+
+    /// rollback segment header page, see trx0rseg.h
+    fn do_rseg(&self, page_no: usize) -> Result<()> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let fil_hdr = fact.read_fil_hdr(page_no)?;
+        let is_other_sys = matches!(fil_hdr.space_id, SpaceId::SystemSpace)
+            && (page_no == FSP_IBUF_HEADER_PAGE_NO || page_no == FSP_DICT_HDR_PAGE_NO);
+        if fil_hdr.page_type != PageTypes::SYS || is_other_sys {
+            return Err(Error::msg(format!(
+                "不是回滚段头页: page_no={}, page_type={}",
+                page_no, fil_hdr.page_type
+            )));
+        }
+
+        let rseg_page: BasePage<RSegHeaderPageBody> = fact.read_page(page_no)?;
+        let body = &rseg_page.page_body;
+        let hdr = &body.rseg_hdr;
+        println!(
+            "RSEG: space_id={}, page_no={}, max_size={}, history_size={}",
+            fil_hdr.space_id.to_string().yellow(),
+            page_no.to_string().cyan(),
+            hdr.max_size.to_string().blue(),
+            hdr.history_size.to_string().blue()
+        );
+        println!("History List: {:?}", &hdr.history_flst);
+        println!("FSeg Header: {:?}", &hdr.fseg_hdr);
+
+        println!("Undo Slots:");
+        for (nth, page_no) in &body.undo_slots {
+            println!(
+                "{:>4}: page_no={}",
+                nth.to_string().magenta(),
+                usize::from(*page_no).to_string().cyan()
+            );
+        }
+        println!(
+            "Total {} undo slots used",
+            body.undo_slots.len().to_string().blue()
+        );
+        Ok(())
+    }
+
+    fn do_undo_space(&self) -> Result<Vec<UndoSegInfo>> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let segs = fact.undo_segments()?;
+        let mut states = BTreeMap::new();
+        for seg in &segs {
+            println!(
+                "rseg={}, rseg_page={}, slot={}, undo_page={}, type={}, state={}, trx_id={}, trx_no={}",
+                seg.rseg_no.to_string().magenta(),
+                seg.rseg_page.to_string().cyan(),
+                seg.slot.to_string().magenta(),
+                seg.undo_page.to_string().cyan(),
+                seg.page_type.to_string().blue(),
+                seg.state.to_string().yellow(),
+                seg.trx_id.map(|v| v.to_string()).unwrap_or("-".into()).green(),
+                seg.trx_no.map(|v| v.to_string()).unwrap_or("-".into()).blue()
+            );
+            *states.entry(seg.state.clone()).or_insert(0) += 1;
+        }
+        println!("Undo Segments Statistics:");
+        for (state, count) in &states {
+            println!(
+                "{:>18} => {}",
+                state.to_string().yellow(),
+                count.to_string().blue()
+            );
+        }
+        let active = segs
+            .iter()
+            .filter(|seg| seg.state == UndoPageStates::TRX_UNDO_ACTIVE)
+            .filter_map(|seg| seg.trx_id)
+            .collect::<Vec<_>>();
+        println!(
+            "Total {} undo segments, {} active transactions {:?}",
+            segs.len().to_string().blue(),
+            active.len().to_string().red(),
+            active
+        );
+        Ok(segs)
+    }
+
+    fn do_frag(&self, root: Option<usize>) -> Result<Vec<FragStat>> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let roots = match root {
+            Some(page_no) => vec![("-".to_string(), page_no)],
+            None => fact
+                .load_table_def()?
+                .idx_defs
+                .iter()
+                .map(|idx| (idx.idx_name.clone(), idx.idx_root as usize))
+                .collect(),
+        };
+
+        let mut ret = vec![];
+        for (name, page_no) in roots {
+            let stat = FragStat::from_chain(&fact.leaf_chain(page_no)?);
+            println!(
+                "index={}, root={}, n_leaves={}, n_scattered={}, fragmentation={}",
+                name.magenta(),
+                page_no.to_string().blue(),
+                stat.n_leaves.to_string().cyan(),
+                stat.n_scattered.to_string().yellow(),
+                format!("{:.2}%", stat.ratio()).red()
+            );
+            ret.push(stat);
+        }
+        debug!("cache stats {:?}", fact.cache_stats());
+        Ok(ret)
+    }
+
+    /// approximate last modified LSN of each index, return the table max LSN
+    fn do_lsn(&self) -> Result<u64> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let lsns = fact.index_max_lsns()?;
+        let tabdef = fact.load_table_def()?;
+        for idx in &tabdef.idx_defs {
+            let lsn = lsns.get(&(idx.idx_id as u64)).copied().unwrap_or(0);
+            println!(
+                "index={}, idx_id={}, max_lsn={}",
+                idx.idx_name.magenta(),
+                idx.idx_id.to_string().blue(),
+                lsn.to_string().yellow()
+            );
+        }
+        let table_lsn = lsns.values().copied().max().unwrap_or(0);
+        println!(
+            "table={}, max_lsn={}",
+            tabdef.tab_name.magenta(),
+            table_lsn.to_string().red()
+        );
+        Ok(table_lsn)
+    }
+
+    fn do_index_pages(&self) -> Result<BTreeMap<u64, Vec<usize>>> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let index_pages = fact.index_pages()?;
+        let tabdef = fact.load_table_def()?;
+        for (idx_id, pages) in &index_pages {
+            let idx_name = tabdef
+                .idx_defs
+                .iter()
+                .find(|idx| idx.idx_id as u64 == *idx_id)
+                .map(|idx| idx.idx_name.clone())
+                .unwrap_or("<unknown>".into());
+            println!(
+                "index={}, idx_id={}, n_pages={}, pages={}",
+                idx_name.magenta(),
+                idx_id.to_string().blue(),
+                pages.len().to_string().yellow(),
+                util::fmt_page_ranges(pages).green()
+            );
+        }
+        Ok(index_pages)
+    }
+
+    fn do_verify(&self, strict_trailer: bool) -> Result<()> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let mut failed = vec![];
+
+        let space_id = fact.read_fil_hdr(0)?.space_id;
+        let mismatched = fact.verify_space_ids()?;
+        for (page_no, page_space_id) in &mismatched {
+            println!(
+                "page={}, space_id={}, {}",
+                colored_page_number(*page_no),
+                page_space_id.to_string().blue(),
+                format!("expect space_id={}", space_id).red()
+            );
+        }
+        if mismatched.is_empty() {
+            println!("space_id={}, {}", space_id.to_string().blue(), "OK".green());
+        } else {
+            failed.push(format!(
+                "表空间ID不一致: {} 个页不属于表空间 {}",
+                mismatched.len(),
+                space_id
+            ));
+        }
+
+        let corrupted = fact.verify_checksums()?;
+        for (page_no, field1, field2) in &corrupted {
+            println!(
+                "page={}, checksum={}, {}",
+                colored_page_number(*page_no),
+                format!("0x{:08x}", field1).blue(),
+                format!("trailer checksum=0x{:08x}", field2).red()
+            );
+        }
+        if corrupted.is_empty() {
+            println!("checksum={}", "OK".green());
+        } else {
+            failed.push(format!("页校验和错误: {} 个页校验失败", corrupted.len()));
+        }
+
+        let errs = fact.verify_fsp_size()?;
+        for err in &errs {
+            println!("{}", err.red());
+        }
+        if errs.is_empty() {
+            println!(
+                "page_count={}, {}",
+                fact.page_count().to_string().blue(),
+                "OK".green()
+            );
+        } else {
+            failed.push(format!("FSP 头校验失败: {} 处不一致", errs.len()));
+        }
+
+        if strict_trailer {
+            let torn = fact.verify_trailer_lsns()?;
+            for (page_no, lsn, lsn_low32bit) in &torn {
+                println!(
+                    "page={}, lsn={}, {}",
+                    colored_page_number(*page_no),
+                    format!("0x{:08x}", lsn & 0xffffffff).blue(),
+                    format!("trailer lsn_low32bit=0x{:08x}", lsn_low32bit).red()
+                );
+            }
+            if torn.is_empty() {
+                println!("trailer_lsn={}", "OK".green());
+            } else {
+                failed.push(format!(
+                    "页尾 LSN 不一致: {} 个页可能是半写的页",
+                    torn.len()
+                ));
+            }
+        }
+
+        // 系统表空间没有 SDI
+        if !self.system {
+            let mut n_errs = 0;
+            for (name, root, err) in fact.verify_index_roots()? {
+                match err {
+                    Some(msg) => {
+                        n_errs += 1;
+                        println!(
+                            "index={}, root={}, {}",
+                            name.magenta(),
+                            root.to_string().blue(),
+                            msg.red()
+                        );
+                    }
+                    None => println!(
+                        "index={}, root={}, {}",
+                        name.magenta(),
+                        root.to_string().blue(),
+                        "OK".green()
+                    ),
+                }
+            }
+            if n_errs > 0 {
+                failed.push(format!("索引根页校验失败: {} 个索引不一致", n_errs));
+            }
+        }
+
+        if !failed.is_empty() {
+            return Err(Error::msg(failed.join("; ")));
+        }
+        Ok(())
+    }
+
+    fn do_audit(&self) -> Result<()> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let audit = fact.audit_xdes()?;
+        debug!("cache stats {:?}", fact.cache_stats());
+        for (page_no, xdes_no) in &audit.conflicts {
+            println!(
+                "page={}, extent={}, {}",
+                colored_page_number(*page_no),
+                colored_extent_number(*xdes_no),
+                "free in XDES but used by segment".red()
+            );
+        }
+        for (inode_page_no, fseg_id, page_no) in &audit.bad_frags {
+            println!(
+                "page={}, inode_page={}, fseg_id={}, {}",
+                page_no.to_string().red(),
+                colored_page_number(*inode_page_no),
+                fseg_id.to_string().blue(),
+                "fragment page beyond the tablespace".red()
+            );
+        }
+        if !audit.is_ok() {
+            return Err(Error::msg(format!(
+                "XDES 分配位图不一致: {} 个页冲突, {} 个碎片页越界",
+                audit.conflicts.len(),
+                audit.bad_frags.len()
+            )));
+        }
+        println!("{}", "OK".green());
+        Ok(())
+    }
+
+    fn do_hash(&self, logical: bool) -> Result<Vec<u64>> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let mut ret = vec![];
+        for page_no in 0..fact.page_count() {
+            let hash = fact.page_hash(page_no, logical)?;
+            println!("{} {}", page_no, format!("{:016x}", hash).yellow());
+            ret.push(hash);
+        }
+        Ok(ret)
+    }
+
+    fn do_diff(&self, other: PathBuf) -> Result<()> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let mut other_fact = DatafileFactory::from_file(other)?;
+        if fact.page_count() != other_fact.page_count() {
+            warn!(
+                "页数不一致: this={}, other={}",
+                fact.page_count(),
+                other_fact.page_count()
+            );
+        }
+
+        let diffs = fact.diff_pages(&mut other_fact)?;
+        let fmt_side = |side: &Option<(PageTypes, u64)>| match side {
+            Some((page_type, lsn)) => format!("{}@{}", page_type, lsn),
+            None => "-".into(),
+        };
+        for diff in &diffs {
+            println!(
+                "page_no={}, this={}, other={}, lsn_delta={}",
+                colored_page_number(diff.page_no),
+                fmt_side(&diff.this).yellow(),
+                fmt_side(&diff.other).cyan(),
+                diff.lsn_delta()
+                    .map(|d| d.to_string())
+                    .unwrap_or("-".into())
+                    .magenta()
+            );
+        }
+        println!("Total {} pages differ", diffs.len().to_string().red());
+        Ok(())
+    }
+
+    fn do_view_heap(
+        body: &IndexPageBody,
+        layout: Option<(Arc<TableDef>, usize)>,
+    ) -> Result<(), Error> {
+        let heap = body.heap_records(layout);
+        let n_heap = body.idx_hdr.heap_count();
+        for (rec_hdr, is_free) in &heap {
+            println!(
+                "heap_no={}, addr={}, status={}, n_owned={}, next={}, {}",
+                rec_hdr.heap_no.to_string().yellow(),
+                (rec_hdr.addr + RECORD_HEADER_SIZE).to_string().blue(),
+                rec_hdr.rec_status.to_string().magenta(),
+                rec_hdr.n_owned,
+                rec_hdr.next_addr(),
+                if *is_free {
+                    "FREE".red()
+                } else {
+                    "USED".green()
+                }
+            );
+        }
+        if heap.len() != n_heap {
+            warn!("堆记录数不一致: found={}, n_heap={}", heap.len(), n_heap);
+        }
+        println!(
+            "Total {} heap records, n_heap={}",
+            heap.len().to_string().blue(),
+            n_heap.to_string().blue()
+        );
+        Ok(())
+    }
+
+    /// dump the trailer of every page, only the last 8 bytes of page are read
+    fn do_trailer(&self) -> Result<usize, Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let page_count = fact.page_count();
+        for page_no in 0..page_count {
+            let fil_trl = fact.read_fil_trl(page_no)?;
+            println!(
+                "{:>7} => check_sum={}, lsn_low32bit={}",
+                colored_page_number(page_no),
+                format!("0x{:08x}", fil_trl.check_sum).yellow(),
+                format!("0x{:08x}", fil_trl.lsn_low32bit).blue(),
+            );
+        }
+        Ok(page_count)
+    }
+
+    /// print the FIL header and trailer only, safe on the corrupted page body
+    fn do_fil(&self, page_no: usize) -> Result<(FilePageHeader, FilePageTrailer), Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let fil_hdr = fact.read_fil_hdr(page_no)?;
+        let fil_trl = fact.read_fil_trl(page_no)?;
+        println!("FIL Header:");
+        for (key, val) in [
+            ("check_sum", format!("0x{:08x}", fil_hdr.check_sum)),
+            ("page_no", fil_hdr.page_no.to_string()),
+            ("prev_page", fil_hdr.prev_page.to_string()),
+            ("next_page", fil_hdr.next_page.to_string()),
+            ("lsn", fil_hdr.lsn.to_string()),
+            ("page_type", fil_hdr.page_type.to_string()),
+            ("flush_lsn", fil_hdr.flush_lsn.to_string()),
+            ("space_id", fil_hdr.space_id.to_string()),
+        ] {
+            println!("{:>12} => {}", key.green(), val.blue());
+        }
+        println!("FIL Trailer:");
+        for (key, val) in [
+            ("check_sum", format!("0x{:08x}", fil_trl.check_sum)),
+            ("lsn_low32", format!("0x{:08x}", fil_trl.lsn_low32bit)),
+        ] {
+            println!("{:>12} => {}", key.green(), val.blue());
+        }
+        Ok((fil_hdr, fil_trl))
+    }
+
+    /// map the byte offset to the page number and the offset inside the page
+    fn do_offset(&self, byte_offset: usize) -> Result<(usize, usize), Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        if byte_offset >= fact.file_size {
+            return Err(Error::msg(format!(
+                "偏移量超出文件大小: byte_offset={}, file_size={}",
+                byte_offset, fact.file_size
+            )));
+        }
+
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
+        let page_size = fsp_page.page_body.fsp_hdr.fsp_flags.page_size();
+        let page_no = byte_offset / page_size;
+        let page_off = byte_offset % page_size;
+        println!(
+            "{:>12} => {}",
+            "page_size".green(),
+            page_size.to_string().blue()
+        );
+        println!(
+            "{:>12} => {}",
+            "page_no".green(),
+            colored_page_number(page_no)
+        );
+        println!(
+            "{:>12} => {}",
+            "page_off".green(),
+            page_off.to_string().blue()
+        );
+
+        if page_size != UNIV_PAGE_SIZE {
+            warn!("不支持的页大小, 跳过读取文件头: page_size={}", page_size);
+        } else if page_no < fact.page_count() {
+            let fil_hdr = fact.read_fil_hdr(page_no)?;
+            println!(
+                "{:>12} => {}",
+                "page_type".green(),
+                fil_hdr.page_type.to_string().yellow()
+            );
+        }
+        Ok((page_no, page_off))
+    }
+
+    /// print the undo record, and the GTID of the undo log header on the page
+    fn do_undo(&self, page_no: usize, boffset: usize, n_uniq: usize) -> Result<Option<String>> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let buf = fact.page_buffer(page_no)?;
+        let addr = 0;
+        let undo_rec = UndoRecord::read(addr, buf, boffset, n_uniq);
+        println!("{:#?}", undo_rec);
+
+        let undo_page: BasePage<UndoLogPageBody> = fact.read_page(page_no)?;
+        let log_hdr = match &undo_page.page_body.undo_log {
+            Some(undo_log) => &undo_log.undo_log_hdr,
+            None => return Ok(None),
+        };
+        for (name, gtid) in [("gtid", &log_hdr.gtid), ("xa_gtid", &log_hdr.xa_gtid)] {
+            if let Some(gtid) = gtid {
+                println!("{:>12} => {}", name.green(), gtid.blue());
+            }
+        }
+        Ok(log_hdr.gtid.clone())
+    }
+
+    fn do_view_record(&self, page_no: usize, heap_no: u16) -> Result<(), Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let fil_hdr = fact.read_fil_hdr(page_no)?;
+        if fil_hdr.page_type != PageTypes::INDEX {
+            return Err(Error::msg(format!(
+                "不支持的页类型: {:?}",
+                fil_hdr.page_type
+            )));
+        }
+
+        let (rec, tuple) = fact.unpack_heap_record(page_no, heap_no)?;
+        println!(
+            "heap_no={}, addr={}, status={}, deleted={}",
+            rec.rec_hdr.heap_no.to_string().yellow(),
+            rec.addr.to_string().blue(),
+            rec.rec_hdr.rec_status.to_string().magenta(),
+            rec.rec_hdr.is_deleted()
+        );
+        println!("rec_hdr : {:?}", &rec.rec_hdr);
+        for ent in &tuple {
+            println!(
+                "{:>12} => {}",
+                &ent.0.to_string().magenta(),
+                ent.1.render(false)
+            );
+        }
+        Ok(())
+    }
+
+    fn do_extract(
+        &self,
+        page_no: Option<usize>,
+        out: PathBuf,
+        range: Option<(usize, usize)>,
+    ) -> Result<()> {
+        let (from, to) = match (page_no, range) {
+            (Some(page_no), None) => (page_no, page_no),
+            (None, Some(range)) => range,
+            _ => return Err(Error::msg("需要指定页码或者页码范围其中之一")),
+        };
+
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let buf = fact.pages_buffer(from, to)?;
+        std::fs::write(&out, buf.as_ref())?;
+        println!(
+            "extract pages {}-{} into {:?}, {} bytes",
+            colored_page_number(from),
+            colored_page_number(to),
+            out,
+            buf.len().to_string().blue()
+        );
+        Ok(())
+    }
+
+    fn do_view_page(
+        &self,
+        page_no: usize,
+        strict: bool,
+        heap: bool,
+        stdin: bool,
+    ) -> Result<(), Error> {
+        // the page read from stdin is the only page of the factory
+        if stdin {
+            let mut buf = vec![0; UNIV_PAGE_SIZE];
+            std::io::stdin().lock().read_exact(&mut buf)?;
+            let fact = DatafileFactory::from_bytes(Bytes::from(buf))?;
+            self.do_view_fact_page(fact, 0, page_no, strict, heap)
+        } else {
+            let fact = DatafileFactory::from_file(self.input.clone())?;
+            self.do_view_fact_page(fact, page_no, page_no, strict, heap)
+        }
+    }
+
+    /// view the page of the factory, the page number in the FIL header is
+    /// checked against expect_page_no
+    fn do_view_fact_page(
+        &self,
+        mut fact: DatafileFactory,
+        page_no: usize,
+        expect_page_no: usize,
+        strict: bool,
+        heap: bool,
+    ) -> Result<(), Error> {
+        if strict {
+            let errs = fact.verify_page_type(page_no)?;
+            if !errs.is_empty() {
+                for err in &errs {
+                    println!("{}", err.red());
+                }
+                return Err(Error::msg(format!(
+                    "页面类型校验失败: page_no={}, {} 处不一致",
+                    page_no,
+                    errs.len()
+                )));
+            }
+        }
+
+        let mut fil_hdr = fact.read_fil_hdr(page_no)?;
+        if !matches!(fil_hdr.page_type, PageTypes::ALLOCATED) {
+            let curr_page_no: usize = fil_hdr.page_no.into();
+            if curr_page_no != expect_page_no {
+                return Err(Error::msg(format!(
+                    "输入的页码和文件头的页码不一致: page_no={}, fil_hdr.page_no={}",
+                    expect_page_no, curr_page_no
+                )));
+            }
+        }
+
+        // page-level compression, the page is inflated by read_page
+        if fil_hdr.page_type == PageTypes::COMPRESSED {
+            let comp_hdr = PageCompressionHeader::new(0, fact.fil_hdr_buffer(page_no)?);
+            println!("{:#?}", comp_hdr);
+            fil_hdr.page_type = comp_hdr.original_type;
+        }
+
+        match fil_hdr.page_type {
+            PageTypes::ALLOCATED => {
+                println!("新分配未使用的页, fil_hdr = {:#?}", fil_hdr);
+            }
+            PageTypes::FSP_HDR => {
+                let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(page_no)?;
+                println!("{:#?}", fsp_page);
+            }
+            PageTypes::XDES => {
+                let xdes_page: BasePage<XDesPageBody> = fact.read_page(page_no)?;
+                println!("{:#?}", xdes_page);
+            }
+            PageTypes::INODE => {
                 let inode_page: BasePage<INodePageBody> = fact.read_page(page_no)?;
                 println!("{:#?}", inode_page);
             }
             PageTypes::INDEX => {
                 let index_page: BasePage<IndexPageBody> = fact.read_page(page_no)?;
+                if heap {
+                    let layout = fact.page_index_layout(&index_page).unwrap_or_else(|err| {
+                        warn!("无法解析索引布局, 只列出链表中的记录: {:?}", err);
+                        None
+                    });
+                    return Self::do_view_heap(&index_page.page_body, layout);
+                }
                 println!("{:#?}", index_page);
+                let idx_hdr = &index_page.page_body.idx_hdr;
+                println!(
+                    "Insert Pattern: {}, direction={}, last_insert={}",
+                    idx_hdr.insert_pattern().green(),
+                    idx_hdr.page_direction.to_string().yellow(),
+                    idx_hdr.page_last_insert.to_string().blue()
+                );
+
+                // resolve the owned record key if the table definition is available
+                let keys: Vec<(u16, String)> = match fact.unpack_index_page(page_no, false) {
+                    Ok(rs) => rs
+                        .records
+                        .iter()
+                        .zip(rs.tuples.iter())
+                        .filter_map(|(rec, tuple)| {
+                            tuple
+                                .first()
+                                .map(|ent| (rec.rec_hdr.heap_no, format!("{:?}", ent.1)))
+                        })
+                        .collect(),
+                    Err(err) => {
+                        warn!("无法解析记录键值: {:?}", err);
+                        vec![]
+                    }
+                };
+
+                println!("Page Directory Slots:");
+                let slots = &index_page.page_body.page_dirs;
+                for (i, rec_hdr) in index_page.page_body.dir_slot_records().iter().enumerate() {
+                    let key = keys
+                        .iter()
+                        .find(|k| k.0 == rec_hdr.heap_no)
+                        .map(|k| k.1.clone())
+                        .unwrap_or(rec_hdr.rec_status.to_string());
+                    println!(
+                        "slot[{}]: offset={}, heap_no={}, n_owned={}, key={}",
+                        i.to_string().magenta(),
+                        slots[i].to_string().blue(),
+                        rec_hdr.heap_no.to_string().yellow(),
+                        rec_hdr.n_owned,
+                        key.green()
+                    );
+                }
+            }
+            PageTypes::RTREE => {
+                let rtree_page: BasePage<RtreePageBody> = fact.read_page(page_no)?;
+                println!("{:#?}", rtree_page.fil_hdr);
+                println!("{:#?}", rtree_page.page_body.index.idx_hdr);
+                println!("MBR Records:");
+                for (i, rec) in rtree_page.page_body.mbr_records.iter().enumerate() {
+                    let mbr = &rec.mbr;
+                    println!(
+                        "rec[{}]: addr={}, mbr=[{}, {}, {}, {}], child={}",
+                        i.to_string().magenta(),
+                        rec.addr.to_string().yellow(),
+                        mbr.xmin,
+                        mbr.xmax,
+                        mbr.ymin,
+                        mbr.ymax,
+                        rec.child_page
+                            .map(|p| p.to_string())
+                            .unwrap_or("-".into())
+                            .blue()
+                    );
+                }
+            }
+            PageTypes::IBUF_FREE_LIST => {
+                let ibuf_page: BasePage<IbufFreeListPageBody> = fact.read_page(page_no)?;
+                println!("{:#?}", ibuf_page.fil_hdr);
+                println!("{:#?}", ibuf_page.page_body.idx_hdr);
+                let node = &ibuf_page.page_body.free_list_node;
+                println!(
+                    "Free List: prev={}, next={}",
+                    node.prev.page_no.to_string().yellow(),
+                    node.next.page_no.to_string().yellow()
+                );
+                match &ibuf_page.page_body.index {
+                    Some(index) => {
+                        println!("Records:");
+                        for (i, rec_hdr) in index.data_rec_hdrs.iter().enumerate() {
+                            println!(
+                                "rec[{}]: heap_no={}, status={}, next={}",
+                                i.to_string().magenta(),
+                                rec_hdr.heap_no.to_string().yellow(),
+                                rec_hdr.rec_status,
+                                rec_hdr.next_addr().to_string().blue()
+                            );
+                        }
+                    }
+                    None => println!("{}", "空闲页面中没有可解析的记录".yellow()),
+                }
             }
             PageTypes::SDI => {
                 let sdi_page: BasePage<SdiPageBody> = fact.read_page(page_no)?;
@@ -644,6 +2139,18 @@ impl App {
                 let undo_log_page: BasePage<UndoLogPageBody> = fact.read_page(page_no)?;
                 println!("{:#?}", undo_log_page);
             }
+            PageTypes::LOB_FIRST => {
+                let lob_first_page: BasePage<LobFirstPageBody> = fact.read_page(page_no)?;
+                println!("{:#?}", lob_first_page);
+            }
+            PageTypes::LOB_INDEX => {
+                let lob_index_page: BasePage<LobIndexPageBody> = fact.read_page(page_no)?;
+                println!("{:#?}", lob_index_page);
+            }
+            PageTypes::LOB_DATA => {
+                let lob_data_page: BasePage<LobDataPageBody> = fact.read_page(page_no)?;
+                println!("{:#?}", lob_data_page);
+            }
             PageTypes::SYS => {
                 let fil_hdr = fact.read_fil_hdr(page_no)?;
                 match fil_hdr.space_id {
@@ -651,7 +2158,17 @@ impl App {
                         let rsa_hdr_page: BasePage<RSegHeaderPageBody> = fact.read_page(page_no)?;
                         println!("{:#?}", rsa_hdr_page);
                     }
-                    SpaceId::SystemSpace if page_no != FSP_DICT_HDR_PAGE_NO => {
+                    SpaceId::TempSpace(_) => {
+                        return Err(Error::msg(format!(
+                            "不支持会话临时表空间的 SYS 页面: page_no={}, space_id={}",
+                            page_no, fil_hdr.space_id
+                        )));
+                    }
+                    SpaceId::SystemSpace if page_no == FSP_DICT_HDR_PAGE_NO => {
+                        let dict_page: BasePage<DictHeaderPageBody> = fact.read_page(page_no)?;
+                        println!("{:#?}", dict_page);
+                    }
+                    SpaceId::SystemSpace => {
                         let rsa_hdr_page: BasePage<RSegHeaderPageBody> = fact.read_page(page_no)?;
                         println!("{:#?}", rsa_hdr_page);
                     }
@@ -665,295 +2182,2369 @@ impl App {
         Ok(())
     }
 
-    fn do_dump_btree(&self, root_page_no: usize) -> Result<(), Error> {
-        let mut fact = DatafileFactory::from_file(self.input.clone())?;
-        let fil_hdr = fact.read_fil_hdr(root_page_no)?;
-        let page_type = fil_hdr.page_type;
-        if page_type != PageTypes::INDEX {
-            return Err(Error::msg(format!("不支持的页类型: {:?}", page_type)));
-        }
-
-        Self::do_traverse_index(&mut fact, root_page_no, 0)?;
+    fn do_dump_btree(&self, root_page_no: usize, max_depth: usize) -> Result<BTreeStat, Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let fil_hdr = fact.read_fil_hdr(root_page_no)?;
+        let page_type = fil_hdr.page_type;
+        if page_type != PageTypes::INDEX && page_type != PageTypes::RTREE {
+            return Err(Error::msg(format!("不支持的页类型: {:?}", page_type)));
+        }
+
+        let mut stat = BTreeStat::default();
+        let mut guard = TraverseGuard::new(max_depth);
+        Self::do_traverse_index(&mut fact, root_page_no, 0, &mut stat, &mut guard)?;
+
+        println!("B+ Tree Summary:");
+        println!(
+            "{:>12} => {}",
+            "height".green(),
+            stat.height.to_string().blue()
+        );
+        for (level, n_pages) in stat.level_pages.iter().rev() {
+            println!(
+                "{:>12} => {} pages",
+                format!("level {}", level).yellow(),
+                n_pages.to_string().blue()
+            );
+        }
+        println!(
+            "{:>12} => {}",
+            "n_leaf_recs".green(),
+            stat.n_leaf_recs.to_string().blue()
+        );
+        debug!("cache stats {:?}", fact.cache_stats());
+
+        Ok(stat)
+    }
+
+    fn do_traverse_index(
+        fact: &mut DatafileFactory,
+        page_no: usize,
+        indent: usize,
+        stat: &mut BTreeStat,
+        guard: &mut TraverseGuard,
+    ) -> Result<()> {
+        guard.enter(page_no, indent)?;
+        if fact.read_fil_hdr(page_no)?.page_type == PageTypes::RTREE {
+            return Self::do_traverse_rtree(fact, page_no, indent, stat, guard);
+        }
+        let curr: BasePage<IndexPageBody> = fact.read_page(page_no)?;
+        let idx_hdr = &curr.page_body.idx_hdr;
+        stat.height = stat.height.max(idx_hdr.page_level as usize + 1);
+        *stat.level_pages.entry(idx_hdr.page_level).or_insert(0) += 1;
+        if idx_hdr.page_level == 0 {
+            stat.n_leaf_recs += idx_hdr.page_n_recs as usize;
+        }
+        for _ in 0..indent {
+            print!("  ");
+        }
+        let result_set = fact.unpack_index_page(page_no, false)?;
+
+        match result_set.tuples.first() {
+            Some(first) => {
+                let sep_idx = first
+                    .iter()
+                    .position(|(_, val)| {
+                        matches!(
+                            val,
+                            DataValue::PageNo(_) | DataValue::TrxId(_) | DataValue::RbPtr(_)
+                        )
+                    })
+                    .unwrap_or(first.len());
+                let key = &first[0..sep_idx];
+                println!(
+                    "{}: level={}, min_key={:?}, n_rec={}",
+                    colored_page_number(page_no),
+                    idx_hdr.page_level,
+                    &key,
+                    idx_hdr.page_n_recs,
+                );
+            }
+            None => {
+                // empty table, the root page has no user record
+                println!(
+                    "{}: level={}, n_rec={}",
+                    colored_page_number(page_no),
+                    idx_hdr.page_level,
+                    idx_hdr.page_n_recs,
+                );
+            }
+        }
+
+        if idx_hdr.page_level > 0 {
+            for node_ptr in result_set.node_ptrs()? {
+                Self::do_traverse_index(fact, node_ptr.child_page, indent + 1, stat, guard)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn do_traverse_rtree(
+        fact: &mut DatafileFactory,
+        page_no: usize,
+        indent: usize,
+        stat: &mut BTreeStat,
+        guard: &mut TraverseGuard,
+    ) -> Result<()> {
+        let curr: BasePage<RtreePageBody> = fact.read_page(page_no)?;
+        let idx_hdr = &curr.page_body.index.idx_hdr;
+        stat.height = stat.height.max(idx_hdr.page_level as usize + 1);
+        *stat.level_pages.entry(idx_hdr.page_level).or_insert(0) += 1;
+        if idx_hdr.page_level == 0 {
+            stat.n_leaf_recs += idx_hdr.page_n_recs as usize;
+        }
+        for _ in 0..indent {
+            print!("  ");
+        }
+        println!(
+            "{}: level={}, first_mbr={:?}, n_rec={}",
+            colored_page_number(page_no),
+            idx_hdr.page_level,
+            curr.page_body.mbr_records.first().map(|rec| &rec.mbr),
+            idx_hdr.page_n_recs,
+        );
+
+        for rec in &curr.page_body.mbr_records {
+            if let Some(PageNumber::Page(child)) = rec.child_page {
+                Self::do_traverse_index(fact, child as usize, indent + 1, stat, guard)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// print [level,n_recs] of INDEX pages in grid, the filtered out INDEX
+    /// pages are left blank, return the number of INDEX pages shown
+    fn do_dump_index_header(&self, only_leaf: bool, only_internal: bool) -> Result<usize, Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let pb = util::progress_bar(fact.page_count());
+        let mut line = String::new();
+        let mut n_shown = 0;
+        for page_no in 0..fact.page_count() {
+            let fil_hdr = fact.read_fil_hdr(page_no)?;
+
+            if page_no % 8 == 0 {
+                line += &format!("{:>7} ", colored_page_number(page_no));
+            }
+
+            if fil_hdr.page_type == PageTypes::INDEX {
+                let idx_hdr = fact.read_idx_hdr(page_no)?;
+                let is_leaf = idx_hdr.page_level == 0;
+                if (only_leaf && !is_leaf) || (only_internal && is_leaf) {
+                    line += &format!("[{:>6}]", "");
+                } else {
+                    line += &format!("[{:>1},{:>4}]", idx_hdr.page_level, idx_hdr.page_n_recs);
+                    n_shown += 1;
+                }
+            } else {
+                line += &format!("[{:>6.6}]", fil_hdr.page_type);
+            }
+
+            if page_no % 8 == 7 {
+                // 暂停进度条, 避免与输出交错
+                pb.suspend(|| println!("{}", line));
+                line.clear();
+            } else {
+                line += " ";
+            }
+            pb.inc(1);
+        }
+        pb.finish_and_clear();
+        if !line.is_empty() {
+            println!("{}", line);
+        }
+        debug!("cache stats {:?}", fact.cache_stats());
+        Ok(n_shown)
+    }
+
+    /// count user records of the leaf chain, start from the given page or the
+    /// clustered index root in SDI
+    fn do_dump_count(&self, page_no: Option<usize>) -> Result<usize> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let page_no = match page_no {
+            Some(page_no) => page_no,
+            None => fact.clustered_root()?,
+        };
+
+        let n_recs = fact.count_leaf_records(page_no)?;
+        debug!("cache stats {:?}", fact.cache_stats());
+        println!(
+            "{}: n_recs={}",
+            colored_page_number(page_no),
+            n_recs.to_string().blue()
+        );
+        Ok(n_recs)
+    }
+
+    fn do_dump_index_record(&mut self, page_no: usize, opts: &DumpOptions) -> Result<usize, Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let result_set = Self::load_dump_records(&mut fact, page_no, opts)?;
+        debug!("cache stats {:?}", fact.cache_stats());
+        self.print_dump_records(&result_set, opts)
+    }
+
+    /// dump the user records of the whole leaf chain, the clustered index is
+    /// used if the root page is not given. The leaf pages are decoded and
+    /// printed one batch at a time, and stop once opts.limit rows are printed
+    fn do_dump_full(&mut self, root: Option<usize>, opts: &DumpOptions) -> Result<FullDumpStat> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let root = match root {
+            Some(root) => root,
+            None => fact.clustered_root()?,
+        };
+
+        let leaves = fact.leaf_chain(root)?;
+        let batch_size = if opts.jobs > 1 {
+            opts.jobs * DUMP_BATCH_PAGES_PER_JOB
+        } else {
+            1
+        };
+        let tabdef = if opts.jobs > 1 {
+            Some(fact.load_table_def()?)
+        } else {
+            None
+        };
+
+        let mut stat = FullDumpStat::default();
+        let mut header = !opts.no_header;
+        let mut truncated = false;
+        'batches: for batch in leaves.chunks(batch_size) {
+            let parts = match &tabdef {
+                Some(tabdef) => self.load_dump_records_parallel(tabdef.clone(), batch, opts)?,
+                None => vec![Self::load_dump_records(&mut fact, batch[0], opts)?],
+            };
+            stat.n_pages += batch.len();
+            let n_parts = parts.len();
+            for (i, part) in parts.iter().enumerate() {
+                let n_rows = min(part.tuples.len(), opts.limit - stat.n_rows);
+                self.print_dump_rows(part, opts, stat.n_rows, n_rows, header)?;
+                header = false;
+                stat.n_rows += n_rows;
+                if stat.n_rows >= opts.limit {
+                    truncated = n_rows < part.tuples.len()
+                        || i + 1 < n_parts
+                        || stat.n_pages < leaves.len();
+                    break 'batches;
+                }
+            }
+        }
+
+        if truncated && opts.format == DumpFormats::TEXT {
+            println!(
+                "ONLY dump {} rows, use `--limit num' to dump more",
+                stat.n_rows
+            )
+        }
+        debug!("cache stats {:?}", fact.cache_stats());
+        Ok(stat)
+    }
+
+    /// split the leaf pages into contiguous chunks, each worker opens its own
+    /// file handle and shares the table definition, the parts are returned in
+    /// leaf order
+    fn load_dump_records_parallel(
+        &self,
+        tabdef: Arc<TableDef>,
+        leaves: &[usize],
+        opts: &DumpOptions,
+    ) -> Result<Vec<ResultSet>, Error> {
+        let chunk_size = leaves.len().div_ceil(opts.jobs);
+
+        std::thread::scope(|scope| {
+            let workers = leaves
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    let tabdef = tabdef.clone();
+                    let input = self.input.clone();
+                    scope.spawn(move || -> Result<Vec<ResultSet>, Error> {
+                        let mut fact = DatafileFactory::from_file(input)?;
+                        fact.tabdef_cache = Some(tabdef);
+                        let parts = chunk
+                            .iter()
+                            .map(|&page_no| Self::load_dump_records(&mut fact, page_no, opts))
+                            .collect();
+                        debug!("worker cache stats {:?}", fact.cache_stats());
+                        parts
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let mut parts = Vec::with_capacity(leaves.len());
+            for worker in workers {
+                let part = worker
+                    .join()
+                    .map_err(|_| Error::msg("解码线程异常退出"))??;
+                parts.extend(part);
+            }
+            Ok(parts)
+        })
+    }
+
+    /// unpack the records of the page for dumping, the garbage records are
+    /// appended after the user records with `--garbage`
+    fn load_dump_records(
+        fact: &mut DatafileFactory,
+        page_no: usize,
+        opts: &DumpOptions,
+    ) -> Result<ResultSet, Error> {
+        let fil_hdr = fact.read_fil_hdr(page_no)?;
+        let page_type = fil_hdr.page_type;
+        if page_type != PageTypes::INDEX {
+            return Err(Error::msg(format!("不支持的页类型: {:?}", page_type)));
+        }
+
+        let mut result_set = if opts.garbage {
+            fact.unpack_all_records(page_no)?
+        } else {
+            fact.unpack_index_page(page_no, false)?
+        };
+        if let Some(trx_id) = opts.trx_id {
+            result_set.filter_trx_id(trx_id);
+        }
+        if !opts.columns.is_empty() {
+            result_set.project(&opts.columns)?;
+        }
+        if opts.trim {
+            result_set.trim_char_columns();
+        }
+        if let Some(tz) = &opts.tz {
+            for tuple in result_set.tuples.iter_mut() {
+                for ent in tuple.iter_mut() {
+                    ent.1 = ent.1.with_tz(tz);
+                }
+            }
+        }
+        Ok(result_set)
+    }
+
+    fn print_dump_records(
+        &self,
+        result_set: &ResultSet,
+        opts: &DumpOptions,
+    ) -> Result<usize, Error> {
+        let n_dump_rows = min(result_set.tuples.len(), opts.limit);
+        self.print_dump_rows(result_set, opts, 0, n_dump_rows, !opts.no_header)?;
+
+        if opts.format == DumpFormats::TEXT && n_dump_rows < result_set.tuples.len() {
+            println!(
+                "ONLY dump {} of {} rows, use `--limit num' to dump more",
+                n_dump_rows,
+                result_set.tuples.len()
+            )
+        }
+
+        Ok(n_dump_rows)
+    }
+
+    /// print the first n_rows of the result set, the rows are numbered after
+    /// seq_base, the CSV header line is printed if header is set
+    fn print_dump_rows(
+        &self,
+        result_set: &ResultSet,
+        opts: &DumpOptions,
+        seq_base: usize,
+        n_rows: usize,
+        header: bool,
+    ) -> Result<(), Error> {
+        if opts.format == DumpFormats::JSONL {
+            let mut out = std::io::stdout().lock();
+            for line in result_set.to_json_lines().take(n_rows) {
+                writeln!(out, "{}", line)?;
+                out.flush()?;
+            }
+            return Ok(());
+        }
+        if opts.format == DumpFormats::CSV {
+            let n_hdr = if header { 1 } else { 0 };
+            let lines = result_set.to_csv(opts.csv_delim, header, &opts.null_as);
+            for line in lines.iter().take(n_hdr + n_rows) {
+                println!("{}", line);
+            }
+            return Ok(());
+        }
+        if opts.format == DumpFormats::SQL {
+            let tab_name = &result_set.tabdef.tab_name;
+            let stmts = result_set.to_sql_inserts(tab_name);
+            for (i, stmt) in stmts[..n_rows].iter().enumerate() {
+                if result_set.garbage[i] && (i == 0 || !result_set.garbage[i - 1]) {
+                    println!("-- garbage records");
+                }
+                println!("{}", stmt);
+            }
+            return Ok(());
+        }
+        for (i, tuple) in result_set.tuples[..n_rows].iter().enumerate() {
+            let rec = &result_set.records[i];
+            let seq = seq_base + i + 1;
+
+            // 打印分割线
+            for _ in 0..40 {
+                print!("*");
+            }
+            if result_set.garbage[i] {
+                print!(" Row {} of {} (GARBAGE) ", seq, &result_set.idx_name);
+            } else {
+                print!(" Row {} of {} ", seq, &result_set.idx_name);
+            }
+            for _ in 0..40 {
+                print!("*");
+            }
+            println!();
+
+            info!(
+                "seq={}, addr=@{}, {:?}",
+                seq.to_string().red(),
+                &rec.addr.to_string().yellow(),
+                &rec,
+            );
+
+            // 打印一些关键信息
+            if opts.verbose {
+                if let Some(row_ver) = rec.rec_hdr.row_version() {
+                    println!("version : {}", row_ver.to_string().yellow());
+                }
+                if let Some(n_ins_col) = rec.rec_hdr.n_instant_col() {
+                    println!("instant : n_fields={}", n_ins_col.to_string().yellow());
+                }
+                println!("row_info: {:?}", &rec.row_info);
+                println!("rec_hdr : {:?}", &rec.rec_hdr);
+                println!("rec_stat: {:?}", &rec.calc_layout());
+            }
+
+            // 打印记录
+            for ent in tuple {
+                let val = opts.render_value(&ent.1);
+                println!("{:>12} => {}", &ent.0.to_string().magenta(), val);
+            }
+            if opts.with_raw {
+                println!(
+                    "{:>12} => {}",
+                    "RAW".cyan(),
+                    util::raw_bytes_str(&rec.raw_body())
+                );
+            }
+            if opts.layout {
+                println!(
+                    "{:>12} => {:>5} {:>5} {:>5}",
+                    "LAYOUT".cyan(),
+                    "start",
+                    "end",
+                    "len"
+                );
+                for fld in rec.field_layout() {
+                    let note = if !fld.phy_exist {
+                        "DEFAULT"
+                    } else if fld.isnull {
+                        "NULL"
+                    } else {
+                        ""
+                    };
+                    println!(
+                        "{:>12} => {:>5} {:>5} {:>5} {}",
+                        fld.col_name.magenta(),
+                        fld.start,
+                        fld.end,
+                        fld.end - fld.start,
+                        note.yellow()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn do_dump_log_records(&self, filter: &LogTypeFilter) -> Result<usize, Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let buf = fact.file_buffer()?;
+        let log_file = LogFile::new(0, buf);
+
+        // the records are addressed by the offset in the record stream
+        let mut n_dumped = 0;
+        for rec in log_file.records() {
+            if !filter.matches(&rec.log_rec_hdr.log_rec_type) {
+                continue;
+            }
+            n_dumped += 1;
+            println!("{:>6} => {:?}", rec.addr, &rec.log_rec_hdr);
+            match &rec.redo_rec_data {
+                RedoRecordPayloads::TableMeta(meta) => {
+                    if let Some(auto_inc) = meta.auto_inc {
+                        println!(
+                            "{:>6}    table_id={}, auto_inc={}",
+                            "",
+                            meta.table_id.to_string().blue(),
+                            auto_inc.to_string().green()
+                        );
+                    }
+                }
+                RedoRecordPayloads::UndoInsert(ins) => {
+                    let undo_hdr = &ins.undo_rec.undo_rec_hdr;
+                    match &ins.undo_rec.undo_rec_data {
+                        UndoRecordPayloads::Insert(r) => println!(
+                            "{:>6}    undo_type={}, undo_no={}, table_id={}",
+                            "",
+                            undo_hdr.type_info.to_string().blue(),
+                            r.undo_no.to_string().yellow(),
+                            r.table_id.to_string().green()
+                        ),
+                        _ => println!(
+                            "{:>6}    undo_type={}, data_len={}",
+                            "",
+                            undo_hdr.type_info.to_string().blue(),
+                            ins.data_len.to_string().green()
+                        ),
+                    }
+                }
+                RedoRecordPayloads::PageCreate(page_kind) => {
+                    println!(
+                        "{:>6}    created {} page",
+                        "",
+                        page_kind.to_string().yellow()
+                    );
+                }
+                RedoRecordPayloads::RecSecIndexDeleteMark(mark) => {
+                    println!(
+                        "{:>6}    value={}, offset={}",
+                        "",
+                        mark.value.to_string().blue(),
+                        mark.offset.to_string().green()
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        Ok(n_dumped)
+    }
+
+    fn do_view_block(&self, block_no: usize) -> Result<(), Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let block = fact.read_block(block_no)?;
+        match block {
+            Blocks::FileHeader(log_fil_hdr) => {
+                println!("{:#?}", log_fil_hdr);
+            }
+            Blocks::Block(log_block) => {
+                if !log_block.checksum_ok {
+                    warn!("日志块校验和不一致: block_no={}", block_no);
+                }
+                println!("{:#?}", log_block);
+            }
+            Blocks::Checkpoint(checkpoint) => {
+                println!("{:#?}", checkpoint);
+            }
+            Blocks::Unused => {
+                println!("Unused block");
+            }
+        }
+        Ok(())
+    }
+
+    fn do_view_log_file(&self) -> Result<(), Error> {
+        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+        let buf = fact.file_buffer()?;
+        let log_file = LogFile::new(0, buf);
+
+        let mut stats = BTreeMap::new();
+        let mut unknown = HashSet::new();
+        for rec in log_file.records() {
+            if matches!(rec.redo_rec_data, RedoRecordPayloads::Unknown) {
+                unknown.insert(rec.log_rec_hdr.log_rec_type.clone());
+            }
+            *stats.entry(rec.log_rec_hdr.log_rec_type).or_insert(0) += 1;
+        }
+
+        println!("RedoRecordTypes Statistics:");
+        for entry in &stats {
+            println!(
+                "{:>28} {}> {}",
+                entry.0.to_string().yellow(),
+                if !unknown.contains(entry.0) {
+                    "=".to_string().green()
+                } else {
+                    "=".to_string().red()
+                },
+                entry.1.to_string().blue()
+            );
+        }
+
+        if !unknown.is_empty() {
+            warn!("Unknown type: {}", &format!("{:?}", &unknown).yellow());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+    use crate::{
+        ibd::page::{
+            Mbr, FIL_HEADER_SIZE, FSP_FIRST_RSEG_PAGE_NO, INF_PAGE_BYTE_OFF, LOB_DATA_PAGE_BEGIN,
+            LOB_FIRST_DATA_BEGIN, LOB_FIRST_INDEX_BEGIN, LOB_FIRST_INDEX_ENTRY_COUNT,
+            LOB_INDEX_ENTRY_SIZE, PAGE_NONE, RTREE_MBR_SIZE, SUP_PAGE_BYTE_OFF, TRX_RSEG_N_SLOTS,
+            TRX_RSEG_SLOT_SIZE, TRX_SYS_DOUBLEWRITE, TRX_SYS_DOUBLEWRITE_MAGIC_N,
+            TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED_N, TRX_SYS_N_RSEGS,
+        },
+        ibd::record::RecordStatus,
+        ibd::redo::LogRecordTypes,
+        meta::def::{ColumnDef, IndexElementDef, IndexTypes, InstantStats},
+        sdi::record::{DataDictIndexElement, EntryTypes},
+        util,
+    };
+
+    const IBD_01: &str = "data/departments.ibd";
+    const IBD_02: &str = "data/dept_manager.ibd";
+    const REDO_1: &str = "data/redo_block_01";
+    const UNDO_1: &str = "data/undo_log_01";
+
+    #[test]
+    fn info_datafile() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app.run(Commands::Info { json: false }).is_ok());
+    }
+
+    #[test]
+    fn info_json() -> Result<()> {
+        util::init_unit_test();
+        let app = App::new(PathBuf::from(IBD_01));
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let text = serde_json::to_string(&app.do_info_json(&mut fact)?)?;
+        let info: Value = serde_json::from_str(&text)?;
+        let file_size = std::fs::metadata(IBD_01)?.len() as usize;
+        assert_eq!(info["page_count"], json!(file_size / UNIV_PAGE_SIZE));
+        assert_eq!(info["file_size"], json!(file_size));
+        assert_eq!(info["space_id"], json!(3));
+        let n_pages: u64 = info["page_types"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|v| v.as_u64().unwrap())
+            .sum();
+        assert_eq!(n_pages as usize, file_size / UNIV_PAGE_SIZE);
+        Ok(())
+    }
+
+    #[test]
+    fn info_compression_stats() -> Result<()> {
+        util::init_unit_test();
+        // only the compression header is counted, the data is not inflated
+        let data = util::patch_fixture(IBD_01, |data| {
+            let page = &mut data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+            page[24..26].copy_from_slice(&(PageTypes::COMPRESSED as u16).to_be_bytes());
+            page[26] = 1;
+            page[27] = 2;
+        });
+        let app = App::new(PathBuf::from(IBD_01));
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data))?;
+        let (page_types, compression) = app.page_type_stats(&mut fact)?;
+        assert_eq!(page_types[&PageTypes::COMPRESSED], 1);
+        assert_eq!(compression.len(), 1);
+        assert_eq!(compression[&CompressionTypes::LZ4], 1);
+
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        assert!(app.page_type_stats(&mut fact)?.1.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn space_summary() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_02));
+        assert!(app.run(Commands::Space).is_ok());
+    }
+
+    /// no system tablespace fixture, build a minimal one with a TRX_SYS page 5
+    /// and a data dictionary header page 7
+    fn build_trx_sys_data(dblwr_magic: u32) -> Vec<u8> {
+        let mut data = vec![0u8; UNIV_PAGE_SIZE * (FSP_DICT_HDR_PAGE_NO + 1)];
+        data[24..26].copy_from_slice(&(PageTypes::FSP_HDR as u16).to_be_bytes());
+
+        let page = &mut data[UNIV_PAGE_SIZE * FSP_DICT_HDR_PAGE_NO..];
+        page[4..8].copy_from_slice(&(FSP_DICT_HDR_PAGE_NO as u32).to_be_bytes());
+        page[24..26].copy_from_slice(&(PageTypes::SYS as u16).to_be_bytes());
+        page[38 + 8..38 + 16].copy_from_slice(&1066u64.to_be_bytes());
+        for (i, root) in [8u32, 9, 10, 11, 12].iter().enumerate() {
+            page[38 + 32 + 4 * i..38 + 36 + 4 * i].copy_from_slice(&root.to_be_bytes());
+        }
+
+        let page = &mut data[UNIV_PAGE_SIZE * FSP_TRX_SYS_PAGE_NO..];
+        page[4..8].copy_from_slice(&(FSP_TRX_SYS_PAGE_NO as u32).to_be_bytes());
+        page[24..26].copy_from_slice(&(PageTypes::TRX_SYS as u16).to_be_bytes());
+        page[38..46].copy_from_slice(&1024u64.to_be_bytes());
+        for nth in 0..TRX_SYS_N_RSEGS {
+            let off = 38 + 18 + 8 * nth;
+            let page_no: u32 = if nth == 0 { 6 } else { PAGE_NONE };
+            page[off + 4..off + 8].copy_from_slice(&page_no.to_be_bytes());
+        }
+        let page = &mut data[UNIV_PAGE_SIZE * FSP_FIRST_RSEG_PAGE_NO..];
+        page[4..8].copy_from_slice(&(FSP_FIRST_RSEG_PAGE_NO as u32).to_be_bytes());
+        page[24..26].copy_from_slice(&(PageTypes::SYS as u16).to_be_bytes());
+        page[38..42].copy_from_slice(&0xfffffffeu32.to_be_bytes());
+        page[38 + 4..38 + 8].copy_from_slice(&3u32.to_be_bytes());
+        for nth in 0..TRX_RSEG_N_SLOTS {
+            let off = 38 + 34 + TRX_RSEG_SLOT_SIZE * nth;
+            let page_no: u32 = match nth {
+                0 => 10,
+                3 => 12,
+                _ => PAGE_NONE,
+            };
+            page[off..off + 4].copy_from_slice(&page_no.to_be_bytes());
+        }
+
+        let page = &mut data[UNIV_PAGE_SIZE * FSP_TRX_SYS_PAGE_NO..];
+        let dbw = TRX_SYS_DOUBLEWRITE;
+        page[dbw + 10..dbw + 14].copy_from_slice(&dblwr_magic.to_be_bytes());
+        page[dbw + 14..dbw + 18].copy_from_slice(&64u32.to_be_bytes());
+        page[dbw + 18..dbw + 22].copy_from_slice(&128u32.to_be_bytes());
+        page[dbw + 22..dbw + 26].copy_from_slice(&dblwr_magic.to_be_bytes());
+        page[dbw + 26..dbw + 30].copy_from_slice(&192u32.to_be_bytes());
+        page[dbw + 30..dbw + 34].copy_from_slice(&256u32.to_be_bytes());
+        page[dbw + 34..dbw + 38]
+            .copy_from_slice(&TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED_N.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn trx_sys_slots() -> Result<()> {
+        util::init_unit_test();
+        let data = build_trx_sys_data(TRX_SYS_DOUBLEWRITE_MAGIC_N);
+        let file = util::TempFile::new("trx_sys", &data)?;
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let trx_sys_page: BasePage<TrxSysPageBody> = fact.read_page(FSP_TRX_SYS_PAGE_NO)?;
+        assert_eq!(trx_sys_page.page_body.trx_id, 1024);
+        assert_eq!(trx_sys_page.page_body.rseg_slots.len(), 1);
+
+        let mut app = App::new(file.path.clone());
+        assert!(app.run(Commands::TrxSys { dblwr: false }).is_ok());
+
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app.run(Commands::TrxSys { dblwr: false }).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn rseg_header_page() -> Result<()> {
+        util::init_unit_test();
+        let data = build_trx_sys_data(TRX_SYS_DOUBLEWRITE_MAGIC_N);
+        let file = util::TempFile::new("rseg", &data)?;
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let rseg_page: BasePage<RSegHeaderPageBody> = fact.read_page(FSP_FIRST_RSEG_PAGE_NO)?;
+        assert_eq!(rseg_page.page_body.rseg_hdr.history_size, 3);
+        assert_eq!(
+            rseg_page.page_body.undo_slots,
+            vec![(0, PageNumber::Page(10)), (3, PageNumber::Page(12))]
+        );
+
+        let mut app = App::new(file.path.clone());
+        assert!(app
+            .run(Commands::Rseg {
+                page_no: FSP_FIRST_RSEG_PAGE_NO
+            })
+            .is_ok());
+        for page_no in [FSP_TRX_SYS_PAGE_NO, FSP_DICT_HDR_PAGE_NO] {
+            assert!(app.run(Commands::Rseg { page_no }).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn system_tablespace() -> Result<()> {
+        util::init_unit_test();
+        let data = build_trx_sys_data(TRX_SYS_DOUBLEWRITE_MAGIC_N);
+        let file = util::TempFile::new("ibdata1", &data)?;
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert!(fact.is_system()?);
+        assert!(fact.load_table_def().is_err());
+
+        let dict_page: BasePage<DictHeaderPageBody> = fact.read_page(FSP_DICT_HDR_PAGE_NO)?;
+        assert_eq!(dict_page.page_body.table_id, 1066);
+        assert_eq!(dict_page.page_body.fields_root, PageNumber::Page(12));
+
+        let mut app = App::new(file.path.clone());
+        app.system = true;
+        assert!(app.run(Commands::Info { json: false }).is_ok());
+        assert!(app.run(Commands::DictHeader).is_ok());
+        assert!(app
+            .run(Commands::View {
+                page_no: FSP_DICT_HDR_PAGE_NO,
+                strict: false,
+                heap: false,
+                stdin: false,
+            })
+            .is_ok());
+
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app.run(Commands::DictHeader).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn trx_sys_dblwr() -> Result<()> {
+        util::init_unit_test();
+        let data = build_trx_sys_data(TRX_SYS_DOUBLEWRITE_MAGIC_N);
+        let file = util::TempFile::new("trx_sys_dblwr", &data)?;
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let trx_sys_page: BasePage<TrxSysPageBody> = fact.read_page(FSP_TRX_SYS_PAGE_NO)?;
+        let dbw = &trx_sys_page.page_body.dbw_info;
+        assert!(dbw.is_valid());
+        assert_eq!(dbw.a_blk1_page_no, PageNumber::Page(64));
+        assert_eq!(dbw.b_blk2_page_no, PageNumber::Page(256));
+        let mut app = App::new(file.path.clone());
+        assert!(app.run(Commands::TrxSys { dblwr: true }).is_ok());
+
+        // corrupted magic number is reported, not panic
+        let data = build_trx_sys_data(0xdead);
+        let file = util::TempFile::new("trx_sys_bad_dblwr", &data)?;
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let trx_sys_page: BasePage<TrxSysPageBody> = fact.read_page(FSP_TRX_SYS_PAGE_NO)?;
+        assert!(!trx_sys_page.page_body.dbw_info.is_valid());
+        let mut app = App::new(file.path.clone());
+        assert!(app.run(Commands::TrxSys { dblwr: true }).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn dump_index_header_no_tty() {
+        util::init_unit_test();
+        let app = App::new(PathBuf::from(IBD_02));
+        assert!(app.do_dump_index_header(false, false).is_ok());
+    }
+
+    #[test]
+    fn dump_btree_stat() {
+        util::init_unit_test();
+        let app = App::new(PathBuf::from(IBD_01));
+        let stat = app.do_dump_btree(4, BTREE_MAX_DEPTH).unwrap();
+        assert_eq!(stat.height, 1);
+        assert_eq!(stat.level_pages.get(&0), Some(&1));
+        assert_eq!(stat.n_leaf_recs, 9);
+    }
+
+    #[test]
+    fn list_datafile() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app
+            .run(Commands::List {
+                index: false,
+                segment: false,
+                extent: false,
+                page: false,
+                all: true,
+                limit: 32,
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn list_page_map_legend() {
+        util::init_unit_test();
+        use std::collections::BTreeSet;
+
+        let legend: BTreeSet<String> = PAGE_MAP_LEGEND
+            .iter()
+            .flat_map(|line| line.trim_start_matches("Page:").split(','))
+            .map(|item| item.trim().split(':').next().unwrap().to_string())
+            .collect();
+        let glyphs: BTreeSet<String> = (0..=29)
+            .chain(17853..=17855)
+            .chain([u16::MAX])
+            .map(|val: u16| (*page_glyph(&PageTypes::from(val))).to_string())
+            .collect();
+        assert_eq!(legend, glyphs);
+        assert_eq!(&*page_glyph(&PageTypes::RTREE), "G");
+        assert_eq!(&*page_glyph(&PageTypes::LOB_INDEX), "i");
+        assert_eq!(&*page_glyph(&PageTypes::LOB_DATA), "L");
+    }
+
+    #[test]
+    fn list_extents_multi_xdes() -> Result<()> {
+        use std::io::{Seek, SeekFrom};
+        util::init_unit_test();
+        let page0 = std::fs::read(IBD_01)?[..UNIV_PAGE_SIZE].to_vec();
+        let mut xdes = page0.clone();
+        xdes[4..8].copy_from_slice(&(EXTENT_PAGE_NUM as u32).to_be_bytes());
+        xdes[24..26].copy_from_slice(&(PageTypes::XDES as u16).to_be_bytes());
+
+        // sparse file, the second XDES page is at page EXTENT_PAGE_NUM
+        let tmp = util::TempFile::new("xdes", &page0)?;
+        let mut file = std::fs::OpenOptions::new().write(true).open(&tmp.path)?;
+        file.set_len((UNIV_PAGE_SIZE * EXTENT_PAGE_NUM) as u64)?;
+        let mut fact = DatafileFactory::from_file(tmp.path.clone())?;
+        assert_eq!(fact.xdes_page_numbers()?, vec![0]);
+
+        file.seek(SeekFrom::Start((UNIV_PAGE_SIZE * EXTENT_PAGE_NUM) as u64))?;
+        file.write_all(&xdes)?;
+        let mut fact = DatafileFactory::from_file(tmp.path.clone())?;
+        assert_eq!(fact.xdes_page_numbers()?, vec![0, EXTENT_PAGE_NUM]);
+
+        let app = App::new(tmp.path.clone());
+        let xdes_nos = app.do_list_ext_free_map(&mut fact)?;
+        let n = xdes_nos.len() / 2;
+        assert!(n > 0);
+        for (a, b) in xdes_nos[..n].iter().zip(&xdes_nos[n..]) {
+            assert_eq!(*b, a + XDES_ENTRY_MAX_COUNT);
+        }
+        assert!(app.do_list_ext_clean_map(&mut fact).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn view_fsp_hdr_page() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app
+            .run(Commands::View {
+                page_no: 0,
+                strict: false,
+                heap: false,
+                stdin: false,
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn view_inode_page() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app
+            .run(Commands::View {
+                page_no: 2,
+                strict: false,
+                heap: false,
+                stdin: false,
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn view_index_page() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app
+            .run(Commands::View {
+                page_no: 4,
+                strict: true,
+                heap: false,
+                stdin: false,
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn view_stdin_page_mismatch() -> Result<()> {
+        util::init_unit_test();
+        // the piped page 4 is viewed as page 5, reported as error, not panic
+        let data = std::fs::read(IBD_01)?;
+        let page = Bytes::copy_from_slice(&data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5]);
+        let app = App::new(PathBuf::from(IBD_01));
+        let fact = DatafileFactory::from_bytes(page.clone())?;
+        let err = app.do_view_fact_page(fact, 0, 5, false, false).unwrap_err();
+        assert!(err.to_string().contains("page_no=5, fil_hdr.page_no=4"));
+
+        let fact = DatafileFactory::from_bytes(page)?;
+        assert!(app.do_view_fact_page(fact, 0, 4, false, false).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn view_sdi_page() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app
+            .run(Commands::View {
+                page_no: 3,
+                strict: false,
+                heap: false,
+                stdin: false,
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn sdi_raw_page() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let headers = fact.read_sdi_headers()?;
+        let table = headers
+            .iter()
+            .find(|h| h.1.data_type == EntryTypes::Table)
+            .unwrap();
+        assert!(table.1.comp_len > 0 && table.1.uncomp_len > table.1.comp_len);
+        assert!(!table.2);
+
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app
+            .run(Commands::Sdi {
+                table_define: false,
+                root_segments: false,
+                compact: false,
+                index: None,
+                raw_page: true,
+                raw_sdi_bytes: None,
+            })
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn sdi_write_raw_json() -> Result<()> {
+        util::init_unit_test();
+        let out = util::TempFile::new("raw_sdi", &[])?;
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app
+            .run(Commands::Sdi {
+                table_define: false,
+                root_segments: false,
+                compact: false,
+                index: None,
+                raw_page: false,
+                raw_sdi_bytes: Some(out.path.clone()),
+            })
+            .is_ok());
+
+        let data = std::fs::read_to_string(&out.path)?;
+        assert!(!data.is_empty());
+        let value: Value = serde_json::from_str(&data)?;
+        let items = value.as_array().unwrap();
+        assert_eq!(items[0], "ibd2sdi");
+        let types = items[1..]
+            .iter()
+            .map(|item| item["type"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        assert!(types.contains(&(EntryTypes::Table as u64)));
+        assert!(types.contains(&(EntryTypes::Tablespace as u64)));
+        assert_eq!(items[1]["object"]["dd_object_type"], "Table");
+        Ok(())
+    }
+
+    #[test]
+    fn desc_instant_default() -> Result<()> {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from("data/tb_row_version_1.ibd"));
+        assert!(app
+            .run(Commands::Desc {
+                columns_only: false,
+                verbose: false,
+            })
+            .is_ok());
+        let mut fact = DatafileFactory::from_file(PathBuf::from("data/tb_row_version_1.ibd"))?;
+        let tabdef = fact.load_table_def()?;
+        let c5 = tabdef.col_defs.iter().find(|c| c.col_name == "c5").unwrap();
+        assert_eq!(c5.version_added, 1);
+        assert_eq!(c5.defval_str(), Some("'c5_def    '".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn version_report() -> Result<()> {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert_eq!(app.do_version()?, 80037);
+        assert!(app.run(Commands::Version).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn collation_by_charset() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_02));
+        assert!(app
+            .run(Commands::Collation {
+                charset: Some("utf8mb4".into())
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn desc_columns_only() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_02))?;
+        let coldefs = fact.load_table_columns()?;
+        assert!(fact.tabdef_cache.is_none());
+        assert!(coldefs.idx_defs.is_empty());
+
+        let tabdef = fact.load_table_def()?;
+        assert_eq!(coldefs.col_defs.len(), tabdef.col_defs.len());
+        assert!(coldefs
+            .col_defs
+            .iter()
+            .zip(&tabdef.col_defs)
+            .all(|(a, b)| a.col_name == b.col_name));
+        assert!(Arc::ptr_eq(&fact.load_table_columns()?, &tabdef));
+
+        let mut app = App::new(PathBuf::from(IBD_02));
+        assert!(app
+            .run(Commands::Desc {
+                columns_only: true,
+                verbose: false,
+            })
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn desc_index_se_private() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let tabdef = fact.load_table_def()?;
+        for idx in &tabdef.idx_defs {
+            assert_eq!(idx.se_private["root"], idx.idx_root.to_string());
+            assert_eq!(idx.se_private["id"], idx.idx_id.to_string());
+            assert!(idx.se_private.contains_key("trx_id"));
+            assert!(idx
+                .se_private_str()
+                .contains(&format!("root={}", idx.idx_root)));
+        }
+
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app
+            .run(Commands::Desc {
+                columns_only: false,
+                verbose: true,
+            })
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn desc_index_type_names() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_02))?;
+        let tabdef = fact.load_table_def()?;
+        let pk = &tabdef.idx_defs[0];
+        assert_eq!(pk.idx_type.to_string(), "PRIMARY");
+        assert_eq!(pk.algorithm.to_string(), "BTREE");
+        assert_eq!("UNIQUE".parse::<IndexTypes>()?, IndexTypes::IT_UNIQUE);
+        Ok(())
+    }
+
+    #[test]
+    fn desc_explicit_pk() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from("data/tb_instant_col_0.ibd"))?;
+        assert!(!fact.load_table_def()?.has_explicit_pk());
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_02))?;
+        assert!(fact.load_table_def()?.has_explicit_pk());
+
+        let mut app = App::new(PathBuf::from("data/tb_instant_col_0.ibd"));
+        assert!(app
+            .run(Commands::Desc {
+                columns_only: false,
+                verbose: false,
+            })
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn desc_prefix_index() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let tabdef = fact.load_table_def()?;
+        let dept_name = &tabdef.col_defs[1];
+
+        // full length secondary index on dept_name varchar(40)
+        let ele = &tabdef.idx_defs[1].elements[0];
+        assert_eq!(ele.prefix_len, None);
+        assert_eq!(ele.key_part(), "dept_name");
+
+        // KEY(dept_name(10)), 10 characters of utf8mb4
+        let dde = DataDictIndexElement {
+            ordinal_position: 1,
+            length: 40,
+            order: 2,
+            hidden: false,
+            column_opx: 1,
+        };
+        let ele = IndexElementDef::from(&dde, dept_name);
+        assert_eq!(ele.prefix_len, Some(10));
+        assert_eq!(ele.key_part(), "dept_name(10)");
+
+        // KEY(dept_name(10)), 10 characters of the double-byte gbk
+        let gbk_col = ColumnDef {
+            charset: "gbk".into(),
+            ..dept_name.clone()
+        };
+        let gbk = DataDictIndexElement { length: 20, ..dde };
+        assert_eq!(IndexElementDef::from(&gbk, &gbk_col).prefix_len, Some(10));
+
+        // hidden element of the full column
+        let dde = DataDictIndexElement {
+            length: u32::MAX,
+            hidden: true,
+            ..dde
+        };
+        assert_eq!(IndexElementDef::from(&dde, dept_name).prefix_len, None);
+        Ok(())
+    }
+
+    #[test]
+    fn desc_instant_stats() -> Result<()> {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from("data/tb_row_version_3.ibd"));
+        assert!(app
+            .run(Commands::Desc {
+                columns_only: false,
+                verbose: false,
+            })
+            .is_ok());
+        let mut fact = DatafileFactory::from_file(PathBuf::from("data/tb_row_version_3.ibd"))?;
+        let stats = fact.load_table_def()?.instant_stats();
+        assert_eq!(
+            stats,
+            InstantStats {
+                n_added: 1,
+                n_dropped: 1,
+                n_current: 7,
+                n_physical: 8,
+            }
+        );
+
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let stats = fact.load_table_def()?.instant_stats();
+        assert_eq!((stats.n_added, stats.n_dropped), (0, 0));
+        assert_eq!(stats.n_current, stats.n_physical);
+        Ok(())
+    }
+
+    #[test]
+    fn view_heap_records() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_02))?;
+        for page_no in [4, 5] {
+            let page: BasePage<IndexPageBody> = fact.read_page(page_no)?;
+            let layout = fact.page_index_layout(&page)?;
+            assert!(layout.is_some());
+            let heap = page.page_body.heap_records(layout);
+            assert_eq!(heap.len(), page.page_body.idx_hdr.heap_count());
+            assert_eq!(heap.len(), page.page_body.heap_records(None).len());
+            assert_eq!(heap[0].0.rec_status, RecordStatus::INFIMUM);
+            assert_eq!(heap[1].0.rec_status, RecordStatus::SUPREMUM);
+        }
+
+        let mut app = App::new(PathBuf::from(IBD_02));
+        assert!(app
+            .run(Commands::View {
+                page_no: 4,
+                strict: false,
+                heap: true,
+                stdin: false,
+            })
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn view_heap_unlinked_record() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let page: BasePage<IndexPageBody> = fact.read_page(4)?;
+        let recs = &page.page_body.data_rec_hdrs;
+        let (prev, lost, next) = (recs[1].addr, recs[2].addr, recs[3].addr);
+        let lost_heap_no = recs[2].heap_no;
+
+        // skip the 3rd record in the user list, it is not in the garbage list
+        let data = util::patch_fixture(IBD_01, |data| {
+            let offset = (next as i16 - prev as i16).to_be_bytes();
+            let at = UNIV_PAGE_SIZE * 4 + prev + 3;
+            data[at..at + 2].copy_from_slice(&offset);
+        });
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data))?;
+        let page: BasePage<IndexPageBody> = fact.read_page(4)?;
+        assert!(page.page_body.data_rec_hdrs.iter().all(|h| h.addr != lost));
+        assert!(page.page_body.free_rec_hdrs.iter().all(|h| h.addr != lost));
+
+        let layout = fact.page_index_layout(&page)?;
+        let heap = page.page_body.heap_records(layout);
+        assert_eq!(heap.len(), page.page_body.idx_hdr.heap_count());
+        let (rec_hdr, unlinked) = heap
+            .iter()
+            .find(|(h, _)| h.heap_no == lost_heap_no)
+            .unwrap();
+        assert_eq!(rec_hdr.addr, lost);
+        assert!(unlinked);
+        assert_eq!(page.page_body.heap_records(None).len() + 1, heap.len());
+
+        let (rec, tuple) = fact.unpack_heap_record(4, lost_heap_no)?;
+        assert_eq!(rec.rec_hdr.addr, lost);
+        assert!(!tuple.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn view_heap_record() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let (rec, tuple) = fact.unpack_heap_record(4, 2)?;
+        assert_eq!(rec.rec_hdr.heap_no, 2);
+        assert!(!tuple.is_empty());
+
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app
+            .run(Commands::Record {
+                page_no: 4,
+                heap_no: 2
+            })
+            .is_ok());
+        assert!(app
+            .run(Commands::Record {
+                page_no: 4,
+                heap_no: 0
+            })
+            .is_err());
+        assert!(app
+            .run(Commands::Record {
+                page_no: 4,
+                heap_no: 999
+            })
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn extract_pages() -> Result<()> {
+        util::init_unit_test();
+        let out = util::TempFile::new("extract", &[])?;
+        let mut app = App::new(PathBuf::from(IBD_01));
+        app.run(Commands::Extract {
+            page_no: Some(4),
+            out: out.path.clone(),
+            range: None,
+        })?;
+        let data = std::fs::read(&out.path)?;
+        assert_eq!(data.len(), UNIV_PAGE_SIZE);
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        assert_eq!(&data[..38], &fact.fil_hdr_buffer(4)?[..]);
+
+        app.run(Commands::Extract {
+            page_no: None,
+            out: out.path.clone(),
+            range: Some((3, 4)),
+        })?;
+        assert_eq!(std::fs::read(&out.path)?.len(), 2 * UNIV_PAGE_SIZE);
+
+        let page_count = fact.page_count();
+        assert!(app
+            .run(Commands::Extract {
+                page_no: Some(page_count),
+                out: out.path.clone(),
+                range: None,
+            })
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn leaf_fragmentation() -> Result<()> {
+        util::init_unit_test();
+        let app = App::new(PathBuf::from(IBD_02));
+        let stats = app.do_frag(None)?;
+        assert_eq!(stats.len(), 2);
+        for stat in &stats {
+            assert!(stat.n_leaves >= 1);
+            assert!(stat.ratio() < 1.0);
+        }
+
+        let stat = FragStat::from_chain(&[4, 5, 6, 9, 10]);
+        assert_eq!(stat.n_scattered, 1);
+        assert_eq!(stat.ratio(), 25.0);
+        Ok(())
+    }
+
+    /// no 2-level index fixture, copy the leaf page 4 to page 6 and turn it
+    /// into a root page with one node pointer pointing to page 4
+    fn build_two_level_data(node_ptr: bool) -> Vec<u8> {
+        util::patch_fixture(IBD_01, |data| {
+            let leaf = data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5].to_vec();
+            let page = &mut data[UNIV_PAGE_SIZE * 6..UNIV_PAGE_SIZE * 7];
+            page.copy_from_slice(&leaf);
+            page[4..8].copy_from_slice(&6u32.to_be_bytes());
+            page[38 + 16..38 + 18].copy_from_slice(&1u16.to_be_bytes());
+            page[38 + 26..38 + 28].copy_from_slice(&1u16.to_be_bytes());
+            if node_ptr {
+                // first record: status NODE_PTR, next to supremum, key d001 + child
+                let inf_next =
+                    i16::from_be_bytes([page[INF_PAGE_BYTE_OFF - 2], page[INF_PAGE_BYTE_OFF - 1]]);
+                let origin = (INF_PAGE_BYTE_OFF as isize + inf_next as isize) as usize;
+                page[origin - 3] = (page[origin - 3] & 0xf8) | RecordStatus::NODE_PTR as u8;
+                let next = (SUP_PAGE_BYTE_OFF as isize - origin as isize) as i16;
+                page[origin - 2..origin].copy_from_slice(&next.to_be_bytes());
+                page[origin + 4..origin + 8].copy_from_slice(&4u32.to_be_bytes());
+            }
+        })
+    }
+
+    #[test]
+    fn page_of_offset() -> Result<()> {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert_eq!(app.do_offset(65536)?, (4, 0));
+        assert_eq!(app.do_offset(UNIV_PAGE_SIZE * 5 + 99)?, (5, 99));
+        assert!(app.do_offset(UNIV_PAGE_SIZE * 8).is_err());
+        assert!(app.run(Commands::Offset { byte_offset: 0 }).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn fil_header_trailer() -> Result<()> {
+        util::init_unit_test();
+        let app = App::new(PathBuf::from(IBD_01));
+        let (fil_hdr, fil_trl) = app.do_fil(0)?;
+        assert_eq!(fil_hdr.page_no, PageNumber::Page(0));
+        assert_eq!(fil_hdr.page_type, PageTypes::FSP_HDR);
+        assert_eq!(fil_trl.lsn_low32bit, (fil_hdr.lsn & 0xffffffff) as u32);
+        assert!(app.do_fil(8).is_err());
+
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert!(app.run(Commands::Fil { page_no: 4 }).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn dump_page_trailers() -> Result<()> {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_01));
+        assert_eq!(app.do_trailer()?, 8);
+        assert!(app.run(Commands::Trailer).is_ok());
+
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        for page_no in 0..fact.page_count() {
+            let fil_trl = fact.read_fil_trl(page_no)?;
+            let fil_hdr = fact.read_fil_hdr(page_no)?;
+            assert_eq!(fil_trl.lsn_low32bit, (fil_hdr.lsn & 0xffffffff) as u32);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn undo_space_segments() -> Result<()> {
+        util::init_unit_test();
+        let app = App::new(PathBuf::from(UNDO_1));
+        let segs = app.do_undo_space()?;
+        assert_eq!(segs.len(), 149);
+        assert!(segs
+            .iter()
+            .all(|seg| seg.state == UndoPageStates::TRX_UNDO_CACHED));
+        assert!(segs.iter().all(|seg| seg.trx_id.is_some()));
+        let last = segs.last().unwrap();
+        assert_eq!(
+            (last.rseg_no, last.rseg_page, last.undo_page),
+            (126, 131, 257)
+        );
+        assert_eq!((last.trx_id, last.trx_no), (Some(808), Some(811)));
+
+        let app = App::new(PathBuf::from(IBD_01));
+        assert!(app.do_undo_space().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn undo_record_gtid() -> Result<()> {
+        util::init_unit_test();
+        let app = App::new(PathBuf::from(UNDO_1));
+        assert_eq!(app.do_undo(188, 418, 1)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn view_rtree_pages() -> Result<()> {
+        util::init_unit_test();
+        let data = util::patch_fixture(IBD_01, |data| {
+            let leaf = data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5].to_vec();
+            let mbr: Vec<u8> = [1.0f64, 3.0, 2.0, 4.0]
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect();
+
+            // page 6 is the root with one node pointer to the leaf page 7
+            for (page_no, level) in [(6usize, 1u16), (7, 0)] {
+                let page = &mut data[UNIV_PAGE_SIZE * page_no..UNIV_PAGE_SIZE * (page_no + 1)];
+                page.copy_from_slice(&leaf);
+                page[4..8].copy_from_slice(&(page_no as u32).to_be_bytes());
+                page[24..26].copy_from_slice(&(PageTypes::RTREE as u16).to_be_bytes());
+                page[26..34].copy_from_slice(&9u64.to_be_bytes());
+                page[38 + 16..38 + 18].copy_from_slice(&1u16.to_be_bytes());
+                page[38 + 26..38 + 28].copy_from_slice(&level.to_be_bytes());
+                let inf_next =
+                    i16::from_be_bytes([page[INF_PAGE_BYTE_OFF - 2], page[INF_PAGE_BYTE_OFF - 1]]);
+                let origin = (INF_PAGE_BYTE_OFF as isize + inf_next as isize) as usize;
+                let next = (SUP_PAGE_BYTE_OFF as isize - origin as isize) as i16;
+                page[origin - 2..origin].copy_from_slice(&next.to_be_bytes());
+                page[origin..origin + RTREE_MBR_SIZE].copy_from_slice(&mbr);
+                page[origin + RTREE_MBR_SIZE..origin + RTREE_MBR_SIZE + 4]
+                    .copy_from_slice(&7u32.to_be_bytes());
+            }
+        });
+        let file = util::TempFile::new("rtree", &data)?;
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let root: BasePage<RtreePageBody> = fact.read_page(6)?;
+        assert_eq!(root.page_body.split_seq_num, 9);
+        let recs = &root.page_body.mbr_records;
+        assert_eq!(recs.len(), 1);
+        assert_eq!(
+            recs[0].mbr,
+            Mbr {
+                xmin: 1.0,
+                xmax: 3.0,
+                ymin: 2.0,
+                ymax: 4.0
+            }
+        );
+        assert_eq!(recs[0].child_page, Some(PageNumber::Page(7)));
+        let leaf: BasePage<RtreePageBody> = fact.read_page(7)?;
+        assert_eq!(leaf.page_body.mbr_records[0].child_page, None);
+
+        let mut app = App::new(file.path.clone());
+        for page_no in [6, 7] {
+            assert!(app
+                .run(Commands::View {
+                    page_no,
+                    strict: false,
+                    heap: false,
+                    stdin: false,
+                })
+                .is_ok());
+        }
+        let stat = app.do_dump_btree(6, BTREE_MAX_DEPTH)?;
+        assert_eq!(stat.height, 2);
+        assert_eq!(stat.n_leaf_recs, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn index_max_lsn() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let lsns = fact.index_max_lsns()?;
+        let tabdef = fact.load_table_def()?;
+        for idx in &tabdef.idx_defs {
+            assert!(lsns[&(idx.idx_id as u64)] > 0);
+        }
+        let app = App::new(PathBuf::from(IBD_01));
+        assert_eq!(app.do_lsn()?, *lsns.values().max().unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn index_page_ranges() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let tabdef = fact.load_table_def()?;
+        let app = App::new(PathBuf::from(IBD_01));
+        let index_pages = app.do_index_pages()?;
+        assert_eq!(index_pages.len(), tabdef.idx_defs.len());
+        for idx in &tabdef.idx_defs {
+            let expected = if idx.idx_name == "PRIMARY" { 4 } else { 5 };
+            assert_eq!(index_pages[&(idx.idx_id as u64)], vec![expected]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn verify_datafile() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_02));
+        assert!(app
+            .run(Commands::Verify {
+                strict_trailer: false,
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn verify_space_ids() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        assert!(fact.verify_space_ids()?.is_empty());
+
+        // page 5 copied from another tablespace
+        let data = util::patch_fixture(IBD_01, |data| {
+            let page = &mut data[UNIV_PAGE_SIZE * 5..UNIV_PAGE_SIZE * 6];
+            page[34..38].copy_from_slice(&42u32.to_be_bytes());
+        });
+        let file = util::TempFile::new("space_id", &data)?;
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert_eq!(fact.verify_space_ids()?, vec![(5, SpaceId::Space(42))]);
+        let mut app = App::new(file.path.clone());
+        assert!(app
+            .run(Commands::Verify {
+                strict_trailer: false,
+            })
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn view_temp_space_sys_page() -> Result<()> {
+        util::init_unit_test();
+        // page 3 of a session temporary tablespace is not a rseg header
+        let data = util::patch_fixture(IBD_01, |data| {
+            let page = &mut data[UNIV_PAGE_SIZE * 3..UNIV_PAGE_SIZE * 4];
+            page[24..26].copy_from_slice(&(PageTypes::SYS as u16).to_be_bytes());
+            page[34..38].copy_from_slice(&u32::from(&SpaceId::TempSpace(1)).to_be_bytes());
+        });
+        let file = util::TempFile::new("temp_sys", &data)?;
+        let mut app = App::new(file.path.clone());
+        let err = app
+            .run(Commands::View {
+                page_no: 3,
+                strict: false,
+                heap: false,
+                stdin: false,
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("会话临时表空间"));
+        Ok(())
+    }
+
+    #[test]
+    fn view_ibuf_free_list_pages() -> Result<()> {
+        util::init_unit_test();
+        let data = util::patch_fixture(IBD_01, |data| {
+            // page 6 and 7 are linked in the ibuf free list, page 7 keeps the
+            // records of the former index page 4
+            let leaf = data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5].to_vec();
+            data[UNIV_PAGE_SIZE * 7..UNIV_PAGE_SIZE * 8].copy_from_slice(&leaf);
+            for (page_no, prev, next) in [(6u32, PAGE_NONE, 7u32), (7, 6, PAGE_NONE)] {
+                let beg = UNIV_PAGE_SIZE * page_no as usize;
+                let page = &mut data[beg..beg + UNIV_PAGE_SIZE];
+                page[4..8].copy_from_slice(&page_no.to_be_bytes());
+                page[24..26].copy_from_slice(&(PageTypes::IBUF_FREE_LIST as u16).to_be_bytes());
+                let node = FIL_HEADER_SIZE + 46;
+                page[node..node + 4].copy_from_slice(&prev.to_be_bytes());
+                page[node + 6..node + 10].copy_from_slice(&next.to_be_bytes());
+            }
+        });
+
+        let file = util::TempFile::new("ibuf", &data)?;
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let page: BasePage<IbufFreeListPageBody> = fact.read_page(6)?;
+        assert_eq!(
+            page.page_body.free_list_node.next.page_no,
+            PageNumber::Page(7)
+        );
+        assert!(page.page_body.index.is_none());
+        let page: BasePage<IbufFreeListPageBody> = fact.read_page(7)?;
+        assert_eq!(
+            page.page_body.free_list_node.prev.page_no,
+            PageNumber::Page(6)
+        );
+        assert!(page.page_body.index.is_some());
+
+        let mut app = App::new(file.path.clone());
+        for page_no in [6, 7] {
+            assert!(app
+                .run(Commands::View {
+                    page_no,
+                    strict: false,
+                    heap: false,
+                    stdin: false,
+                })
+                .is_ok());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn view_lob_pages() -> Result<()> {
+        util::init_unit_test();
+        let text = b"hello, large object";
+        let data = util::patch_fixture(IBD_01, |data| {
+            // page 6 is the LOB first page, one index entry points to data page 7
+            let page = &mut data[UNIV_PAGE_SIZE * 6..UNIV_PAGE_SIZE * 7];
+            page[4..8].copy_from_slice(&6u32.to_be_bytes());
+            page[24..26].copy_from_slice(&(PageTypes::LOB_FIRST as u16).to_be_bytes());
+            let body = &mut page[FIL_HEADER_SIZE..];
+            body[0] = 1;
+            body[2..6].copy_from_slice(&1u32.to_be_bytes());
+            body[16..20].copy_from_slice(&(text.len() as u32).to_be_bytes());
+            body[20..26].copy_from_slice(&0x1234u64.to_be_bytes()[2..]);
+            let ent_off = (FIL_HEADER_SIZE + LOB_FIRST_INDEX_BEGIN) as u16;
+            body[26..30].copy_from_slice(&1u32.to_be_bytes());
+            body[30..34].copy_from_slice(&6u32.to_be_bytes());
+            body[34..36].copy_from_slice(&ent_off.to_be_bytes());
+            body[36..40].copy_from_slice(&6u32.to_be_bytes());
+            body[40..42].copy_from_slice(&ent_off.to_be_bytes());
+            let ent =
+                &mut body[LOB_FIRST_INDEX_BEGIN..LOB_FIRST_INDEX_BEGIN + LOB_INDEX_ENTRY_SIZE];
+            ent[0..4].copy_from_slice(&PAGE_NONE.to_be_bytes());
+            ent[6..10].copy_from_slice(&PAGE_NONE.to_be_bytes());
+            ent[48..52].copy_from_slice(&7u32.to_be_bytes());
+            ent[52..56].copy_from_slice(&(text.len() as u32).to_be_bytes());
+            body[LOB_FIRST_DATA_BEGIN..LOB_FIRST_DATA_BEGIN + text.len()].copy_from_slice(text);
+
+            let page = &mut data[UNIV_PAGE_SIZE * 7..UNIV_PAGE_SIZE * 8];
+            page[4..8].copy_from_slice(&7u32.to_be_bytes());
+            page[24..26].copy_from_slice(&(PageTypes::LOB_DATA as u16).to_be_bytes());
+            let body = &mut page[FIL_HEADER_SIZE..];
+            body[1..5].copy_from_slice(&(text.len() as u32).to_be_bytes());
+            body[LOB_DATA_PAGE_BEGIN..LOB_DATA_PAGE_BEGIN + text.len()].copy_from_slice(text);
+        });
+
+        let file = util::TempFile::new("lob", &data)?;
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let first: BasePage<LobFirstPageBody> = fact.read_page(6)?;
+        let body = &first.page_body;
+        assert_eq!(body.version, 1);
+        assert_eq!(body.lob_version, 1);
+        assert_eq!(body.trx_id, 0x1234);
+        assert_eq!(body.index_list.len, 1);
+        assert_eq!(body.index_entries.len(), LOB_FIRST_INDEX_ENTRY_COUNT);
+        assert_eq!(&body.data[..], text);
+        let used = body.used_entries();
+        assert_eq!(used.len(), 1);
+        assert_eq!(used[0].page_no, PageNumber::Page(7));
+        assert_eq!(used[0].data_len as usize, text.len());
+
+        let lob_data: BasePage<LobDataPageBody> = fact.read_page(7)?;
+        assert_eq!(&lob_data.page_body.data[..], text);
+
+        let mut app = App::new(file.path.clone());
+        for page_no in [6, 7] {
+            assert!(app
+                .run(Commands::View {
+                    page_no,
+                    strict: false,
+                    heap: false,
+                    stdin: false,
+                })
+                .is_ok());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn verify_torn_page_trailer() -> Result<()> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        assert!(fact.verify_trailer_lsns()?.is_empty());
+
+        let end = UNIV_PAGE_SIZE * 4;
+        let mut lsn_low = 0;
+        let data = util::patch_fixture(IBD_01, |data| {
+            lsn_low = u32::from_be_bytes(data[end - 4..end].try_into().unwrap());
+            data[end - 4..end].copy_from_slice(&(lsn_low ^ 0xff).to_be_bytes());
+        });
+        let file = util::TempFile::new("torn", &data)?;
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let torn = fact.verify_trailer_lsns()?;
+        assert_eq!(torn.len(), 1);
+        assert_eq!(torn[0].0, 3);
+        assert_eq!(torn[0].1 & 0xffffffff, lsn_low as u64);
+        assert_eq!(torn[0].2, lsn_low ^ 0xff);
+
+        let mut app = App::new(file.path.clone());
+        assert!(app
+            .run(Commands::Verify {
+                strict_trailer: false,
+            })
+            .is_ok());
+        assert!(app
+            .run(Commands::Verify {
+                strict_trailer: true,
+            })
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn verify_page_checksums() -> Result<()> {
+        util::init_unit_test();
+        for ibd in [IBD_01, IBD_02, "data/tb_row_version_0.ibd"] {
+            let mut fact = DatafileFactory::from_file(PathBuf::from(ibd))?;
+            assert!(fact.verify_checksums()?.is_empty());
+        }
+
+        // a flipped byte in the page body of page 4
+        let beg = UNIV_PAGE_SIZE * 4;
+        let data = util::patch_fixture(IBD_01, |data| data[beg + 200] ^= 0xff);
+        let file = util::TempFile::new("checksum", &data)?;
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let corrupted = fact.verify_checksums()?;
+        assert_eq!(corrupted.len(), 1);
+        assert_eq!(corrupted[0].0, 4);
+        let mut app = App::new(file.path.clone());
+        assert!(app
+            .run(Commands::Verify {
+                strict_trailer: false,
+            })
+            .is_err());
+
+        // the same page written by the none, innodb and legacy crc32 algorithms,
+        // the innodb old checksum covers the header checksum
+        type Stamp = fn(&[u8]) -> u32;
+        let stamps: [(Stamp, Stamp); 4] = [
+            (|_| 0xdeadbeef, |_| 0xdeadbeef),
+            (util::page_innodb_checksum, util::page_old_checksum),
+            (
+                |_| 0,
+                |page| u32::from_be_bytes(page[16..20].try_into().unwrap()),
+            ),
+            (util::page_crc32c_legacy, util::page_crc32c_legacy),
+        ];
+        for (stamp1, stamp2) in stamps {
+            let data = util::patch_fixture(IBD_01, |data| {
+                let page = &mut data[beg..beg + UNIV_PAGE_SIZE];
+                page[200] ^= 0xff;
+                let field1 = stamp1(page);
+                page[..4].copy_from_slice(&field1.to_be_bytes());
+                let field2 = stamp2(page);
+                page[UNIV_PAGE_SIZE - 8..UNIV_PAGE_SIZE - 4].copy_from_slice(&field2.to_be_bytes());
+            });
+            let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+            assert!(fact.verify_checksums()?.is_empty());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn verify_fsp_size() -> Result<()> {
+        util::init_unit_test();
+        for ibd in [IBD_01, IBD_02, "data/tb_row_version_0.ibd"] {
+            let mut fact = DatafileFactory::from_file(PathBuf::from(ibd))?;
+            assert!(fact.verify_fsp_size()?.is_empty());
+        }
+
+        // fsp_size 4: autoextend in progress, fsp_size 100: truncated
+        for (fsp_size, free_limit, n_errs) in [(4u32, 64u32, 0), (100, 64, 1), (8, 128, 1)] {
+            let data = util::patch_fixture(IBD_01, |data| {
+                data[FIL_HEADER_SIZE + 8..FIL_HEADER_SIZE + 12]
+                    .copy_from_slice(&fsp_size.to_be_bytes());
+                data[FIL_HEADER_SIZE + 12..FIL_HEADER_SIZE + 16]
+                    .copy_from_slice(&free_limit.to_be_bytes());
+            });
+            let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+            assert_eq!(fact.verify_fsp_size()?.len(), n_errs);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn diff_same_datafile() {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_02));
+        assert!(app
+            .run(Commands::Diff {
+                other: PathBuf::from(IBD_02)
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn hash_stable_pages() -> Result<()> {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_02));
+        assert!(app.run(Commands::Hash { logical: true }).is_ok());
+        let app2 = App::new(PathBuf::from(IBD_02));
+        assert_eq!(app.do_hash(false)?, app2.do_hash(false)?);
+        Ok(())
+    }
+
+    #[test]
+    fn traverse_two_level_index() -> Result<()> {
+        util::init_unit_test();
+        let data = build_two_level_data(true);
+        let file = util::TempFile::new("two_level", &data)?;
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let node_ptrs = fact.unpack_index_page(6, false)?.node_ptrs()?;
+        assert_eq!(node_ptrs.len(), 1);
+        assert_eq!(node_ptrs[0].child_page, 4);
+        assert_eq!(node_ptrs[0].key[0].1, DataValue::Str("d001".into()));
+        assert_eq!(fact.first_leaf_page(6)?, 4);
+
+        let app = App::new(file.path.clone());
+        let stat = app.do_dump_btree(6, BTREE_MAX_DEPTH)?;
+        assert_eq!(stat.height, 2);
+        assert_eq!(stat.n_leaf_recs, 9);
+
+        let data = build_two_level_data(false);
+        let file = util::TempFile::new("bad_node_ptr", &data)?;
+        let app = App::new(file.path.clone());
+        assert!(app.do_dump_btree(6, BTREE_MAX_DEPTH).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn traverse_empty_index() -> Result<()> {
+        util::init_unit_test();
+        // the infimum points to the supremum directly, no user record
+        let data = util::patch_fixture(IBD_01, |data| {
+            let page = &mut data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+            let next = (SUP_PAGE_BYTE_OFF - INF_PAGE_BYTE_OFF) as i16;
+            page[INF_PAGE_BYTE_OFF - 2..INF_PAGE_BYTE_OFF].copy_from_slice(&next.to_be_bytes());
+            page[38 + 16..38 + 18].copy_from_slice(&0u16.to_be_bytes());
+        });
+        let file = util::TempFile::new("empty_index", &data)?;
+        let app = App::new(file.path.clone());
+        let stat = app.do_dump_btree(4, BTREE_MAX_DEPTH)?;
+        assert_eq!(stat.height, 1);
+        assert_eq!(stat.n_leaf_recs, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn dump_index_header_by_level() -> Result<()> {
+        util::init_unit_test();
+        let data = build_two_level_data(true);
+        let file = util::TempFile::new("header_level", &data)?;
+        let app = App::new(file.path.clone());
+        assert_eq!(app.do_dump_index_header(false, false)?, 3);
+        assert_eq!(app.do_dump_index_header(true, false)?, 2);
+        assert_eq!(app.do_dump_index_header(false, true)?, 1);
 
+        use clap::Parser;
+        let args = ["ibr", "a.ibd", "dump", "--only-leaf", "--only-internal"];
+        assert!(crate::Args::try_parse_from(args).is_err());
         Ok(())
     }
 
-    fn do_traverse_index(fact: &mut DatafileFactory, page_no: usize, indent: usize) -> Result<()> {
-        let curr: BasePage<IndexPageBody> = fact.read_page(page_no)?;
-        let idx_hdr = &curr.page_body.idx_hdr;
-        for _ in 0..indent {
-            print!("  ");
-        }
-        let result_set = fact.unpack_index_page(page_no, false)?;
-
-        let first = &result_set.tuples[0];
-        let sep_idx = first
-            .iter()
-            .position(|(_, val)| {
-                matches!(
-                    val,
-                    DataValue::PageNo(_) | DataValue::TrxId(_) | DataValue::RbPtr(_)
-                )
-            })
-            .unwrap_or(first.len());
-        let key = &first[0..sep_idx];
-        println!(
-            "{}: level={}, min_key={:?}, n_rec={}",
-            colored_page_number(page_no),
-            idx_hdr.page_level,
-            &key,
-            idx_hdr.page_n_recs,
-        );
+    #[test]
+    fn traverse_cyclic_index() -> Result<()> {
+        util::init_unit_test();
+        let data = build_two_level_data(true);
+        let file = util::TempFile::new("cyclic", &data)?;
+        let app = App::new(file.path.clone());
+        assert!(app.do_dump_btree(6, 1).is_err());
+        assert!(app.do_dump_btree(6, 2).is_ok());
 
-        if idx_hdr.page_level > 0 {
-            for tuple in &result_set.tuples {
-                let node_ptr = tuple.last().unwrap();
-                match node_ptr.1 {
-                    DataValue::PageNo(child_page_no) => {
-                        Self::do_traverse_index(fact, child_page_no as usize, indent + 1)?;
-                    }
-                    _ => panic!("错误的节点: {:?}", tuple),
-                }
-            }
-        }
+        // the node pointer of the root references the root itself
+        let mut data = data;
+        let page = &mut data[UNIV_PAGE_SIZE * 6..UNIV_PAGE_SIZE * 7];
+        let inf_next =
+            i16::from_be_bytes([page[INF_PAGE_BYTE_OFF - 2], page[INF_PAGE_BYTE_OFF - 1]]);
+        let origin = (INF_PAGE_BYTE_OFF as isize + inf_next as isize) as usize;
+        page[origin + 4..origin + 8].copy_from_slice(&6u32.to_be_bytes());
+        let file = util::TempFile::new("cyclic_self", &data)?;
+        let app = App::new(file.path.clone());
 
+        let err = app.do_dump_btree(6, BTREE_MAX_DEPTH).unwrap_err();
+        assert!(err.to_string().contains("page_no=6"));
         Ok(())
     }
 
-    fn do_dump_index_header(&self) -> Result<(), Error> {
-        let mut fact = DatafileFactory::from_file(self.input.clone())?;
-        for page_no in 0..fact.page_count() {
-            let fil_hdr = fact.read_fil_hdr(page_no)?;
+    #[test]
+    fn lenient_corrupted_page() -> Result<()> {
+        util::init_unit_test();
+        // break the trailer checksum and the supremum string of page 4
+        let data = util::patch_fixture(IBD_01, |data| {
+            let page = &mut data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+            page[UNIV_PAGE_SIZE - 8] ^= 0xff;
+            page[SUP_PAGE_BYTE_OFF..SUP_PAGE_BYTE_OFF + 8].copy_from_slice(b"SUPREMUM");
+        });
+        let file = util::TempFile::new("lenient", &data)?;
 
-            if page_no % 8 == 0 {
-                print!("{:>7} ", colored_page_number(page_no));
-            }
+        let strict = std::panic::catch_unwind(|| {
+            let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone())).unwrap();
+            fact.read_page::<IndexPageBody>(4).map(|_| ())
+        });
+        assert!(strict.is_err());
 
-            if fil_hdr.page_type == PageTypes::INDEX {
-                let idx_hdr = fact.read_idx_hdr(page_no)?;
-                print!("[{:>1},{:>4}]", idx_hdr.page_level, idx_hdr.page_n_recs);
-            } else {
-                print!("[{:>6.6}]", fil_hdr.page_type);
-            }
+        util::set_lenient(true);
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let rs = fact.unpack_index_page(4, false);
+        let mut app = App::new(file.path.clone());
+        let dumped = app.run(Commands::View {
+            page_no: 4,
+            strict: false,
+            heap: false,
+            stdin: false,
+        });
+        util::set_lenient(false);
 
-            if page_no % 8 == 7 {
-                println!();
-            } else {
-                print!(" ");
-            }
-        }
+        assert_eq!(rs?.tuples.len(), 9);
+        assert!(dumped.is_ok());
         Ok(())
     }
 
-    fn do_dump_index_record(
-        &mut self,
-        page_no: usize,
-        limit: usize,
-        garbage: bool,
-        verbose: bool,
-    ) -> Result<(), Error> {
-        let mut fact = DatafileFactory::from_file(self.input.clone())?;
+    #[test]
+    fn dump_internal_page() -> Result<()> {
+        util::init_unit_test();
+        let data = build_two_level_data(true);
+        let file = util::TempFile::new("internal_page", &data)?;
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let rs = fact.unpack_index_page(6, false)?;
+        let names: Vec<_> = rs.tuples[0].iter().map(|ent| ent.0.as_str()).collect();
+        assert_eq!(names, vec!["dept_no", "NODE_PTR"]);
+        assert_eq!(rs.tuples[0][1].1, DataValue::PageNo(4));
 
-        let fil_hdr = fact.read_fil_hdr(page_no)?;
-        let page_type = fil_hdr.page_type;
-        if page_type != PageTypes::INDEX {
-            return Err(Error::msg(format!("不支持的页类型: {:?}", page_type)));
-        }
+        let mut app = App::new(file.path.clone());
+        let opts = DumpOptions {
+            limit: 10,
+            ..Default::default()
+        };
+        assert!(app.do_dump_index_record(6, &opts).is_ok());
+        Ok(())
+    }
 
-        let result_set = fact.unpack_index_page(page_no, garbage)?;
-        let n_dump_rows = min(result_set.tuples.len(), limit);
-        for (i, tuple) in result_set.tuples[..n_dump_rows].iter().enumerate() {
-            let rec = &result_set.records[i];
-            let seq = i + 1;
+    /// copy IBD_01 and move the last user record of page 4 to the garbage
+    /// list, return the patched bytes and the original number of records
+    fn move_last_record_to_garbage() -> Result<(Vec<u8>, usize)> {
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let rs = fact.unpack_index_page(4, false)?;
+        let n_recs = rs.records.len();
+        let prev = rs.records[n_recs - 2].addr;
+        let last = rs.records[n_recs - 1].addr;
 
-            // 打印分割线
-            for _ in 0..40 {
-                print!("*");
-            }
-            print!(" Row {} ", seq);
-            for _ in 0..40 {
-                print!("*");
-            }
-            println!();
+        // move the last user record to the garbage list
+        let data = util::patch_fixture(IBD_01, |data| {
+            let page = &mut data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+            let off = (SUP_PAGE_BYTE_OFF as isize - prev as isize) as i16;
+            page[prev - 2..prev].copy_from_slice(&off.to_be_bytes());
+            page[last - 2..last].copy_from_slice(&0i16.to_be_bytes());
+            page[FIL_HEADER_SIZE + 6..FIL_HEADER_SIZE + 8]
+                .copy_from_slice(&(last as u16).to_be_bytes());
+        });
+        Ok((data, n_recs))
+    }
 
-            info!(
-                "seq={}, addr=@{}, {:?}",
-                seq.to_string().red(),
-                &rec.addr.to_string().yellow(),
-                &rec,
-            );
+    #[test]
+    fn dump_limit_with_garbage() -> Result<()> {
+        util::init_unit_test();
+        let (data, n_recs) = move_last_record_to_garbage()?;
+        let file = util::TempFile::new("garbage", &data)?;
 
-            // 打印一些关键信息
-            if verbose {
-                println!("row_info: {:?}", &rec.row_info);
-                println!("rec_hdr : {:?}", &rec.rec_hdr);
-                println!("rec_stat: {:?}", &rec.calc_layout());
-            }
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert_eq!(fact.unpack_index_page(4, true)?.records.len(), 1);
 
-            // 打印记录
-            for ent in tuple {
-                println!("{:>12} => {:?}", &ent.0.to_string().magenta(), &ent.1);
-            }
+        let mut app = App::new(file.path.clone());
+        for (limit, garbage, expected) in [
+            (3, true, 3),
+            (n_recs - 1, true, n_recs - 1),
+            (100, true, n_recs),
+            (100, false, n_recs - 1),
+        ] {
+            let opts = DumpOptions {
+                limit,
+                garbage,
+                ..Default::default()
+            };
+            assert_eq!(app.do_dump_index_record(4, &opts)?, expected);
         }
+        Ok(())
+    }
 
-        if n_dump_rows < result_set.tuples.len() {
-            println!(
-                "ONLY dump {} of {} rows, use `--limit num' to dump more",
-                n_dump_rows,
-                result_set.tuples.len()
-            )
+    #[test]
+    fn dump_all_records_alias() -> Result<()> {
+        util::init_unit_test();
+        let (data, n_recs) = move_last_record_to_garbage()?;
+        let file = util::TempFile::new("all_records", &data)?;
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let rs = fact.unpack_all_records(4)?;
+        assert_eq!(rs.tuples.len(), n_recs);
+        assert!(rs.garbage[..n_recs - 1].iter().all(|g| !g));
+        assert!(rs.garbage[n_recs - 1]);
+
+        // `--all-records` is the same as `--garbage`
+        use clap::Parser;
+        for flag in ["--garbage", "--all-records"] {
+            let args = ["ibr", "x.ibd", "dump", "4", flag];
+            match crate::Args::try_parse_from(args)?.command {
+                Commands::Dump { garbage, .. } => assert!(garbage),
+                cmd => panic!("unexpected command: {:?}", cmd),
+            }
         }
+        let mut app = App::new(file.path.clone());
+        let opts = DumpOptions {
+            limit: 100,
+            garbage: true,
+            ..Default::default()
+        };
+        assert_eq!(app.do_dump_index_record(4, &opts)?, n_recs);
+        Ok(())
+    }
 
+    #[test]
+    fn dump_full_clustered_index() -> Result<()> {
+        util::init_unit_test();
+        let mut app = App::new(PathBuf::from(IBD_02));
+        let opts = DumpOptions {
+            limit: 100,
+            ..Default::default()
+        };
+        assert_eq!(app.do_dump_full(None, &opts)?.n_rows, 24);
+        let opts = DumpOptions {
+            limit: 5,
+            ..Default::default()
+        };
+        assert_eq!(app.do_dump_full(Some(5), &opts)?.n_rows, 5);
         Ok(())
     }
 
-    fn do_dump_log_records(&self, log_rec_type: LogRecordTypes) -> Result<(), Error> {
-        let mut fact = DatafileFactory::from_file(self.input.clone())?;
-        let buf = fact.file_buffer()?;
-        let log_file = LogFile::new(0, buf);
+    /// no multi-leaf fixture, copy the leaf page 4 to page 7 and link them in
+    /// the leaf chain 4 => 7
+    fn build_two_leaf_data() -> Vec<u8> {
+        util::patch_fixture(IBD_01, |data| {
+            let leaf = data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5].to_vec();
+            data[UNIV_PAGE_SIZE * 7..UNIV_PAGE_SIZE * 8].copy_from_slice(&leaf);
+            let page = &mut data[UNIV_PAGE_SIZE * 7..UNIV_PAGE_SIZE * 8];
+            page[4..8].copy_from_slice(&7u32.to_be_bytes());
+            page[8..12].copy_from_slice(&4u32.to_be_bytes());
+            page[12..16].copy_from_slice(&PAGE_NONE.to_be_bytes());
+            let page = &mut data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+            page[12..16].copy_from_slice(&7u32.to_be_bytes());
+        })
+    }
 
-        for blk in &log_file.log_block_list {
-            if let Blocks::Block(block) = blk {
-                let rec_type = if let Some(rec) = &block.log_record {
-                    rec.log_rec_hdr.log_rec_type.clone()
-                } else {
-                    LogRecordTypes::UNDEF
-                };
-                if rec_type != log_rec_type {
-                    continue;
-                }
-                if let Some(rec) = &block.log_record {
-                    println!("{:>6} => {:?}", block.block_no, &rec.log_rec_hdr);
-                }
-            }
+    #[test]
+    fn dump_full_stops_at_limit() -> Result<()> {
+        util::init_unit_test();
+        let file = util::TempFile::new("two_leaf", &build_two_leaf_data())?;
+        let mut app = App::new(file.path.clone());
+        for (limit, n_rows, n_pages) in [(3, 3, 1), (9, 9, 1), (10, 10, 2), (100, 18, 2)] {
+            let opts = DumpOptions {
+                limit,
+                ..Default::default()
+            };
+            let stat = app.do_dump_full(Some(4), &opts)?;
+            assert_eq!((stat.n_rows, stat.n_pages), (n_rows, n_pages));
         }
 
+        // the parallel dump decodes one bounded batch at a time
+        let opts = DumpOptions {
+            limit: 100,
+            jobs: 2,
+            ..Default::default()
+        };
+        let stat = app.do_dump_full(Some(4), &opts)?;
+        assert_eq!((stat.n_rows, stat.n_pages), (18, 2));
         Ok(())
     }
 
-    fn do_view_block(&self, block_no: usize) -> Result<(), Error> {
-        let mut fact = DatafileFactory::from_file(self.input.clone())?;
-        let block = fact.read_block(block_no)?;
-        match block {
-            Blocks::FileHeader(log_fil_hdr) => {
-                println!("{:#?}", log_fil_hdr);
-            }
-            Blocks::Block(log_block) => {
-                println!("{:#?}", log_block);
-            }
-            Blocks::Checkpoint(checkpoint) => {
-                println!("{:#?}", checkpoint);
-            }
-            Blocks::Unused => {
-                println!("Unused block");
-            }
-        }
+    #[test]
+    fn dump_full_jsonl_streaming() -> Result<()> {
+        util::init_unit_test();
+        use clap::Parser;
+        let args = [
+            "ibr", "a.ibd", "dump", "--full", "--format", "jsonl", "--limit", "1",
+        ];
+        assert!(crate::Args::try_parse_from(args).is_ok());
+
+        // only the first leaf is decoded for the first row
+        let file = util::TempFile::new("jsonl_leaf", &build_two_leaf_data())?;
+        let mut app = App::new(file.path.clone());
+        let opts = DumpOptions {
+            limit: 1,
+            format: DumpFormats::JSONL,
+            ..Default::default()
+        };
+        let stat = app.do_dump_full(Some(4), &opts)?;
+        assert_eq!((stat.n_rows, stat.n_pages), (1, 1));
         Ok(())
     }
 
-    fn do_view_log_file(&self) -> Result<(), Error> {
-        let mut fact = DatafileFactory::from_file(self.input.clone())?;
-        let buf = fact.file_buffer()?;
-        let log_file = LogFile::new(0, buf);
+    #[test]
+    fn dump_null_as_token() {
+        util::init_unit_test();
+        let opts = DumpOptions {
+            null_as: "\\N".into(),
+            ..Default::default()
+        };
+        assert_eq!(opts.render_value(&DataValue::Null), "\\N");
+        assert_eq!(opts.render_value(&DataValue::Str("NULL".into())), "NULL");
+        let opts = DumpOptions {
+            verbose: true,
+            ..Default::default()
+        };
+        assert_eq!(opts.null_as, "NULL");
+        assert_eq!(opts.render_value(&DataValue::Null), "NULL");
+    }
 
-        let mut stats = BTreeMap::new();
-        let mut unknown = HashSet::new();
-        for blk in &log_file.log_block_list {
-            if let Blocks::Block(block) = blk {
-                let rec_type = if let Some(rec) = &block.log_record {
-                    if matches!(rec.redo_rec_data, RedoRecordPayloads::Unknown) {
-                        unknown.insert(rec.log_rec_hdr.log_rec_type.clone());
-                    }
-                    rec.log_rec_hdr.log_rec_type.clone()
-                } else {
-                    LogRecordTypes::UNDEF
-                };
-                *stats.entry(rec_type).or_insert(0) += 1;
+    #[test]
+    fn dump_full_parallel_jobs() -> Result<()> {
+        util::init_unit_test();
+        let app = App::new(PathBuf::from(IBD_02));
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_02))?;
+        let root = fact.clustered_root()?;
+        let leaves = fact.leaf_chain(root)?;
+        let serial = leaves
+            .iter()
+            .map(|&page_no| App::load_dump_records(&mut fact, page_no, &DumpOptions::default()))
+            .collect::<Result<Vec<_>>>()?;
+        for jobs in [2, 3, 8] {
+            let opts = DumpOptions {
+                limit: 100,
+                jobs,
+                ..Default::default()
+            };
+            let parallel =
+                app.load_dump_records_parallel(fact.load_table_def()?, &leaves, &opts)?;
+            assert_eq!(parallel.len(), serial.len());
+            for (part, expected) in parallel.iter().zip(&serial) {
+                assert_eq!(part.tuples, expected.tuples);
+                assert_eq!(part.garbage, expected.garbage);
             }
+            let mut app = App::new(PathBuf::from(IBD_02));
+            assert_eq!(app.do_dump_full(None, &opts)?.n_rows, 24);
         }
 
-        println!("RedoRecordTypes Statistics:");
-        for entry in &stats {
-            println!(
-                "{:>28} {}> {}",
-                entry.0.to_string().yellow(),
-                if !unknown.contains(entry.0) {
-                    "=".to_string().green()
-                } else {
-                    "=".to_string().red()
-                },
-                entry.1.to_string().blue()
-            );
-        }
-
-        if !unknown.is_empty() {
-            warn!("Unknown type: {}", &format!("{:?}", &unknown).yellow());
+        // the parts are reassembled in the given page order
+        let app = App::new(PathBuf::from(IBD_01));
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let pages = [4, 5, 4, 5];
+        let opts = DumpOptions {
+            jobs: 3,
+            ..Default::default()
+        };
+        let parts = app.load_dump_records_parallel(fact.load_table_def()?, &pages, &opts)?;
+        assert_eq!(parts.len(), pages.len());
+        for (part, page_no) in parts.iter().zip(pages) {
+            let expected = App::load_dump_records(&mut fact, page_no, &opts)?;
+            assert_eq!(part.idx_name, expected.idx_name);
+            assert_eq!(part.tuples, expected.tuples);
         }
-
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod app_tests {
+    #[test]
+    fn dump_unknown_index_best_effort() -> Result<()> {
+        util::init_unit_test();
+        let data = util::patch_fixture(IBD_01, |data| {
+            let page = &mut data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+            page[FIL_HEADER_SIZE + 28..FIL_HEADER_SIZE + 36]
+                .copy_from_slice(&9999u64.to_be_bytes());
+        });
+        let file = util::TempFile::new("orphan", &data)?;
 
-    use super::*;
-    use crate::util;
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert!(fact.unpack_index_page(4, false).is_err());
 
-    const IBD_01: &str = "data/departments.ibd";
-    const IBD_02: &str = "data/dept_manager.ibd";
-    const REDO_1: &str = "data/redo_block_01";
-    const UNDO_1: &str = "data/undo_log_01";
+        util::set_best_effort(true);
+        let rs = fact.unpack_index_page(4, false)?;
+        assert_eq!(rs.idx_name, "UNKNOWN_9999");
+        assert_eq!(rs.tuples.len(), 9);
+        assert_eq!(rs.tuples[0][0].0, "RAW");
+        match &rs.tuples[0][0].1 {
+            DataValue::Unknown(b) => assert!(b.starts_with(b"d001")),
+            val => panic!("unexpected value: {:?}", val),
+        }
 
-    #[test]
-    fn info_datafile() {
-        util::init_unit_test();
-        let mut app = App::new(PathBuf::from(IBD_01));
-        assert!(app.run(Commands::Info {}).is_ok());
+        let mut app = App::new(file.path.clone());
+        let opts = DumpOptions {
+            limit: 3,
+            garbage: true,
+            ..Default::default()
+        };
+        assert_eq!(app.do_dump_index_record(4, &opts)?, 3);
+        util::set_best_effort(false);
+        Ok(())
     }
 
     #[test]
-    fn list_datafile() {
+    fn info_encryption() -> Result<()> {
         util::init_unit_test();
-        let mut app = App::new(PathBuf::from(IBD_01));
-        assert!(app
-            .run(Commands::List {
-                index: false,
-                segment: false,
-                extent: false,
-                page: false,
-                all: true,
-                limit: 32,
-            })
-            .is_ok());
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
+        assert!(!fsp_page.page_body.fsp_hdr.fsp_flags.encryption);
+
+        // no encrypted fixture, set the encryption flag and info on page 0
+        let data = util::patch_fixture(IBD_01, |data| {
+            let flags = u32::from_be_bytes(data[38 + 16..38 + 20].try_into().unwrap()) | 1 << 13;
+            data[38 + 16..38 + 20].copy_from_slice(&flags.to_be_bytes());
+            let info = fsp_page.page_body.encryption_info().addr;
+            data[info..info + 3].copy_from_slice(b"lCC");
+            data[info + 3..info + 7].copy_from_slice(&7u32.to_be_bytes());
+            data[info + 7..info + 43].copy_from_slice(b"d6f2b2a4-1c3e-11ef-9a5b-0242ac110002");
+        });
+        let file = util::TempFile::new("encryption", &data)?;
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
+        assert!(fsp_page.page_body.fsp_hdr.fsp_flags.encryption);
+        let info = fsp_page.page_body.encryption_info();
+        assert!(info.is_valid());
+        assert_eq!(info.master_key_id, 7);
+        assert_eq!(info.server_uuid, "d6f2b2a4-1c3e-11ef-9a5b-0242ac110002");
+        let mut app = App::new(file.path.clone());
+        assert!(app.run(Commands::Info { json: false }).is_ok());
+        Ok(())
     }
 
     #[test]
-    fn view_fsp_hdr_page() {
+    fn inode_total_pages() -> Result<()> {
         util::init_unit_test();
-        let mut app = App::new(PathBuf::from(IBD_01));
-        assert!(app.run(Commands::View { page_no: 0 }).is_ok());
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_01))?;
+        let inode_page: BasePage<INodePageBody> = fact.read_page(2)?;
+        let inodes = &inode_page.page_body.inode_ent_list;
+        assert!(!inodes.is_empty());
+        for inode in inodes {
+            assert!(inode.check_frag_pages(fact.page_count()).is_empty());
+            assert!(inode.total_pages() <= fact.page_count());
+        }
+        // the leaf segment of PRIMARY holds the root page 4 as a fragment
+        assert!(inodes.iter().any(|inode| inode.fseg_frag_arr.contains(&4)));
+        assert!(inodes[0].check_frag_pages(1).len() >= inodes[0].fseg_frag_arr.len());
+        Ok(())
     }
 
     #[test]
-    fn view_inode_page() {
+    fn audit_xdes_bitmap() -> Result<()> {
         util::init_unit_test();
-        let mut app = App::new(PathBuf::from(IBD_01));
-        assert!(app.run(Commands::View { page_no: 2 }).is_ok());
+        for ibd in [IBD_01, IBD_02] {
+            let mut fact = DatafileFactory::from_file(PathBuf::from(ibd))?;
+            assert_eq!(fact.inode_page_numbers()?, vec![2]);
+            assert!(fact.audit_xdes()?.is_ok());
+            let mut app = App::new(PathBuf::from(ibd));
+            assert!(app.run(Commands::Audit).is_ok());
+        }
+        Ok(())
     }
 
     #[test]
-    fn view_index_page() {
+    fn audit_xdes_all_inode_pages() -> Result<()> {
         util::init_unit_test();
-        let mut app = App::new(PathBuf::from(IBD_01));
-        assert!(app.run(Commands::View { page_no: 4 }).is_ok());
+        // append a copy of INODE page 2 as page 8, linked after page 2 in the
+        // FSP_SEG_INODES_FREE list, its first segment uses the free page 7 and
+        // a page beyond the tablespace
+        let data = util::patch_fixture(IBD_01, |data| {
+            let mut page = data[UNIV_PAGE_SIZE * 2..UNIV_PAGE_SIZE * 3].to_vec();
+            page[4..8].copy_from_slice(&8u32.to_be_bytes());
+            page[FIL_HEADER_SIZE..FIL_HEADER_SIZE + 6].copy_from_slice(&[0, 0, 0, 2, 0, 38]);
+            page[FIL_HEADER_SIZE + 6..FIL_HEADER_SIZE + 10]
+                .copy_from_slice(&u32::MAX.to_be_bytes());
+            let frag_arr = FIL_HEADER_SIZE + 12 + 64;
+            page[frag_arr..frag_arr + 4].copy_from_slice(&7u32.to_be_bytes());
+            page[frag_arr + 4..frag_arr + 8].copy_from_slice(&1000u32.to_be_bytes());
+            data.extend_from_slice(&page);
+
+            let node = UNIV_PAGE_SIZE * 2 + FIL_HEADER_SIZE;
+            data[node + 6..node + 12].copy_from_slice(&[0, 0, 0, 8, 0, 38]);
+            let base = FIL_HEADER_SIZE + 96;
+            data[base..base + 4].copy_from_slice(&2u32.to_be_bytes());
+            data[base + 10..base + 16].copy_from_slice(&[0, 0, 0, 8, 0, 38]);
+        });
+        let file = util::TempFile::new("inode_pages", &data)?;
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data))?;
+        assert_eq!(fact.inode_page_numbers()?, vec![2, 8]);
+        let audit = fact.audit_xdes()?;
+        assert_eq!(audit.conflicts, vec![(7, 0)]);
+        assert_eq!(audit.bad_frags, vec![(8, 1, 1000)]);
+
+        let mut app = App::new(file.path.clone());
+        assert!(app.run(Commands::Audit).is_err());
+        Ok(())
     }
 
     #[test]
-    fn view_sdi_page() {
+    fn dump_count_rows() -> Result<()> {
         util::init_unit_test();
-        let mut app = App::new(PathBuf::from(IBD_01));
-        assert!(app.run(Commands::View { page_no: 3 }).is_ok());
+        let app = App::new(PathBuf::from(IBD_02));
+        let stat = app.do_dump_btree(4, BTREE_MAX_DEPTH)?;
+        assert_eq!(app.do_dump_count(None)?, stat.n_leaf_recs);
+        assert_eq!(app.do_dump_count(Some(4))?, 24);
+        Ok(())
     }
 
     #[test]
@@ -966,6 +4557,23 @@ mod app_tests {
             garbage: false,
             verbose: false,
             btree_root: None,
+            tz: None,
+            columns: vec![],
+            raw_bytes: false,
+            format: DumpFormats::TEXT,
+            trx_id: None,
+            csv_delim: ',',
+            no_header: false,
+            count: false,
+            full: false,
+            with_raw: true,
+            layout: true,
+            jobs: 1,
+            null_as: "NULL".into(),
+            trim: false,
+            only_leaf: false,
+            only_internal: false,
+            max_depth: BTREE_MAX_DEPTH,
         });
         assert!(ans.is_ok());
     }
@@ -978,6 +4586,42 @@ mod app_tests {
         assert!(ans.is_ok());
     }
 
+    #[test]
+    fn dump_redo_log_types() {
+        util::init_unit_test();
+        let app = App::new(PathBuf::from(REDO_1));
+        let n_dump = |s: &str| app.do_dump_log_records(&s.parse().unwrap()).unwrap();
+        let n_insert = n_dump("MLOG_REC_INSERT");
+        let n_delete = n_dump("MLOG_REC_DELETE");
+        assert!(n_insert > 0 && n_delete > 0);
+        assert_eq!(
+            n_dump("MLOG_REC_INSERT,MLOG_REC_DELETE"),
+            n_insert + n_delete
+        );
+        assert!(n_dump("ALL") >= n_insert + n_delete);
+
+        // every record is tested, not only the first record of each block
+        let mut fact = DatafileFactory::from_file(PathBuf::from(REDO_1)).unwrap();
+        let log_file = LogFile::new(0, fact.file_buffer().unwrap());
+        let n_typed = log_file
+            .records()
+            .filter(|rec| rec.log_rec_hdr.log_rec_type != LogRecordTypes::UNDEF)
+            .count();
+        assert_eq!(n_dump("ALL"), n_typed);
+        let n_first_insert = log_file
+            .log_block_list
+            .iter()
+            .filter(|blk| {
+                matches!(blk, Blocks::Block(b) if b.log_record.as_ref().is_some_and(
+                    |rec| rec.log_rec_hdr.log_rec_type == LogRecordTypes::MLOG_REC_INSERT))
+            })
+            .count();
+        assert!(n_insert > n_first_insert);
+        assert!("MLOG_REC_INSERT,NO_SUCH_TYPE"
+            .parse::<LogTypeFilter>()
+            .is_err());
+    }
+
     #[test]
     fn view_redo_log_block_0() {
         util::init_unit_test();
@@ -990,7 +4634,12 @@ mod app_tests {
     fn it_works() {
         util::init_unit_test();
         let mut app = App::new(PathBuf::from(IBD_02));
-        assert!(app.run(Commands::Desc).is_ok());
+        assert!(app
+            .run(Commands::Desc {
+                columns_only: false,
+                verbose: false,
+            })
+            .is_ok());
         assert!(app
             .run(Commands::Dump {
                 page_no: Some(4),
@@ -998,6 +4647,23 @@ mod app_tests {
                 garbage: false,
                 verbose: false,
                 btree_root: None,
+                tz: None,
+                columns: vec![],
+                raw_bytes: false,
+                format: DumpFormats::TEXT,
+                trx_id: None,
+                csv_delim: ',',
+                no_header: false,
+                count: false,
+                full: false,
+                with_raw: false,
+                layout: false,
+                jobs: 1,
+                null_as: "NULL".into(),
+                trim: false,
+                only_leaf: false,
+                only_internal: false,
+                max_depth: BTREE_MAX_DEPTH,
             })
             .is_ok());
     }
@@ -1,5 +1,6 @@
 use std::{
-    collections::HashMap,
+    cmp::max,
+    collections::{BTreeMap, HashMap},
     fs::File,
     io::{Read, Seek, SeekFrom},
     path::PathBuf,
@@ -13,36 +14,84 @@ use log::{debug, info, warn};
 use crate::{
     ibd::{
         page::{
-            BasePage, BasePageBody, FilePageHeader, FileSpaceHeaderPageBody, INodeEntry,
-            INodePageBody, IndexHeader, IndexPageBody, SdiPageBody, XDesEntry, XDesPageBody,
-            FIL_HEADER_SIZE, INDEX_HEADER_SIZE, PAGE_NONE, UNIV_PAGE_SIZE,
+            BasePage, BasePageBody, CompressionTypes, FilePageHeader, FilePageTrailer,
+            FileSpaceHeaderPageBody, INodeEntry, INodePageBody, IndexHeader, IndexPageBody,
+            PageCompressionHeader, PageNumber, PageTypes, RSegArrayPageBody, RSegHeaderPageBody,
+            RecordSource, SdiPageBody, SpaceId, UndoPageHeader, UndoPageStates, UndoPageTypes,
+            UndoSegmentHeader, XDesEntry, XDesPageBody, EXTENT_PAGE_NUM, FIL_HEADER_SIZE,
+            FIL_TRAILER_SIZE, FSP_RSEG_ARRAY_PAGE_NO, INDEX_HEADER_SIZE, INF_PAGE_BYTE_OFF,
+            PAGE_NONE, RECORD_HEADER_SIZE, SUP_PAGE_BYTE_OFF, UNIV_PAGE_SIZE, XDES_ENTRY_MAX_COUNT,
+            XDES_PAGE_COUNT,
         },
-        record::{DataValue, ResultSet},
+        record::{DataValue, Record, RecordHeader, RecordStatus, ResultSet},
         redo::{Blocks, LogBlock, LogCheckpoint, LogFileHeader, OS_FILE_LOG_BLOCK_SIZE},
-        undo::RollPtr,
+        sdi::{SdiDataHeader, SdiExternRef, SdiRecord, BTR_BLOB_HDR_SIZE},
+        undo::{RollPtr, UndoLogHeader},
     },
     meta::{
         cst::coll_find,
         def::{ColumnDef, ColumnTypes, HiddenTypes, IndexDef, IndexElementDef, TableDef},
     },
-    sdi::record::SdiTableObject,
+    sdi::record::{EntryTypes, SdiTableObject, SdiTablespaceObject},
     util::{
-        u32_val, unpack_datetime2_val, unpack_enum_val, unpack_i32_val, unpack_i64_val,
-        unpack_newdate_val, unpack_timestamp2_val, unpack_u48_val, unpack_u56_val,
+        self, u32_val, unpack_datetime2_val, unpack_enum_val, unpack_i16_val, unpack_i24_val,
+        unpack_i32_val, unpack_i64_val, unpack_i8_val, unpack_newdate_val, unpack_timestamp2_val,
+        unpack_u32_val, unpack_u48_val, unpack_u56_val, unpack_u64_val,
     },
 };
 
 pub const SDI_META_INFO_MIN_VER: u32 = 80000;
 
+/// a page differs between two datafiles, the `None` side means the page is
+/// beyond the end of that file
+#[derive(Debug, Clone)]
+pub struct PageDiff {
+    /// page number
+    pub page_no: usize,
+
+    /// page type and LSN in this file
+    pub this: Option<(PageTypes, u64)>,
+
+    /// page type and LSN in the other file
+    pub other: Option<(PageTypes, u64)>,
+}
+
+/// result of cross-checking the XDES free bitmap against the segments
+#[derive(Debug, Default)]
+pub struct XdesAudit {
+    /// (page_no, xdes_no) of the pages marked free but used by a segment
+    pub conflicts: Vec<(usize, usize)>,
+
+    /// (inode page_no, fseg_id, page_no) of the fragment pages beyond the
+    /// end of the tablespace
+    pub bad_frags: Vec<(usize, u64, usize)>,
+}
+
+impl XdesAudit {
+    pub fn is_ok(&self) -> bool {
+        self.conflicts.is_empty() && self.bad_frags.is_empty()
+    }
+}
+
+impl PageDiff {
+    /// LSN delta, other - this
+    pub fn lsn_delta(&self) -> Option<i128> {
+        match (&self.this, &self.other) {
+            (Some(a), Some(b)) => Some(b.1 as i128 - a.1 as i128),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct DatafileFactory {
-    /// target datafile
+    /// target datafile, the first one if the tablespace is split
     pub target: PathBuf,
 
-    /// data file handler
-    pub file_handler: File,
+    /// data file handlers and sizes, the pages are laid out in file order
+    pub datafiles: Vec<(File, usize)>,
 
-    /// data file size
+    /// data file size, sum of all datafiles
     pub file_size: usize,
 
     /// segment descriptor cache, the inode cache, map[page_no, boffset] => INodeEntry
@@ -50,33 +99,160 @@ pub struct DatafileFactory {
 
     /// extent descriptor cache, map[page_no, boffset] => XDesEntry
     pub extent_cache: HashMap<usize, HashMap<u16, XDesEntry>>,
+
+    /// table definition cache, loaded from SDI at the first use
+    pub tabdef_cache: Option<Arc<TableDef>>,
+
+    /// hit/miss counters of the inode and extent caches
+    pub cache_stat: CacheStats,
+
+    /// in-memory data, used instead of the datafiles if exists
+    pub membuf: Option<Bytes>,
+}
+
+/// undo segment in a undo slot of the rollback segment
+#[derive(Debug, Clone)]
+pub struct UndoSegInfo {
+    /// rollback segment number in the RSEG array
+    pub rseg_no: usize,
+
+    /// rollback segment header page
+    pub rseg_page: usize,
+
+    /// undo slot number in the rollback segment
+    pub slot: usize,
+
+    /// the first undo log page of the segment
+    pub undo_page: usize,
+
+    /// insert or update undo
+    pub page_type: UndoPageTypes,
+
+    /// undo segment state
+    pub state: UndoPageStates,
+
+    /// transaction id of the last undo log
+    pub trx_id: Option<u64>,
+
+    /// transaction number of the last undo log, assigned at commit
+    pub trx_no: Option<u64>,
+}
+
+/// cache statistics of the datafile factory
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    /// inode cache hits
+    pub inode_hits: usize,
+
+    /// inode cache misses, the INODE page is read
+    pub inode_misses: usize,
+
+    /// cached inode entries
+    pub inode_entries: usize,
+
+    /// extent cache hits
+    pub extent_hits: usize,
+
+    /// extent cache misses, the XDES page is read
+    pub extent_misses: usize,
+
+    /// cached extent entries
+    pub extent_entries: usize,
 }
 
 impl DatafileFactory {
     /// construct the datafile factory
     pub fn from_file(target: PathBuf) -> Result<Self> {
-        if !target.exists() {
-            return Err(Error::msg(format!("没有找到目标文件: {:?}", target)));
+        Self::from_files(vec![target])
+    }
+
+    /// construct the datafile factory from multiple files, which presents a
+    /// contiguous page space, e.g. ibdata1;ibdata2
+    pub fn from_files(targets: Vec<PathBuf>) -> Result<Self> {
+        if targets.is_empty() {
+            return Err(Error::msg("没有指定目标文件"));
         }
 
-        let file = File::open(&target)?;
-        let size = file.metadata()?.len() as usize;
+        let mut datafiles = Vec::with_capacity(targets.len());
+        for (i, target) in targets.iter().enumerate() {
+            if !target.exists() {
+                return Err(Error::msg(format!("没有找到目标文件: {:?}", target)));
+            }
+
+            let file = File::open(target)?;
+            let size = file.metadata()?.len() as usize;
+            if i + 1 < targets.len() && !size.is_multiple_of(UNIV_PAGE_SIZE) {
+                return Err(Error::msg(format!(
+                    "数据文件大小未按页对齐: {:?}, size={}",
+                    target, size
+                )));
+            }
+
+            info!("加载数据文件: {:?}", &file);
+            datafiles.push((file, size));
+        }
 
-        info!("加载数据文件: {:?}", &file);
+        Ok(Self {
+            target: targets[0].clone(),
+            file_size: datafiles.iter().map(|f| f.1).sum(),
+            datafiles,
+            inode_cache: HashMap::new(),
+            extent_cache: HashMap::new(),
+            tabdef_cache: None,
+            cache_stat: CacheStats::default(),
+            membuf: None,
+        })
+    }
 
+    /// construct the datafile factory from in-memory bytes, e.g. a page read
+    /// from stdin
+    pub fn from_bytes(buf: Bytes) -> Result<Self> {
+        if buf.is_empty() {
+            return Err(Error::msg("没有输入数据"));
+        }
         Ok(Self {
-            target,
-            file_size: size,
-            file_handler: file,
+            target: PathBuf::from("-"),
+            file_size: buf.len(),
+            datafiles: vec![],
             inode_cache: HashMap::new(),
             extent_cache: HashMap::new(),
+            tabdef_cache: None,
+            cache_stat: CacheStats::default(),
+            membuf: Some(buf),
         })
     }
 
+    /// read bytes at the logical offset, translate into (file index, offset)
+    fn read_buffer(&mut self, offset: usize, len: usize) -> Result<Arc<Bytes>> {
+        if let Some(mem) = &self.membuf {
+            if offset + len > mem.len() {
+                return Err(Error::msg(format!("文件偏移溢出: offset={}", offset)));
+            }
+            return Ok(Arc::new(mem.slice(offset..offset + len)));
+        }
+        let mut base = 0;
+        for (file, size) in self.datafiles.iter_mut() {
+            if offset < base + *size {
+                file.seek(SeekFrom::Start((offset - base) as u64))?;
+                let mut buffer = vec![0; len];
+                file.read_exact(&mut buffer)?;
+                return Ok(Arc::new(Bytes::from(buffer)));
+            }
+            base += *size;
+        }
+        Err(Error::msg(format!("文件偏移溢出: offset={}", offset)))
+    }
+
     /// get file buffer
     pub fn file_buffer(&mut self) -> Result<Arc<Bytes>> {
+        if let Some(mem) = &self.membuf {
+            return Ok(Arc::new(mem.clone()));
+        }
         let mut buffer = vec![];
-        self.file_handler.read_to_end(&mut buffer)?;
+        for (file, _) in self.datafiles.iter_mut() {
+            file.seek(SeekFrom::Start(0))?;
+            file.read_to_end(&mut buffer)?;
+        }
         Ok(Arc::new(Bytes::from(buffer)))
     }
 
@@ -91,12 +267,7 @@ impl DatafileFactory {
             return Err(Error::msg(format!("块号范围溢出: block_no={}", block_no)));
         }
 
-        let offset = (block_no * OS_FILE_LOG_BLOCK_SIZE) as u64;
-        self.file_handler.seek(SeekFrom::Start(offset))?;
-
-        let mut buffer = vec![0; OS_FILE_LOG_BLOCK_SIZE];
-        self.file_handler.read_exact(&mut buffer)?;
-        Ok(Arc::new(Bytes::from(buffer)))
+        self.read_buffer(block_no * OS_FILE_LOG_BLOCK_SIZE, OS_FILE_LOG_BLOCK_SIZE)
     }
 
     /// count the page
@@ -110,11 +281,24 @@ impl DatafileFactory {
             return Err(Error::msg(format!("页码范围溢出: page_no={}", page_no)));
         }
 
-        let offset = (page_no * UNIV_PAGE_SIZE) as u64;
-        self.file_handler.seek(SeekFrom::Start(offset))?;
+        self.read_buffer(page_no * UNIV_PAGE_SIZE, UNIV_PAGE_SIZE)
+    }
+
+    /// get raw bytes of the page range, both ends are inclusive
+    pub fn pages_buffer(&mut self, from: usize, to: usize) -> Result<Arc<Bytes>> {
+        if from > to || to >= self.page_count() {
+            return Err(Error::msg(format!(
+                "页码范围溢出: {}-{}, page_count={}",
+                from,
+                to,
+                self.page_count()
+            )));
+        }
 
-        let mut buffer = vec![0; UNIV_PAGE_SIZE];
-        self.file_handler.read_exact(&mut buffer)?;
+        let mut buffer = Vec::with_capacity((to - from + 1) * UNIV_PAGE_SIZE);
+        for page_no in from..=to {
+            buffer.extend_from_slice(&self.page_buffer(page_no)?);
+        }
         Ok(Arc::new(Bytes::from(buffer)))
     }
 
@@ -124,12 +308,18 @@ impl DatafileFactory {
             return Err(Error::msg(format!("页码范围溢出: page_no={}", page_no)));
         }
 
-        let offset = (page_no * UNIV_PAGE_SIZE) as u64;
-        self.file_handler.seek(SeekFrom::Start(offset))?;
+        self.read_buffer(page_no * UNIV_PAGE_SIZE, FIL_HEADER_SIZE)
+    }
 
-        let mut buffer = vec![0; FIL_HEADER_SIZE];
-        self.file_handler.read_exact(&mut buffer)?;
-        Ok(Arc::new(Bytes::from(buffer)))
+    pub fn fil_trl_buffer(&mut self, page_no: usize) -> Result<Arc<Bytes>> {
+        if page_no >= self.page_count() {
+            return Err(Error::msg(format!("页码范围溢出: page_no={}", page_no)));
+        }
+
+        self.read_buffer(
+            (page_no + 1) * UNIV_PAGE_SIZE - FIL_TRAILER_SIZE,
+            FIL_TRAILER_SIZE,
+        )
     }
 
     pub fn idx_hdr_buffer(&mut self, page_no: usize) -> Result<Arc<Bytes>> {
@@ -137,12 +327,10 @@ impl DatafileFactory {
             return Err(Error::msg(format!("页码范围溢出: page_no={}", page_no)));
         }
 
-        let offset = (page_no * UNIV_PAGE_SIZE + FIL_HEADER_SIZE) as u64;
-        self.file_handler.seek(SeekFrom::Start(offset))?;
-
-        let mut buffer = vec![0; INDEX_HEADER_SIZE];
-        self.file_handler.read_exact(&mut buffer)?;
-        Ok(Arc::new(Bytes::from(buffer)))
+        self.read_buffer(
+            page_no * UNIV_PAGE_SIZE + FIL_HEADER_SIZE,
+            INDEX_HEADER_SIZE,
+        )
     }
 
     pub fn read_fil_hdr(&mut self, page_no: usize) -> Result<FilePageHeader> {
@@ -150,6 +338,11 @@ impl DatafileFactory {
         Ok(FilePageHeader::new(0, buf.clone()))
     }
 
+    pub fn read_fil_trl(&mut self, page_no: usize) -> Result<FilePageTrailer> {
+        let buf = self.fil_trl_buffer(page_no)?;
+        Ok(FilePageTrailer::new(0, buf.clone()))
+    }
+
     pub fn read_idx_hdr(&mut self, page_no: usize) -> Result<IndexHeader> {
         let buf = self.idx_hdr_buffer(page_no)?;
         Ok(IndexHeader::new(0, buf.clone()))
@@ -159,10 +352,104 @@ impl DatafileFactory {
     where
         P: BasePageBody,
     {
-        let buf = self.page_buffer(page_no)?;
+        let mut buf = self.page_buffer(page_no)?;
+        if FilePageHeader::new(0, buf.clone()).page_type == PageTypes::COMPRESSED {
+            buf = Self::uncompress_page(&buf)?;
+        }
         Ok(BasePage::new(0, buf.clone()))
     }
 
+    /// restore the page of page-level compression, which is identified by the
+    /// page type, not by the zip_size of FSP flags (ROW_FORMAT=COMPRESSED).
+    /// The FIL header is kept with the original page type, and the body is
+    /// inflated after it
+    pub fn uncompress_page(buf: &Arc<Bytes>) -> Result<Arc<Bytes>> {
+        let comp_hdr = PageCompressionHeader::new(0, buf.clone());
+        debug!("comp_hdr={:?}", &comp_hdr);
+        let beg = FIL_HEADER_SIZE;
+        let end = beg + comp_hdr.compress_size as usize;
+        if end > buf.len() {
+            return Err(Error::msg(format!(
+                "压缩数据长度溢出: compress_size={}",
+                comp_hdr.compress_size
+            )));
+        }
+        let body = match comp_hdr.algorithm {
+            CompressionTypes::NONE => buf[beg..end].to_vec(),
+            CompressionTypes::ZLIB => util::zlib_uncomp_bytes(&buf[beg..end])?,
+            CompressionTypes::LZ4 => {
+                util::lz4_uncomp_bytes(&buf[beg..end], comp_hdr.original_size as usize)?
+            }
+            _ => {
+                return Err(Error::msg(format!(
+                    "不支持的页面压缩算法: {}",
+                    comp_hdr.algorithm
+                )));
+            }
+        };
+        if body.len() != comp_hdr.original_size as usize {
+            return Err(Error::msg(format!(
+                "解压后的数据长度不一致: expect={}, actual={}",
+                comp_hdr.original_size,
+                body.len()
+            )));
+        }
+
+        let mut page = buf[..beg].to_vec();
+        page[24..26].copy_from_slice(&(comp_hdr.original_type as u16).to_be_bytes());
+        page.extend_from_slice(&body);
+        page.resize(UNIV_PAGE_SIZE, 0);
+        Ok(Arc::new(Bytes::from(page)))
+    }
+
+    /// walk the undo tablespace: RSEG array page => RSEG header pages => undo
+    /// slots => undo segments
+    pub fn undo_segments(&mut self) -> Result<Vec<UndoSegInfo>> {
+        let space_id = self.read_fil_hdr(0)?.space_id;
+        if !matches!(space_id, SpaceId::UndoSpace(_)) {
+            return Err(Error::msg(format!(
+                "不是 undo 表空间: space_id={}",
+                space_id
+            )));
+        }
+        let page_type = self.read_fil_hdr(FSP_RSEG_ARRAY_PAGE_NO)?.page_type;
+        if page_type != PageTypes::RSEG_ARRAY {
+            return Err(Error::msg(format!(
+                "不是回滚段数组页: page_type={}",
+                page_type
+            )));
+        }
+
+        let mut ret = vec![];
+        let rseg_array: BasePage<RSegArrayPageBody> = self.read_page(FSP_RSEG_ARRAY_PAGE_NO)?;
+        for (rseg_no, rseg_page) in &rseg_array.page_body.arr_slots {
+            let rseg_page = usize::from(*rseg_page);
+            let rseg: BasePage<RSegHeaderPageBody> = self.read_page(rseg_page)?;
+            for (slot, undo_page) in &rseg.page_body.undo_slots {
+                // only the headers are parsed, the undo records are skipped
+                let undo_page = usize::from(*undo_page);
+                let buf = self.page_buffer(undo_page)?;
+                let page_hdr = UndoPageHeader::new(FIL_HEADER_SIZE, buf.clone());
+                let seg_hdr = UndoSegmentHeader::new(FIL_HEADER_SIZE + 18, buf.clone());
+                let log_hdr = match seg_hdr.undo_last_log as usize {
+                    0 => None,
+                    addr => Some(UndoLogHeader::new(addr, buf.clone())),
+                };
+                ret.push(UndoSegInfo {
+                    rseg_no: *rseg_no,
+                    rseg_page,
+                    slot: *slot,
+                    undo_page,
+                    page_type: page_hdr.page_type,
+                    state: seg_hdr.undo_state,
+                    trx_id: log_hdr.as_ref().map(|hdr| hdr.trx_id),
+                    trx_no: log_hdr.as_ref().map(|hdr| hdr.trx_no),
+                });
+            }
+        }
+        Ok(ret)
+    }
+
     pub fn read_block(&mut self, block_no: usize) -> Result<Blocks> {
         let buf = self.block_buffer(block_no)?;
         let data = match block_no {
@@ -176,11 +463,15 @@ impl DatafileFactory {
 
     pub fn read_inode_entry(&mut self, page_no: usize, boffset: u16) -> Result<INodeEntry> {
         let inode = match self.inode_cache.get(&page_no) {
-            Some(inode_map) => inode_map
-                .get(&boffset)
-                .expect("未找到 INodeEntry 数据项")
-                .clone(),
+            Some(inode_map) => {
+                self.cache_stat.inode_hits += 1;
+                inode_map
+                    .get(&boffset)
+                    .expect("未找到 INodeEntry 数据项")
+                    .clone()
+            }
             None => {
+                self.cache_stat.inode_misses += 1;
                 let inode_map = self
                     .read_page::<INodePageBody>(page_no)?
                     .page_body
@@ -202,11 +493,15 @@ impl DatafileFactory {
 
     pub fn read_xdes_entry(&mut self, page_no: usize, boffset: u16) -> Result<XDesEntry> {
         let xdes = match self.extent_cache.get(&page_no) {
-            Some(xdes_map) => xdes_map
-                .get(&boffset)
-                .expect("未找到 XDesEntry 数据项")
-                .clone(),
+            Some(xdes_map) => {
+                self.cache_stat.extent_hits += 1;
+                xdes_map
+                    .get(&boffset)
+                    .expect("未找到 XDesEntry 数据项")
+                    .clone()
+            }
             None => {
+                self.cache_stat.extent_misses += 1;
                 let xdes_map = self
                     .read_page::<XDesPageBody>(page_no)?
                     .page_body
@@ -223,222 +518,1768 @@ impl DatafileFactory {
             }
         };
 
-        Ok(xdes)
+        Ok(xdes)
+    }
+
+    /// hit/miss counters and number of cached entries of the inode and
+    /// extent caches
+    pub fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            inode_entries: self.inode_cache.values().map(|m| m.len()).sum(),
+            extent_entries: self.extent_cache.values().map(|m| m.len()).sum(),
+            ..self.cache_stat.clone()
+        }
+    }
+
+    /// check the tablespace is temporary by FSP flags or space_id
+    pub fn is_temporary(&mut self) -> Result<bool> {
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = self.read_page(0)?;
+        let fsp_hdr = &fsp_page.page_body.fsp_hdr;
+        Ok(fsp_hdr.fsp_flags.temporary
+            || matches!(
+                fsp_hdr.space_id,
+                SpaceId::InnoTempSpace | SpaceId::TempSpace(_)
+            ))
+    }
+
+    /// the system tablespace, e.g. ibdata1
+    pub fn is_system(&mut self) -> Result<bool> {
+        let fil_hdr = self.read_fil_hdr(0)?;
+        Ok(matches!(fil_hdr.space_id, SpaceId::SystemSpace))
+    }
+
+    fn read_sdi_page(&mut self) -> Result<BasePage<SdiPageBody>, Error> {
+        if self.is_temporary()? {
+            return Err(Error::msg("临时表空间不包含 SDI，没有表元信息"));
+        }
+        if self.is_system()? {
+            return Err(Error::msg("系统表空间不包含 SDI，没有表元信息"));
+        }
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = self.read_page(0)?;
+        if fsp_page.fil_hdr.server_version() < SDI_META_INFO_MIN_VER {
+            return Err(Error::msg("数据文件版本过低，没有表元信息"));
+        }
+        if !fsp_page.page_body.fsp_hdr.fsp_flags.sdi {
+            return Err(Error::msg("表空间未设置 SDI 标志，没有表元信息"));
+        }
+        let sdi_meta = fsp_page.page_body.sdi_meta();
+        let sdi_page_no: usize = sdi_meta.sdi_page_no.into();
+        self.read_page(sdi_page_no)
+    }
+
+    /// read the externally stored part of SDI data. The SDI_BLOB pages are
+    /// chained by the BLOB header, the SDI_ZBLOB pages are chained by the file
+    /// header and inflated as one zlib stream
+    pub fn read_sdi_extern(&mut self, ext: &SdiExternRef) -> Result<Vec<u8>> {
+        // the compressed page is zip_size bytes on disk and has no trailer
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = self.read_page(0)?;
+        let zip_size = fsp_page.page_body.fsp_hdr.fsp_flags.zip_size();
+        let (page_size, data_end) = if zip_size > 0 {
+            (zip_size, zip_size)
+        } else {
+            (UNIV_PAGE_SIZE, UNIV_PAGE_SIZE - FIL_TRAILER_SIZE)
+        };
+
+        let mut page_no = ext.page_no as usize;
+        let mut offset = ext.offset as usize;
+        let mut data = vec![];
+        let mut first_type = None;
+        let mut n_pages = 0;
+        while page_no != PAGE_NONE as usize {
+            n_pages += 1;
+            if n_pages > self.file_size / page_size {
+                return Err(Error::msg(format!(
+                    "SDI 外部页链表存在环: page_no={}",
+                    page_no
+                )));
+            }
+            let buf = self.read_buffer(page_no * page_size, page_size)?;
+            let fil_hdr = FilePageHeader::new(0, buf.clone());
+            if page_no == ext.page_no as usize {
+                first_type = Some(fil_hdr.page_type.clone());
+            }
+            let overflow = |len: usize| {
+                Error::msg(format!(
+                    "SDI 外部数据越界: page_no={}, offset={}, len={}",
+                    page_no, offset, len
+                ))
+            };
+            match fil_hdr.page_type {
+                PageTypes::SDI_BLOB => {
+                    let blob_hdr = buf
+                        .get(offset..offset + BTR_BLOB_HDR_SIZE)
+                        .ok_or_else(|| overflow(BTR_BLOB_HDR_SIZE))?;
+                    let part_len = u32_val(blob_hdr, 0) as usize;
+                    let beg = offset + BTR_BLOB_HDR_SIZE;
+                    let part = buf[..data_end]
+                        .get(beg..beg + part_len)
+                        .ok_or_else(|| overflow(part_len))?;
+                    data.extend_from_slice(part);
+                    page_no = u32_val(blob_hdr, 4) as usize;
+                }
+                PageTypes::SDI_ZBLOB => {
+                    let part = buf[..data_end]
+                        .get(offset..)
+                        .ok_or_else(|| overflow(data_end.saturating_sub(offset)))?;
+                    data.extend_from_slice(part);
+                    page_no = fil_hdr.next_page as usize;
+                }
+                _ => {
+                    return Err(Error::msg(format!(
+                        "不支持的 SDI 外部页类型: page_no={}, page_type={}",
+                        page_no, fil_hdr.page_type
+                    )));
+                }
+            }
+            offset = FIL_HEADER_SIZE;
+        }
+
+        if matches!(first_type, Some(PageTypes::SDI_ZBLOB)) {
+            data = util::zlib_uncomp_bytes(&data)?;
+        }
+        if data.len() != ext.ext_len() {
+            return Err(Error::msg(format!(
+                "SDI 外部数据长度不一致: expect={}, actual={}",
+                ext.ext_len(),
+                data.len()
+            )));
+        }
+        Ok(data)
+    }
+
+    /// read the SDI objects, the externally stored data is resolved
+    pub fn read_sdi_objects(&mut self) -> Result<Vec<SdiRecord>> {
+        let sdi_page = self.read_sdi_page()?;
+        let mut objects = sdi_page.page_body.read_sdi_objects()?;
+        for obj in objects.iter_mut() {
+            if let Some(ext) = obj.sdi_ext.clone() {
+                let ext_data = self.read_sdi_extern(&ext)?;
+                obj.resolve(&ext_data)?;
+            }
+        }
+        Ok(objects)
+    }
+
+    /// read the SDI record headers on the SDI page, the data is untouched
+    pub fn read_sdi_headers(&mut self) -> Result<Vec<(RecordHeader, SdiDataHeader, bool)>> {
+        self.read_sdi_page()?.page_body.read_sdi_headers()
+    }
+
+    /// find the SDI string of given entry type
+    fn load_sdi_entry(&mut self, data_type: EntryTypes) -> Result<String> {
+        self.read_sdi_objects()?
+            .into_iter()
+            .find(|obj| obj.sdi_hdr.data_type == data_type)
+            .map(|obj| obj.sdi_str)
+            .ok_or(Error::msg(format!("未找到 SDI 对象: {:?}", data_type)))
+    }
+
+    /// load SDI json string, pretty printed if not compact
+    pub fn load_sdi_string(&mut self, compact: bool) -> Result<Vec<String>, Error> {
+        let ret: Vec<String> = self
+            .read_sdi_objects()?
+            .iter()
+            .map(|obj| {
+                if compact {
+                    jsonxf::minimize(&obj.sdi_str).unwrap_or(obj.sdi_str.clone())
+                } else {
+                    jsonxf::pretty_print(&obj.sdi_str).unwrap_or("".into())
+                }
+            })
+            .collect();
+        Ok(ret)
+    }
+
+    /// concatenate the raw SDI objects into the ibd2sdi output format, which is
+    /// ["ibd2sdi", {"type": 1, "id": 10, "object": {...}}, ...]
+    pub fn load_sdi_raw_json(&mut self) -> Result<String> {
+        let mut ret = String::from("[\"ibd2sdi\"");
+        for obj in self.read_sdi_objects()? {
+            ret.push_str(&format!(
+                ",{{\"type\":{},\"id\":{},\"object\":{}}}",
+                obj.sdi_hdr.data_type as u32,
+                obj.sdi_hdr.data_id,
+                obj.sdi_str.trim_end_matches('\0')
+            ));
+        }
+        ret.push(']');
+        Ok(ret)
+    }
+
+    pub fn load_tablespace_object(&mut self) -> Result<SdiTablespaceObject> {
+        let sdi_str = self.load_sdi_entry(EntryTypes::Tablespace)?;
+        SdiTablespaceObject::from_str(&sdi_str)
+    }
+
+    pub fn load_table_def(&mut self) -> Result<Arc<TableDef>> {
+        if let Some(tabdef) = &self.tabdef_cache {
+            return Ok(tabdef.clone());
+        }
+        let tabdef = self.parse_table_def(true)?;
+        self.tabdef_cache = Some(tabdef.clone());
+        Ok(tabdef)
+    }
+
+    /// load the table definition without the indexes, which is not cached
+    pub fn load_table_columns(&mut self) -> Result<Arc<TableDef>> {
+        if let Some(tabdef) = &self.tabdef_cache {
+            return Ok(tabdef.clone());
+        }
+        self.parse_table_def(false)
+    }
+
+    fn parse_table_def(&mut self, with_index: bool) -> Result<Arc<TableDef>> {
+        let sdi_str = self.load_sdi_entry(EntryTypes::Table)?;
+        let dd_object = SdiTableObject::from_str(&sdi_str)?.dd_object;
+        debug!("dd_object={:#?}", &dd_object);
+
+        let coll = coll_find(dd_object.collation_id);
+        info!("当前文件字符集: {:?}", &coll);
+
+        let coldefs = dd_object
+            .columns
+            .iter()
+            .map(ColumnDef::from)
+            .collect::<Vec<_>>();
+        let idxdefs = dd_object
+            .indexes
+            .iter()
+            .filter(|_| with_index)
+            .map(|idx| {
+                let ele_defs: Vec<IndexElementDef> = idx
+                    .elements
+                    .iter()
+                    .map(|ele| {
+                        let ref_col = &coldefs[ele.column_opx as usize];
+                        IndexElementDef::from(ele, ref_col)
+                    })
+                    .collect();
+
+                IndexDef::from(idx, ele_defs)
+            })
+            .collect();
+        debug!("idxdefs={:?}", &idxdefs);
+
+        Ok(Arc::from(TableDef::from(
+            &dd_object, coll, coldefs, idxdefs,
+        )))
+    }
+
+    /// check the index root page in SDI is an INDEX page with the matching
+    /// index id, return (index name, root page, mismatch message)
+    pub fn verify_index_roots(&mut self) -> Result<Vec<(String, i32, Option<String>)>> {
+        let tabdef = self.load_table_def()?;
+        let mut ret = vec![];
+        for idxdef in &tabdef.idx_defs {
+            let root = idxdef.idx_root;
+            let err = if root <= 0 || root as usize >= self.page_count() {
+                Some(format!("错误的索引根页码: root={}", root))
+            } else {
+                let fil_hdr = self.read_fil_hdr(root as usize)?;
+                if fil_hdr.page_type != PageTypes::INDEX {
+                    Some(format!("根页不是索引页: page_type={}", fil_hdr.page_type))
+                } else {
+                    let idx_hdr = self.read_idx_hdr(root as usize)?;
+                    if idx_hdr.page_index_id != idxdef.idx_id as u64 {
+                        Some(format!(
+                            "索引ID不一致: sdi={}, page={}",
+                            idxdef.idx_id, idx_hdr.page_index_id
+                        ))
+                    } else {
+                        None
+                    }
+                }
+            };
+            ret.push((idxdef.idx_name.clone(), root, err));
+        }
+        Ok(ret)
+    }
+
+    /// scan the FIL header of all pages, return the pages whose space_id
+    /// differs from page 0, the allocated pages are skipped
+    pub fn verify_space_ids(&mut self) -> Result<Vec<(usize, SpaceId)>> {
+        let space_id = self.read_fil_hdr(0)?.space_id;
+        let mut ret = vec![];
+        for page_no in 1..self.page_count() {
+            let fil_hdr = self.read_fil_hdr(page_no)?;
+            if fil_hdr.page_type == PageTypes::ALLOCATED {
+                continue;
+            }
+            if fil_hdr.space_id != space_id {
+                ret.push((page_no, fil_hdr.space_id));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// compare the low 32 bits of the FIL header LSN with the trailer of all
+    /// pages, return the torn pages with the header LSN and the trailer value
+    pub fn verify_trailer_lsns(&mut self) -> Result<Vec<(usize, u64, u32)>> {
+        let mut ret = vec![];
+        for page_no in 0..self.page_count() {
+            let buf = self.page_buffer(page_no)?;
+            let fil_hdr = FilePageHeader::new(0, buf.clone());
+            let fil_trl = FilePageTrailer::new(buf.len() - FIL_TRAILER_SIZE, buf.clone());
+            if (fil_hdr.lsn & 0xffffffff) as u32 != fil_trl.lsn_low32bit {
+                ret.push((page_no, fil_hdr.lsn, fil_trl.lsn_low32bit));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// check the page checksums, return the corrupted pages with the checksum
+    /// fields of the header and the trailer. The compressed pages of
+    /// ROW_FORMAT=COMPRESSED are skipped
+    pub fn verify_checksums(&mut self) -> Result<Vec<(usize, u32, u32)>> {
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = self.read_page(0)?;
+        if fsp_page.page_body.fsp_hdr.fsp_flags.zip_size() > 0 {
+            warn!("压缩表空间暂不支持页校验和检查, 已跳过");
+            return Ok(vec![]);
+        }
+        let mut ret = vec![];
+        for page_no in 0..self.page_count() {
+            let buf = self.page_buffer(page_no)?;
+            if !util::page_checksum_valid(&buf) {
+                ret.push((page_no, u32_val(&buf, 0), u32_val(&buf, buf.len() - 8)));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// check the FSP header sizes against the file, return the violated
+    /// invariants. The free_limit is initialized by extents, so it can exceed
+    /// the fsp_size of a small file, but not the extent aligned one
+    pub fn verify_fsp_size(&mut self) -> Result<Vec<String>> {
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = self.read_page(0)?;
+        let fsp_hdr = &fsp_page.page_body.fsp_hdr;
+        let fsp_size = fsp_hdr.fsp_size as usize;
+        let free_limit = fsp_hdr.free_limit as usize;
+        let page_count = self.page_count();
+
+        let mut errs = vec![];
+        if free_limit > fsp_size.next_multiple_of(XDES_PAGE_COUNT) {
+            errs.push(format!(
+                "free_limit 超出表空间大小: free_limit={}, fsp_size={}",
+                free_limit, fsp_size
+            ));
+        }
+        if fsp_size > page_count {
+            errs.push(format!(
+                "fsp_size 大于文件页数, 文件可能被截断: fsp_size={}, page_count={}",
+                fsp_size, page_count
+            ));
+        } else if fsp_size < page_count {
+            warn!(
+                "fsp_size 小于文件页数, 可能正在自动扩展: fsp_size={}, page_count={}",
+                fsp_size, page_count
+            );
+        }
+        Ok(errs)
+    }
+
+    /// cross-check the declared page type against the page content, return
+    /// a list of violated invariants (empty when the page looks healthy)
+    pub fn verify_page_type(&mut self, page_no: usize) -> Result<Vec<String>> {
+        let fil_hdr = self.read_fil_hdr(page_no)?;
+        let mut errs = vec![];
+        match fil_hdr.page_type {
+            PageTypes::FSP_HDR if page_no != 0 => {
+                errs.push(format!("FSP_HDR 页必须是 0 号页: page_no={}", page_no));
+            }
+            PageTypes::XDES if !page_no.is_multiple_of(EXTENT_PAGE_NUM) => {
+                errs.push(format!(
+                    "XDES 页码必须是 {} 的倍数: page_no={}",
+                    EXTENT_PAGE_NUM, page_no
+                ));
+            }
+            PageTypes::INDEX | PageTypes::SDI => {
+                let buf = self.page_buffer(page_no)?;
+                if &buf[INF_PAGE_BYTE_OFF..INF_PAGE_BYTE_OFF + 8] != b"infimum\0" {
+                    errs.push("索引页缺少 infimum 记录".into());
+                }
+                if &buf[SUP_PAGE_BYTE_OFF..SUP_PAGE_BYTE_OFF + 8] != b"supremum" {
+                    errs.push("索引页缺少 supremum 记录".into());
+                }
+            }
+            _ => {}
+        }
+        let hdr_page_no: usize = fil_hdr.page_no.into();
+        if !matches!(fil_hdr.page_type, PageTypes::ALLOCATED) && hdr_page_no != page_no {
+            errs.push(format!(
+                "文件头页码不一致: page_no={}, fil_hdr.page_no={}",
+                page_no, hdr_page_no
+            ));
+        }
+        Ok(errs)
+    }
+
+    /// descend to the leftmost leaf page through the first node pointer
+    pub fn first_leaf_page(&mut self, page_no: usize) -> Result<usize> {
+        let mut page_no = page_no;
+        loop {
+            let fil_hdr = self.read_fil_hdr(page_no)?;
+            if fil_hdr.page_type != PageTypes::INDEX {
+                return Err(Error::msg(format!(
+                    "不支持的页类型: page_no={}, page_type={}",
+                    page_no, fil_hdr.page_type
+                )));
+            }
+            if self.read_idx_hdr(page_no)?.page_level == 0 {
+                return Ok(page_no);
+            }
+            let rs = self.unpack_index_page(page_no, false)?;
+            page_no = match rs.node_ptrs()?.first() {
+                Some(node_ptr) => node_ptr.child_page,
+                None => return Err(Error::msg(format!("错误的节点指针: page_no={}", page_no))),
+            };
+        }
+    }
+
+    /// root page number of the clustered index
+    pub fn clustered_root(&mut self) -> Result<usize> {
+        let tabdef = self.load_table_def()?;
+        match tabdef.primary_index() {
+            Some(idxdef) => Ok(idxdef.idx_root as usize),
+            None => Err(Error::msg("没有找到聚簇索引")),
+        }
+    }
+
+    /// page numbers of the leaf chain in logical order, start from the leftmost
+    /// leaf of the given page, only the file headers are read
+    pub fn leaf_chain(&mut self, page_no: usize) -> Result<Vec<usize>> {
+        let mut page_no = self.first_leaf_page(page_no)?;
+        let mut chain = vec![];
+        loop {
+            if chain.len() >= self.page_count() {
+                return Err(Error::msg(format!("叶子页链表存在环: page_no={}", page_no)));
+            }
+            chain.push(page_no);
+            let fil_hdr = self.read_fil_hdr(page_no)?;
+            if fil_hdr.next_page == PAGE_NONE {
+                break;
+            }
+            page_no = fil_hdr.next_page as usize;
+        }
+        Ok(chain)
+    }
+
+    /// page numbers of the XDES pages present in the file, one XDES page (or
+    /// the FSP_HDR page 0) describes every EXTENT_PAGE_NUM pages
+    pub fn xdes_page_numbers(&mut self) -> Result<Vec<usize>> {
+        let mut ret = vec![];
+        for page_no in (0..self.page_count()).step_by(EXTENT_PAGE_NUM) {
+            let fil_hdr = self.read_fil_hdr(page_no)?;
+            match fil_hdr.page_type {
+                PageTypes::FSP_HDR | PageTypes::XDES => ret.push(page_no),
+                _ => warn!(
+                    "XDES 页类型错误: page_no={}, page_type={}",
+                    page_no, fil_hdr.page_type
+                ),
+            }
+        }
+        Ok(ret)
+    }
+
+    /// the maximum page LSN of each index, map[index_id] => max_lsn, all the
+    /// INDEX pages are scanned by the index id in the index header
+    pub fn index_max_lsns(&mut self) -> Result<HashMap<u64, u64>> {
+        let mut ret = HashMap::new();
+        for page_no in 0..self.page_count() {
+            let fil_hdr = self.read_fil_hdr(page_no)?;
+            if fil_hdr.page_type != PageTypes::INDEX {
+                continue;
+            }
+            let idx_hdr = self.read_idx_hdr(page_no)?;
+            let lsn = ret.entry(idx_hdr.page_index_id).or_insert(0);
+            *lsn = max(*lsn, fil_hdr.lsn);
+        }
+        Ok(ret)
+    }
+
+    /// the INDEX pages of each index in page order, map[index_id] => page_nos
+    pub fn index_pages(&mut self) -> Result<BTreeMap<u64, Vec<usize>>> {
+        let mut ret = BTreeMap::new();
+        for page_no in 0..self.page_count() {
+            if self.read_fil_hdr(page_no)?.page_type != PageTypes::INDEX {
+                continue;
+            }
+            let idx_hdr = self.read_idx_hdr(page_no)?;
+            ret.entry(idx_hdr.page_index_id)
+                .or_insert_with(Vec::new)
+                .push(page_no);
+        }
+        Ok(ret)
+    }
+
+    /// count user records in the leaf chain, descend to the leftmost leaf page
+    /// if the page is not leaf, only the record headers are parsed on leaves
+    pub fn count_leaf_records(&mut self, page_no: usize) -> Result<usize> {
+        let mut n_recs = 0;
+        for page_no in self.leaf_chain(page_no)? {
+            let page: BasePage<IndexPageBody> = self.read_page(page_no)?;
+            n_recs += page.page_body.data_rec_hdrs.len();
+        }
+        Ok(n_recs)
+    }
+
+    /// the INODE pages in the FSP_SEG_INODES_FULL and FSP_SEG_INODES_FREE lists
+    /// of the FSP header
+    pub fn inode_page_numbers(&mut self) -> Result<Vec<usize>> {
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = self.read_page(0)?;
+        let fsp_hdr = &fsp_page.page_body.fsp_hdr;
+        let mut ret = vec![];
+        for base in [&fsp_hdr.inodes_full, &fsp_hdr.inodes_free] {
+            let mut faddr = base.first.clone();
+            for _ in 0..base.len {
+                if matches!(faddr.page_no, PageNumber::None) {
+                    break;
+                }
+                let page_no: usize = faddr.page_no.into();
+                if page_no >= self.page_count() || ret.contains(&page_no) {
+                    warn!("INODE 页链表异常: page_no={}", page_no);
+                    break;
+                }
+                ret.push(page_no);
+                let inode_page: BasePage<INodePageBody> = self.read_page(page_no)?;
+                faddr = inode_page.page_body.inode_page_node.next.clone();
+            }
+        }
+        Ok(ret)
+    }
+
+    /// cross-check the XDES free bitmap against the segments of all INODE
+    /// pages. Only the fragment pages and the pages of full extents are known
+    /// to be used, the fragment pages beyond the tablespace are reported
+    pub fn audit_xdes(&mut self) -> Result<XdesAudit> {
+        let page_count = self.page_count();
+        let mut ret = XdesAudit::default();
+        for inode_page_no in self.inode_page_numbers()? {
+            let inode_page: BasePage<INodePageBody> = self.read_page(inode_page_no)?;
+            let inodes = inode_page.page_body.inode_ent_list.iter();
+            for inode in inodes.filter(|inode| inode.fseg_id > 0) {
+                for page_no in &inode.fseg_frag_arr {
+                    let page_no = *page_no as usize;
+                    if page_no >= page_count {
+                        ret.bad_frags.push((inode_page_no, inode.fseg_id, page_no));
+                        continue;
+                    }
+                    let xdes_page_no = page_no / EXTENT_PAGE_NUM * EXTENT_PAGE_NUM;
+                    let xdes_seq = page_no % EXTENT_PAGE_NUM / XDES_PAGE_COUNT;
+                    let xdes_page: BasePage<XDesPageBody> = self.read_page(xdes_page_no)?;
+                    let xdes = &xdes_page.page_body.xdes_ent_list[xdes_seq];
+                    if xdes.bitmap[page_no % XDES_PAGE_COUNT].1.free() {
+                        ret.conflicts.push((page_no, page_no / XDES_PAGE_COUNT));
+                    }
+                }
+
+                let mut faddr = inode.fseg_full.first.clone();
+                for _ in 0..inode.fseg_full.len {
+                    if matches!(faddr.page_no, PageNumber::None) {
+                        break;
+                    }
+                    let page_no: usize = faddr.page_no.into();
+                    let xdes = self.read_xdes_entry(page_no, faddr.boffset)?;
+                    let xdes_no = page_no / EXTENT_PAGE_NUM * XDES_ENTRY_MAX_COUNT + xdes.xdes_seq;
+                    for (nth, free, _) in &xdes.bitmap {
+                        if free.free() {
+                            ret.conflicts
+                                .push((xdes_no * XDES_PAGE_COUNT + *nth as usize, xdes_no));
+                        }
+                    }
+                    faddr = xdes.flst_node.next;
+                }
+            }
+        }
+        Ok(ret)
+    }
+
+    /// compare with the other datafile page by page, a page is different if
+    /// the LSN or the content hash is different
+    pub fn diff_pages(&mut self, other: &mut DatafileFactory) -> Result<Vec<PageDiff>> {
+        let page_count = max(self.page_count(), other.page_count());
+        let mut ret = vec![];
+        for page_no in 0..page_count {
+            let this = self.page_digest(page_no)?;
+            let that = other.page_digest(page_no)?;
+            if this == that {
+                continue;
+            }
+            ret.push(PageDiff {
+                page_no,
+                this: this.map(|d| (d.0, d.1)),
+                other: that.map(|d| (d.0, d.1)),
+            });
+        }
+        Ok(ret)
+    }
+
+    /// stable content hash of the page, the checksum and LSN fields in the FIL
+    /// header and the trailer are excluded if logical, so is the flush LSN
+    /// (FIL_PAGE_FILE_FLUSH_LSN) of page 0
+    pub fn page_hash(&mut self, page_no: usize, logical: bool) -> Result<u64> {
+        let buf = self.page_buffer(page_no)?;
+        if logical {
+            let flush_lsn_beg = if page_no == 0 { 26 } else { 34 };
+            Ok(util::fnv1a64(&[
+                &buf[4..16],
+                &buf[24..flush_lsn_beg],
+                &buf[34..FIL_HEADER_SIZE],
+                &buf[FIL_HEADER_SIZE..buf.len() - FIL_TRAILER_SIZE],
+            ]))
+        } else {
+            Ok(util::fnv1a64(&[&buf]))
+        }
+    }
+
+    /// page type, LSN and content hash, None if the page is out of range
+    fn page_digest(&mut self, page_no: usize) -> Result<Option<(PageTypes, u64, u64)>> {
+        if page_no >= self.page_count() {
+            return Ok(None);
+        }
+        let fil_hdr = self.read_fil_hdr(page_no)?;
+        let hash = self.page_hash(page_no, false)?;
+        Ok(Some((fil_hdr.page_type, fil_hdr.lsn, hash)))
+    }
+
+    pub fn unpack_index_page(&mut self, page_no: usize, garbage: bool) -> Result<ResultSet, Error> {
+        let source = if garbage {
+            RecordSource::Free
+        } else {
+            RecordSource::User
+        };
+        self.unpack_page_records(page_no, Some(source))
+    }
+
+    /// unpack the user records followed by the garbage records of the page
+    pub fn unpack_all_records(&mut self, page_no: usize) -> Result<ResultSet, Error> {
+        self.unpack_page_records(page_no, None)
+    }
+
+    /// unpack the records in the given list, or in both lists if source is None
+    fn unpack_page_records(
+        &mut self,
+        page_no: usize,
+        source: Option<RecordSource>,
+    ) -> Result<ResultSet, Error> {
+        let page: BasePage<IndexPageBody> = self.read_page(page_no)?;
+        // let page_level = page.page_body.idx_hdr.page_level;
+        // if page_level != 0 {
+        //     return Err(Error::msg(format!(
+        //         "不支持查看非叶子节点: page_level={:?}",
+        //         page_level
+        //     )));
+        // }
+
+        let tabdef = self.load_table_def()?;
+        let index_id = page.page_body.idx_hdr.page_index_id;
+        let index = match tabdef
+            .idx_defs
+            .iter()
+            .enumerate()
+            .find(|idx| idx.1.idx_id == index_id as i32)
+        {
+            Some(val) => val,
+            None if util::is_best_effort() => {
+                warn!("未找到索引的元信息, 按原始字节解析: index_id={}", index_id);
+                return Self::unpack_raw_index_page(&page, &tabdef, source);
+            }
+            None => {
+                return Err(Error::msg(format!(
+                    "未找到索引的元信息: index_id={}",
+                    index_id
+                )));
+            }
+        };
+        info!("当前页所引用的索引: index_name={}", index.1.idx_name);
+
+        let body = &page.page_body;
+        let tagged_list = match source {
+            Some(RecordSource::User) => tag_records(
+                RecordSource::User,
+                body.read_user_records(tabdef.clone(), index.0)?,
+            ),
+            Some(RecordSource::Free) => tag_records(
+                RecordSource::Free,
+                body.read_free_records(tabdef.clone(), index.0)?,
+            ),
+            None => {
+                let mut tagged_list = tag_records(
+                    RecordSource::User,
+                    body.read_user_records(tabdef.clone(), index.0)?,
+                );
+                tagged_list.extend(tag_records(
+                    RecordSource::Free,
+                    body.read_free_records(tabdef.clone(), index.0)?,
+                ));
+                tagged_list
+            }
+        };
+        let (sources, rec_list): (Vec<_>, Vec<_>) = tagged_list.into_iter().unzip();
+        debug!("rec_list={:?}", rec_list);
+
+        let tuples = rec_list
+            .iter()
+            .map(|rec| unpack_tuple(&tabdef, rec))
+            .collect();
+
+        Ok(ResultSet {
+            garbage: sources.iter().map(|s| *s == RecordSource::Free).collect(),
+            tabdef: tabdef.clone(),
+            idx_name: index.1.idx_name.clone(),
+            records: rec_list,
+            tuples,
+        })
+    }
+
+    /// unpack the records of a page whose index is missing in the table
+    /// definition, each record is a single raw bytes field
+    fn unpack_raw_index_page(
+        page: &BasePage<IndexPageBody>,
+        tabdef: &Arc<TableDef>,
+        source: Option<RecordSource>,
+    ) -> Result<ResultSet, Error> {
+        let index_id = page.page_body.idx_hdr.page_index_id;
+        let idx_name = format!("UNKNOWN_{}", index_id);
+        let mut generic = tabdef.as_ref().clone();
+        generic.idx_defs.push(IndexDef {
+            pos: generic.idx_defs.len() + 1,
+            idx_name: idx_name.clone(),
+            idx_id: index_id as i32,
+            ..Default::default()
+        });
+        let generic = Arc::new(generic);
+        let index_pos = generic.idx_defs.len() - 1;
+
+        let body = &page.page_body;
+        let mut tagged_list = vec![];
+        if source != Some(RecordSource::Free) {
+            let recs = body.read_raw_user_records(generic.clone(), index_pos)?;
+            tagged_list.extend(tag_records(RecordSource::User, recs));
+        }
+        if source != Some(RecordSource::User) {
+            let recs = body.read_raw_free_records(generic.clone(), index_pos)?;
+            tagged_list.extend(tag_records(RecordSource::Free, recs));
+        }
+        let (sources, rec_list): (Vec<_>, Vec<_>) = tagged_list.into_iter().unzip();
+        let tuples = rec_list
+            .iter()
+            .map(|rec| {
+                vec![(
+                    "RAW".to_string(),
+                    DataValue::Unknown(page.page_body.raw_record_bytes(rec.addr)),
+                )]
+            })
+            .collect();
+
+        Ok(ResultSet {
+            garbage: sources.iter().map(|s| *s == RecordSource::Free).collect(),
+            tabdef: generic,
+            idx_name,
+            records: rec_list,
+            tuples,
+        })
+    }
+
+    /// the table definition and the position of the index the page belongs
+    /// to, None if the index is missing in the table definition
+    pub fn page_index_layout(
+        &mut self,
+        page: &BasePage<IndexPageBody>,
+    ) -> Result<Option<(Arc<TableDef>, usize)>, Error> {
+        let tabdef = self.load_table_def()?;
+        let index_id = page.page_body.idx_hdr.page_index_id as i32;
+        Ok(tabdef
+            .idx_defs
+            .iter()
+            .position(|idx| idx.idx_id == index_id)
+            .map(|pos| (tabdef.clone(), pos)))
+    }
+
+    /// unpack the record with the given heap_no, the heap is scanned so the
+    /// garbage records and the records unlinked from both lists are found
+    pub fn unpack_heap_record(
+        &mut self,
+        page_no: usize,
+        heap_no: u16,
+    ) -> Result<(Record, Vec<(String, DataValue)>), Error> {
+        let page: BasePage<IndexPageBody> = self.read_page(page_no)?;
+        let (tabdef, index_pos) = match self.page_index_layout(&page)? {
+            Some(val) => val,
+            None => {
+                return Err(Error::msg(format!(
+                    "未找到索引的元信息: index_id={}",
+                    page.page_body.idx_hdr.page_index_id
+                )));
+            }
+        };
+        let (rec_hdr, _) = match page
+            .page_body
+            .heap_records(Some((tabdef.clone(), index_pos)))
+            .into_iter()
+            .find(|(h, _)| h.heap_no == heap_no)
+        {
+            Some(val) => val,
+            None => {
+                return Err(Error::msg(format!(
+                    "未找到堆记录: page_no={}, heap_no={}",
+                    page_no, heap_no
+                )));
+            }
+        };
+        if matches!(
+            rec_hdr.rec_status,
+            RecordStatus::INFIMUM | RecordStatus::SUPREMUM
+        ) {
+            return Err(Error::msg(format!(
+                "系统记录不能解析: heap_no={}, status={}",
+                heap_no, rec_hdr.rec_status
+            )));
+        }
+
+        let rec = page.page_body.parse_record(
+            rec_hdr.addr + RECORD_HEADER_SIZE,
+            tabdef.clone(),
+            index_pos,
+        )?;
+        let tuple = unpack_tuple(&tabdef, &rec);
+        Ok((rec, tuple))
+    }
+}
+
+/// unpack the row data of the record into named values
+fn unpack_tuple(tabdef: &TableDef, rec: &Record) -> Vec<(String, DataValue)> {
+    rec.row_data
+        .data_list
+        .iter()
+        .map(|d| {
+            if d.opx == PAGE_NONE as usize {
+                return (
+                    "NODE_PTR".to_string(),
+                    DataValue::PageNo(u32_val(&d.rbuf.clone().unwrap(), 0)),
+                );
+            }
+            let col = &tabdef.col_defs[d.opx];
+            let val = match &d.rbuf {
+                Some(b) => match col.hidden {
+                    HiddenTypes::HT_VISIBLE => match col.dd_type {
+                        ColumnTypes::TINY
+                        | ColumnTypes::SHORT
+                        | ColumnTypes::INT24
+                        | ColumnTypes::LONG
+                        | ColumnTypes::LONGLONG => unpack_integer_val(col, b),
+                        ColumnTypes::NEWDATE => DataValue::Date(
+                            unpack_newdate_val(b)
+                                .unwrap_or_else(|| panic!("日期格式错误: {:?}", &d)),
+                        ),
+                        ColumnTypes::DATETIME2 => DataValue::DateTime(
+                            unpack_datetime2_val(b)
+                                .unwrap_or_else(|| panic!("时间格式错误: {:?}", &d)),
+                        ),
+                        ColumnTypes::TIMESTAMP2 => {
+                            DataValue::Timestamp(unpack_timestamp2_val(b).fixed_offset())
+                        }
+                        ColumnTypes::VARCHAR | ColumnTypes::VAR_STRING | ColumnTypes::STRING => {
+                            unpack_string_val(col, b)
+                        }
+                        ColumnTypes::ENUM => DataValue::Enum(unpack_enum_val(b)),
+                        _ => {
+                            warn!("不支持解析的类型: {:?}", &col);
+                            DataValue::Unknown(b.clone())
+                        }
+                    },
+                    HiddenTypes::HT_HIDDEN_SE => match col.col_name.as_str() {
+                        "DB_ROW_ID" => DataValue::RowId(unpack_u48_val(b)),
+                        "DB_TRX_ID" => DataValue::TrxId(unpack_u48_val(b)),
+                        "DB_ROLL_PTR" => DataValue::RbPtr(RollPtr::new(unpack_u56_val(b))),
+                        _ => todo!("不支持的隐藏字段名称: {:?}", col),
+                    },
+                    _ => todo!("不支持的隐藏字段类型: {:?}", col),
+                },
+                None => DataValue::Null,
+            };
+            (col.col_name.clone(), val)
+        })
+        .collect()
+}
+
+/// tag each record with the list it comes from
+fn tag_records(source: RecordSource, recs: Vec<Record>) -> Vec<(RecordSource, Record)> {
+    recs.into_iter().map(|rec| (source, rec)).collect()
+}
+
+/// unpack the integer column, the signed integer has its sign bit flipped on
+/// disk while the unsigned integer is stored as is
+fn unpack_integer_val(col: &ColumnDef, b: &Bytes) -> DataValue {
+    match (&col.dd_type, col.is_unsigned) {
+        (ColumnTypes::LONGLONG, false) => DataValue::I64(unpack_i64_val(b)),
+        (ColumnTypes::LONGLONG, true) => DataValue::U64(unpack_u64_val(b)),
+        (ColumnTypes::LONG, false) => DataValue::I32(unpack_i32_val(b)),
+        (ColumnTypes::INT24, false) => DataValue::I32(unpack_i24_val(b)),
+        (ColumnTypes::SHORT, false) => DataValue::I32(unpack_i16_val(b) as i32),
+        (_, false) => DataValue::I32(unpack_i8_val(b) as i32),
+        (_, true) => DataValue::U32(unpack_u32_val(b)),
+    }
+}
+
+/// unpack the string column, binary string (charset binary, collation id 63)
+/// is kept as raw bytes, the trailing 0x00 padding of BINARY is preserved.
+/// The text not in UTF-8, like latin1 or gbk above 0x7f, is kept as raw bytes
+fn unpack_string_val(col: &ColumnDef, b: &Bytes) -> DataValue {
+    if col.charset == "binary" {
+        return DataValue::Bytes(b.clone());
+    }
+    match String::from_utf8(b.to_vec()) {
+        Ok(text) => DataValue::Str(text),
+        Err(_) => {
+            debug!(
+                "非 UTF-8 字符串, 按原始字节输出: col={}, charset={}",
+                col.col_name, col.charset
+            );
+            DataValue::Bytes(b.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod factory_tests {
+
+    use std::{path::PathBuf, sync::Arc};
+
+    use anyhow::Error;
+    use bytes::Bytes;
+    use chrono::{Datelike, Timelike};
+    use log::{debug, info};
+
+    use super::{unpack_integer_val, unpack_string_val};
+    use crate::{
+        factory::{CacheStats, DataValue, DatafileFactory, ResultSet},
+        ibd::{
+            page::{
+                BasePage, FileSpaceHeaderPageBody, IndexPageBody, PageTypes, FIL_HEADER_SIZE,
+                FIL_TRAILER_SIZE, PAGE_NONE, UNIV_PAGE_SIZE,
+            },
+            record::RecordStatus,
+        },
+        meta::def::{ColumnDef, ColumnTypes},
+        sdi::record::EntryTypes,
+        util,
+    };
+
+    // employee schema
+    const IBD_DEPT: &str = "data/departments.ibd";
+    const IBD_DEPT_BIN: &str = "data/departments_binary.ibd";
+    const IBD_DEPT_WIDE: &str = "data/departments_wide.ibd";
+    const IBD_DEPT_MGR: &str = "data/dept_manager.ibd";
+
+    // tb_row_version.sql
+    const IBD_RV_0: &str = "data/tb_row_version_0.ibd";
+    const IBD_RV_1: &str = "data/tb_row_version_1.ibd";
+    const IBD_RV_2: &str = "data/tb_row_version_2.ibd";
+    const IBD_RV_3: &str = "data/tb_row_version_3.ibd";
+    const IBD_RV_4: &str = "data/tb_row_version_4.ibd";
+
+    // tb_instant_col.sql
+    const IBD_IC_0: &str = "data/tb_instant_col_0.ibd";
+    const IBD_IC_1: &str = "data/tb_instant_col_1.ibd";
+    const IBD_IC_2: &str = "data/tb_instant_col_2.ibd";
+
+    #[test]
+    fn load_buffer() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let buf = fact.fil_hdr_buffer(0)?;
+        assert_eq!(buf.len(), FIL_HEADER_SIZE);
+        Ok(())
+    }
+
+    #[test]
+    fn read_fsp_hdr_page() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
+        // info!("fsp_page={:#?}", fsp_page);
+        assert_eq!(fsp_page.fil_hdr.page_type, PageTypes::FSP_HDR);
+        assert_eq!(fsp_page.fil_hdr.server_version(), 80037);
+        assert_eq!(fsp_page.fil_hdr.space_version(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn load_temporary_space() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        assert!(!fact.is_temporary()?);
+
+        // 设置 FSP_FLAGS 的 TEMPORARY 标志位
+        let mut data = fact.file_buffer()?.to_vec();
+        let flags_addr = FIL_HEADER_SIZE + 16;
+        data[flags_addr + 2] |= 0x10;
+
+        let mut temp = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert!(temp.is_temporary()?);
+        let err = temp.load_table_def().unwrap_err();
+        assert!(err.to_string().contains("临时表空间"));
+        let index_page: BasePage<IndexPageBody> = temp.read_page(4)?;
+        assert_eq!(index_page.page_body.idx_hdr.page_n_recs, 9);
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_page_dir_slots() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let index_page: BasePage<IndexPageBody> = fact.read_page(4)?;
+        let slots = index_page.page_body.dir_slot_records();
+        debug!("slots={:?}", slots);
+        assert_eq!(slots.len(), index_page.page_body.page_dirs.len());
+        assert_eq!(slots[0].rec_status, RecordStatus::INFIMUM);
+        assert_eq!(slots.last().unwrap().rec_status, RecordStatus::SUPREMUM);
+        assert_eq!(slots.last().unwrap().heap_no, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn read_corrupted_next_record() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let tabdef = fact.load_table_def()?;
+        let page: BasePage<IndexPageBody> = fact.read_page(4)?;
+        let first = page.page_body.data_rec_hdrs[0].addr;
+        let second = page.page_body.data_rec_hdrs[1].addr;
+
+        // cyclic: the second record points back to the first one
+        let mut data = fact.page_buffer(4)?.to_vec();
+        let off = (first as i16 - second as i16).to_be_bytes();
+        data[second + 3..second + 5].copy_from_slice(&off);
+        let page: BasePage<IndexPageBody> = BasePage::new(0, Arc::new(Bytes::from(data)));
+        let err = page.page_body.read_user_records(tabdef.clone(), 0);
+        info!("err={:?}", err.as_ref().err());
+        assert!(err.unwrap_err().to_string().contains("环"));
+
+        // out of page: the first record points beyond the page directory
+        let mut data = fact.page_buffer(4)?.to_vec();
+        data[first + 3..first + 5].copy_from_slice(&0x7fffi16.to_be_bytes());
+        let page: BasePage<IndexPageBody> = BasePage::new(0, Arc::new(Bytes::from(data)));
+        let err = page.page_body.read_user_records(tabdef, 0).unwrap_err();
+        assert!(err.to_string().contains("错误的记录地址"));
+        Ok(())
+    }
+
+    #[test]
+    fn verify_index_roots() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT_MGR))?;
+        let ans = fact.verify_index_roots()?;
+        assert!(!ans.is_empty());
+        for (name, root, err) in ans {
+            info!("index={}, root={}, err={:?}", name, root, err);
+            assert!(err.is_none());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn verify_page_type() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        for page_no in 0..fact.page_count() {
+            assert!(fact.verify_page_type(page_no)?.is_empty());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn diff_datafiles() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let mut same = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        assert!(fact.diff_pages(&mut same)?.is_empty());
+
+        // mutate one byte in page 4 and truncate the last page
+        let data = util::patch_fixture(IBD_DEPT, |data| {
+            data[4 * UNIV_PAGE_SIZE + 200] ^= 0xff;
+            data.truncate(data.len() - UNIV_PAGE_SIZE);
+        });
+        let mut mutated = DatafileFactory::from_bytes(Bytes::from(data))?;
+        let ans = fact.diff_pages(&mut mutated)?;
+        info!("ans={:?}", ans);
+        assert_eq!(ans.len(), 2);
+        assert_eq!(ans[0].page_no, 4);
+        assert_eq!(ans[0].lsn_delta(), Some(0));
+        assert_eq!(ans[1].page_no, fact.page_count() - 1);
+        assert!(ans[1].other.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn page_content_hash() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let data = std::fs::read(IBD_DEPT)?;
+        let mut copied = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        for page_no in 0..fact.page_count() {
+            for logical in [false, true] {
+                assert_eq!(
+                    fact.page_hash(page_no, logical)?,
+                    copied.page_hash(page_no, logical)?
+                );
+            }
+        }
+
+        // bump the LSN and checksum of page 4, the logical hash is unchanged
+        let mut data = data;
+        let page = &mut data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+        page[0] ^= 0xff;
+        page[23] = page[23].wrapping_add(1);
+        page[UNIV_PAGE_SIZE - 1] = page[UNIV_PAGE_SIZE - 1].wrapping_add(1);
+        let mut bumped = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert_ne!(fact.page_hash(4, false)?, bumped.page_hash(4, false)?);
+        assert_eq!(fact.page_hash(4, true)?, bumped.page_hash(4, true)?);
+
+        // the content changed
+        data[UNIV_PAGE_SIZE * 4 + 200] ^= 0xff;
+        let mut changed = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert_ne!(fact.page_hash(4, true)?, changed.page_hash(4, true)?);
+
+        // the flush LSN of page 0 is skipped, the same bytes of other pages
+        // are kept
+        let mut flushed = data.clone();
+        flushed[26..34].copy_from_slice(&12345u64.to_be_bytes());
+        flushed[UNIV_PAGE_SIZE * 5 + 26] ^= 0xff;
+        let mut flushed = DatafileFactory::from_bytes(Bytes::from(flushed))?;
+        assert_eq!(changed.page_hash(0, true)?, flushed.page_hash(0, true)?);
+        assert_ne!(changed.page_hash(0, false)?, flushed.page_hash(0, false)?);
+        assert_ne!(changed.page_hash(5, true)?, flushed.page_hash(5, true)?);
+        Ok(())
+    }
+
+    /// re-encode the decoded value into the on-disk bytes, None if the value
+    /// is not encodable
+    fn pack_datum(col: Option<&ColumnDef>, val: &DataValue, len: usize) -> Option<Vec<u8>> {
+        let width = match col.map(|c| &c.dd_type) {
+            Some(ColumnTypes::TINY) => 1,
+            Some(ColumnTypes::SHORT) => 2,
+            Some(ColumnTypes::INT24) => 3,
+            Some(ColumnTypes::LONG) => 4,
+            _ => 8,
+        };
+        let signed = |v: i64| {
+            let mut b = v.to_be_bytes()[8 - width..].to_vec();
+            b[0] ^= 0x80;
+            b
+        };
+        let packed = match val {
+            DataValue::RowId(v) | DataValue::TrxId(v) => v.to_be_bytes()[2..].to_vec(),
+            DataValue::RbPtr(p) => p.value.to_be_bytes()[1..].to_vec(),
+            DataValue::PageNo(v) => v.to_be_bytes().to_vec(),
+            DataValue::I32(v) => signed(*v as i64),
+            DataValue::I64(v) => signed(*v),
+            DataValue::U32(v) => (*v as u64).to_be_bytes()[8 - width..].to_vec(),
+            DataValue::U64(v) => v.to_be_bytes().to_vec(),
+            DataValue::Str(v) => v.as_bytes().to_vec(),
+            DataValue::Bytes(v) => v.to_vec(),
+            DataValue::Enum(v) => v.to_be_bytes()[2 - len..].to_vec(),
+            DataValue::Date(d) => {
+                let v = (d.year() as u32) << 9 | d.month() << 5 | d.day() | 0x800000;
+                v.to_be_bytes()[1..].to_vec()
+            }
+            DataValue::DateTime(t) => {
+                let ym = t.year() as u64 * 13 + t.month() as u64;
+                let v = ym << 22
+                    | (t.day() as u64) << 17
+                    | (t.hour() as u64) << 12
+                    | (t.minute() as u64) << 6
+                    | t.second() as u64
+                    | 1 << 39;
+                v.to_be_bytes()[3..].to_vec()
+            }
+            DataValue::Timestamp(t) => (t.timestamp() as u32).to_be_bytes().to_vec(),
+            DataValue::Unknown(_) | DataValue::Null => return None,
+        };
+        Some(packed)
+    }
+
+    /// compare the re-encoded values with the original bytes, return the
+    /// (row, column) whose round-trip differs
+    fn round_trip_diffs(rs: &ResultSet) -> Vec<(usize, String)> {
+        let mut diffs = vec![];
+        for (i, (rec, tuple)) in rs.records.iter().zip(rs.tuples.iter()).enumerate() {
+            for (d, (name, val)) in rec.row_data.data_list.iter().zip(tuple) {
+                let orig = match &d.rbuf {
+                    Some(b) => b,
+                    None => continue,
+                };
+                let col = rs.tabdef.col_defs.get(d.opx);
+                if let Some(packed) = pack_datum(col, val, orig.len()) {
+                    if packed != orig.to_vec() {
+                        info!("round-trip differs: row={}, col={}, {:?}", i, name, val);
+                        diffs.push((i, name.clone()));
+                    }
+                }
+            }
+        }
+        diffs
+    }
+
+    #[test]
+    fn round_trip_records() -> Result<(), Error> {
+        util::init_unit_test();
+        for (path, page_no) in [
+            (IBD_DEPT, 4),
+            (IBD_DEPT, 5),
+            (IBD_DEPT_MGR, 4),
+            (IBD_DEPT_MGR, 5),
+        ] {
+            let mut fact = DatafileFactory::from_file(PathBuf::from(path))?;
+            let rs = fact.unpack_index_page(page_no, false)?;
+            assert!(!rs.tuples.is_empty());
+            assert_eq!(round_trip_diffs(&rs), vec![], "{}:{}", path, page_no);
+        }
+
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let mut rs = fact.unpack_index_page(4, false)?;
+        rs.tuples[1][0].1 = DataValue::Str("d999".into());
+        assert_eq!(round_trip_diffs(&rs), vec![(1, "dept_no".to_string())]);
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_index_name() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        assert_eq!(fact.unpack_index_page(4, false)?.idx_name, "PRIMARY");
+        assert_eq!(fact.unpack_index_page(5, false)?.idx_name, "dept_name");
+        Ok(())
+    }
+
+    #[test]
+    fn clustered_root() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT_MGR))?;
+        let tabdef = fact.load_table_def()?;
+        assert_eq!(tabdef.primary_index().unwrap().idx_name, "PRIMARY");
+        assert_eq!(fact.clustered_root()?, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn record_raw_body() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let rs = fact.unpack_index_page(4, false)?;
+        for (rec, tuple) in rs.records.iter().zip(rs.tuples.iter()) {
+            let raw = rec.raw_body();
+            assert_eq!(raw.len(), rec.calc_layout().phy_data_size);
+            let name = match &tuple[3].1 {
+                DataValue::Str(s) => s.len(),
+                val => panic!("unexpected value: {:?}", val),
+            };
+            // dept_no + DB_TRX_ID + DB_ROLL_PTR + dept_name
+            assert_eq!(raw.len(), 4 + 6 + 7 + name);
+        }
+        assert!(rs.records[0].raw_body().starts_with(b"d001"));
+        Ok(())
+    }
+
+    #[test]
+    fn record_field_layout() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let rs = fact.unpack_index_page(4, false)?;
+        let rec = &rs.records[0];
+        let flds = rec.field_layout();
+        let names = flds.iter().map(|f| f.col_name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["dept_no", "DB_TRX_ID", "DB_ROLL_PTR", "dept_name"]);
+        assert_eq!(flds[0].start, rec.addr);
+        assert_eq!(flds[0].end - flds[0].start, 4);
+        for pair in flds.windows(2) {
+            assert!(pair[0].start < pair[0].end);
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+        let last = flds.last().unwrap();
+        assert_eq!(last.end, rec.addr + rec.calc_layout().phy_data_size);
+        Ok(())
+    }
+
+    #[test]
+    fn project_columns() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let mut rs = fact.unpack_index_page(4, false)?;
+        rs.project(&["dept_name".into()])?;
+        assert_eq!(
+            rs.tuples[0],
+            vec![("dept_name".into(), DataValue::Str("Marketing".into()))]
+        );
+        assert!(rs.project(&["no_such_col".into()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn render_raw_bytes() {
+        let val = DataValue::Unknown(Bytes::from_static(&[0x80, 0x00, 0x01]));
+        assert_eq!(val.render(true), "Raw(3 bytes: 80 00 01)");
+        assert_eq!(val.render(false), "0x800001");
+        assert_eq!(DataValue::I32(1).render(true), "1");
+    }
+
+    #[test]
+    fn display_data_value() {
+        assert_eq!(DataValue::Str("d001".into()).to_string(), "d001");
+        assert_eq!(DataValue::I32(-5).to_string(), "-5");
+        assert_eq!(DataValue::Null.to_string(), "NULL");
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 8, 11).unwrap();
+        assert_eq!(DataValue::Date(date).to_string(), "2024-08-11");
+        assert_eq!(format!("{:?}", DataValue::I32(5)), "I32(5)");
+    }
+
+    #[test]
+    fn gen_sql_inserts() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let rs = fact.unpack_index_page(4, false)?;
+        let stmts = rs.to_sql_inserts("departments");
+        assert_eq!(stmts.len(), rs.tuples.len());
+        assert_eq!(
+            stmts[0],
+            "INSERT INTO `departments` (`dept_no`, `dept_name`) VALUES ('d001', 'Marketing');"
+        );
+        assert_eq!(DataValue::Str("it's".into()).to_sql_literal(), "'it''s'");
+        assert_eq!(DataValue::Null.to_sql_literal(), "NULL");
+        Ok(())
     }
 
-    fn read_sdi_page(&mut self) -> Result<BasePage<SdiPageBody>, Error> {
-        let fsp_page: BasePage<FileSpaceHeaderPageBody> = self.read_page(0)?;
-        if fsp_page.fil_hdr.server_version() < SDI_META_INFO_MIN_VER {
-            return Err(Error::msg("数据文件版本过低，没有表元信息"));
+    #[test]
+    fn unpack_unsigned_integer() {
+        util::init_unit_test();
+        let col = ColumnDef {
+            col_name: "c_ubig".into(),
+            dd_type: ColumnTypes::LONGLONG,
+            is_unsigned: true,
+            ..ColumnDef::default()
+        };
+        let b = Bytes::from_static(&[0xff; 8]);
+        assert_eq!(unpack_integer_val(&col, &b), DataValue::U64(u64::MAX));
+        assert_eq!(
+            unpack_integer_val(&col, &b).to_string(),
+            "18446744073709551615"
+        );
+
+        let col = ColumnDef {
+            is_unsigned: false,
+            ..col
+        };
+        assert_eq!(unpack_integer_val(&col, &b), DataValue::I64(i64::MAX));
+
+        for (dd_type, b, signed, unsigned) in [
+            (ColumnTypes::TINY, vec![0x7f], -1, 0x7f),
+            (ColumnTypes::SHORT, vec![0x80, 0x01], 1, 0x8001),
+            (ColumnTypes::INT24, vec![0x00, 0x00, 0x00], -0x800000, 0),
+            (
+                ColumnTypes::LONG,
+                vec![0xff, 0xff, 0xff, 0xff],
+                i32::MAX,
+                u32::MAX,
+            ),
+        ] {
+            let b = Bytes::from(b);
+            let col = ColumnDef {
+                dd_type,
+                ..ColumnDef::default()
+            };
+            assert_eq!(unpack_integer_val(&col, &b), DataValue::I32(signed));
+            let col = ColumnDef {
+                is_unsigned: true,
+                ..col
+            };
+            assert_eq!(unpack_integer_val(&col, &b), DataValue::U32(unsigned));
         }
-        let sdi_meta = fsp_page.page_body.sdi_meta();
-        let sdi_page_no: usize = sdi_meta.sdi_page_no.into();
-        self.read_page(sdi_page_no)
     }
 
-    pub fn load_sdi_string(&mut self) -> Result<Vec<String>, Error> {
-        let sdi_page = self.read_sdi_page()?;
-        let ret: Vec<String> = sdi_page
-            .page_body
-            .read_sdi_objects()?
-            .iter()
-            .map(|obj| jsonxf::pretty_print(&obj.sdi_str).unwrap_or("".into()))
-            .collect();
-        Ok(ret)
+    #[test]
+    fn unpack_binary_string() -> Result<(), Error> {
+        util::init_unit_test();
+        // departments with dept_name as varbinary(160), 'Sales' of d007 is
+        // patched to 0xfffe6c6573
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT_BIN))?;
+        let tabdef = fact.load_table_def()?;
+        assert_eq!(tabdef.col_defs[1].charset, "binary");
+        let rs = fact.unpack_index_page(4, false)?;
+        for tuple in &rs.tuples {
+            assert!(matches!(tuple[3], (_, DataValue::Bytes(_))));
+        }
+        let val = &rs.tuples[6][3].1;
+        assert_eq!(rs.tuples[6][0].1, DataValue::Str("d007".into()));
+        assert_eq!(val, &DataValue::Bytes(Bytes::from_static(b"\xff\xfeles")));
+        assert_eq!(val.to_sql_literal(), "X'fffe6c6573'");
+        assert_eq!(val.to_json_value(), "fffe6c6573");
+        assert_eq!(fact.unpack_index_page(5, false)?.tuples.len(), 9);
+
+        // latin1 text above 0x7f is not UTF-8
+        let col = ColumnDef {
+            dd_type: ColumnTypes::VARCHAR,
+            charset: "latin1".into(),
+            ..ColumnDef::default()
+        };
+        let b = Bytes::from_static(b"caf\xe9");
+        assert_eq!(unpack_string_val(&col, &b), DataValue::Bytes(b.clone()));
+        let val = unpack_string_val(&col, &Bytes::from("abc"));
+        assert_eq!(val, DataValue::Str("abc".into()));
+        Ok(())
     }
 
-    pub fn load_table_def(&mut self) -> Result<Arc<TableDef>> {
-        let sdi_page = self.read_sdi_page()?;
+    #[test]
+    fn gen_json_lines() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let rs = fact.unpack_index_page(4, false)?;
+        let lines: Vec<String> = rs.to_json_lines().collect();
+        assert_eq!(lines.len(), rs.tuples.len());
+        for line in &lines {
+            assert!(!line.contains('\n'));
+            let obj: serde_json::Value = serde_json::from_str(line)?;
+            assert!(obj.is_object());
+        }
+        let first: serde_json::Value = serde_json::from_str(&lines[0])?;
+        assert_eq!(first["dept_no"], "d001");
+        assert_eq!(first["dept_name"], "Marketing");
+        Ok(())
+    }
 
-        let sdi_str = sdi_page.page_body.get_tabdef_str()?;
-        let dd_object = SdiTableObject::from_str(&sdi_str)?.dd_object;
-        debug!("dd_object={:#?}", &dd_object);
+    #[test]
+    fn read_sdi_extern_pages() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT_WIDE))?;
+        let tab_obj = fact
+            .read_sdi_objects()?
+            .into_iter()
+            .find(|obj| obj.sdi_hdr.data_type == EntryTypes::Table)
+            .unwrap();
+        let ext = tab_obj.sdi_ext.clone().unwrap();
+        assert_eq!(ext.page_no, 6);
+        assert!(tab_obj.sdi_prefix.is_empty());
+        assert_eq!(ext.ext_len(), tab_obj.sdi_hdr.comp_len as usize);
+        assert!(ext.ext_len() > UNIV_PAGE_SIZE);
+        assert_eq!(tab_obj.sdi_str.len(), tab_obj.sdi_hdr.uncomp_len as usize);
+
+        // 500 virtual columns, which are not stored in the index
+        let tabdef = fact.load_table_def()?;
+        assert_eq!(tabdef.col_defs.len(), 504);
+        assert_eq!(tabdef.col_defs[2].col_name, "v0001");
+        assert_eq!(tabdef.col_defs[503].col_name, "DB_ROLL_PTR");
+        let rs = fact.unpack_index_page(4, false)?;
+        assert_eq!(rs.tuples.len(), 9);
+        assert_eq!(rs.tuples[0][0].1, DataValue::Str("d001".into()));
+        assert_eq!(rs.tuples[0][3].1, DataValue::Str("Marketing".into()));
+        Ok(())
+    }
 
-        let coll = coll_find(dd_object.collation_id);
-        info!("当前文件字符集: {:?}", &coll);
+    #[test]
+    fn read_sdi_extern_corrupt() -> Result<(), Error> {
+        util::init_unit_test();
+        let data = std::fs::read(IBD_DEPT_WIDE)?;
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT_WIDE))?;
+        let tab_obj = fact
+            .read_sdi_objects()?
+            .into_iter()
+            .find(|obj| obj.sdi_hdr.data_type == EntryTypes::Table)
+            .unwrap();
+        let ext = tab_obj.sdi_ext.unwrap();
+        let comped = fact.read_sdi_extern(&ext)?;
+
+        // the part length overflows the page
+        let mut corrupt = data.clone();
+        let hdr = 6 * UNIV_PAGE_SIZE + FIL_HEADER_SIZE;
+        corrupt[hdr..hdr + 4].copy_from_slice(&(UNIV_PAGE_SIZE as u32).to_be_bytes());
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(corrupt))?;
+        let err = fact.read_sdi_extern(&ext).unwrap_err();
+        assert!(err.to_string().contains("SDI 外部数据越界"), "{}", err);
+
+        // the same pages as SDI_ZBLOB, the trailer is not part of the stream
+        let mut zlib = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+        std::io::Write::write_all(&mut zlib, &comped)?;
+        let zdata = zlib.finish()?;
+        let cap = UNIV_PAGE_SIZE - FIL_HEADER_SIZE - FIL_TRAILER_SIZE;
+        assert!(zdata.len() > cap);
+        let mut zblob = data.clone();
+        for (i, part) in zdata.chunks(cap).enumerate() {
+            let page = (6 + i) * UNIV_PAGE_SIZE;
+            let next = if i == 0 { 7 } else { PAGE_NONE };
+            zblob[page + 12..page + 16].copy_from_slice(&next.to_be_bytes());
+            zblob[page + 24..page + 26]
+                .copy_from_slice(&(PageTypes::SDI_ZBLOB as u16).to_be_bytes());
+            zblob[page + FIL_HEADER_SIZE..page + UNIV_PAGE_SIZE - FIL_TRAILER_SIZE].fill(0);
+            zblob[page + FIL_HEADER_SIZE..page + FIL_HEADER_SIZE + part.len()]
+                .copy_from_slice(part);
+        }
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(zblob))?;
+        assert_eq!(fact.read_sdi_extern(&ext)?, comped);
+        Ok(())
+    }
 
-        let coldefs = dd_object
-            .columns
+    #[test]
+    fn record_row_version() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_RV_4))?;
+        let rs = fact.unpack_index_page(4, false)?;
+        let versions = rs
+            .records
             .iter()
-            .map(ColumnDef::from)
+            .map(|rec| rec.rec_hdr.row_version())
             .collect::<Vec<_>>();
-        let idxdefs = dd_object
-            .indexes
-            .iter()
-            .map(|idx| {
-                let ele_defs: Vec<IndexElementDef> = idx
-                    .elements
-                    .iter()
-                    .map(|ele| {
-                        let ref_col = &coldefs[ele.column_opx as usize];
-                        IndexElementDef::from(ele, ref_col)
-                    })
-                    .collect();
+        assert_eq!(versions, vec![None, Some(1), Some(2)]);
+        for rec in &rs.records {
+            assert!(!rec.rec_hdr.is_instant());
+            assert_eq!(rec.rec_hdr.n_instant_col(), None);
+            assert_eq!(
+                rec.rec_hdr.row_version().unwrap_or(0),
+                rec.row_info.row_version
+            );
+        }
+        Ok(())
+    }
 
-                IndexDef::from(idx, ele_defs)
-            })
-            .collect();
-        debug!("idxdefs={:?}", &idxdefs);
+    #[test]
+    fn trim_char_padding() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_RV_4))?;
+        let mut rs = fact.unpack_index_page(4, false)?;
+        let c1 = |rs: &ResultSet| rs.tuples[0].iter().find(|e| e.0 == "c1").unwrap().1.clone();
+        assert_eq!(c1(&rs), DataValue::Str("r1c1      ".into()));
+        rs.trim_char_columns();
+        assert_eq!(c1(&rs), DataValue::Str("r1c1".into()));
 
-        Ok(Arc::from(TableDef::from(
-            &dd_object, coll, coldefs, idxdefs,
-        )))
+        // VARCHAR keeps the trailing spaces
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let mut rs = fact.unpack_index_page(4, false)?;
+        rs.tuples[0][3].1 = DataValue::Str("Marketing  ".into());
+        rs.trim_char_columns();
+        assert_eq!(rs.tuples[0][0].1, DataValue::Str("d001".into()));
+        assert_eq!(rs.tuples[0][3].1, DataValue::Str("Marketing  ".into()));
+        Ok(())
     }
 
-    pub fn unpack_index_page(&mut self, page_no: usize, garbage: bool) -> Result<ResultSet, Error> {
-        let page: BasePage<IndexPageBody> = self.read_page(page_no)?;
-        // let page_level = page.page_body.idx_hdr.page_level;
-        // if page_level != 0 {
-        //     return Err(Error::msg(format!(
-        //         "不支持查看非叶子节点: page_level={:?}",
-        //         page_level
-        //     )));
-        // }
+    #[test]
+    fn filter_by_trx_id() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_RV_4))?;
+        let mut rs = fact.unpack_index_page(4, false)?;
+        rs.filter_trx_id(4458);
+        assert_eq!(rs.tuples.len(), 1);
+        assert_eq!(rs.records.len(), 1);
+        let c1 = rs.tuples[0].iter().find(|ent| ent.0 == "c1").unwrap();
+        assert_eq!(c1.1, DataValue::Str("r2c1      ".into()));
 
-        let tabdef = self.load_table_def()?;
-        let index_id = page.page_body.idx_hdr.page_index_id;
-        let index = match tabdef
-            .idx_defs
-            .iter()
-            .enumerate()
-            .find(|idx| idx.1.idx_id == index_id as i32)
-        {
-            Some(val) => val,
-            None => {
-                return Err(Error::msg(format!(
-                    "未找到索引的元信息: index_id={}",
-                    index_id
-                )));
-            }
-        };
-        info!("当前页所引用的索引: index_name={}", index.1.idx_name);
+        rs.filter_trx_id(1);
+        assert!(rs.tuples.is_empty());
+        assert!(rs.records.is_empty());
+        Ok(())
+    }
 
-        let rec_list = if garbage {
-            page.page_body.read_free_records(tabdef.clone(), index.0)?
-        } else {
-            page.page_body.read_user_records(tabdef.clone(), index.0)?
-        };
-        debug!("rec_list={:?}", rec_list);
+    #[test]
+    fn gen_csv_quoting() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let mut rs = fact.unpack_index_page(4, false)?;
+        rs.project(&["dept_no".into(), "dept_name".into()])?;
+        rs.tuples.truncate(1);
+        rs.tuples[0][1].1 = DataValue::Str("Sales, \"East\"".into());
+        rs.tuples[0].push(("note".into(), DataValue::Null));
+
+        let lines = rs.to_csv(',', true, "");
+        assert_eq!(lines[0], "dept_no,dept_name,note");
+        assert_eq!(lines[1], r#"d001,"Sales, ""East""","#);
+        let field = &lines[1]["d001,".len()..lines[1].len() - 1];
+        let decoded = field[1..field.len() - 1].replace("\"\"", "\"");
+        assert_eq!(decoded, "Sales, \"East\"");
+
+        let lines = rs.to_csv(';', false, "");
+        assert_eq!(lines, vec![r#"d001;"Sales, ""East""";"#]);
+
+        let lines = rs.to_csv(',', false, "\\N");
+        assert_eq!(lines, vec![r#"d001,"Sales, ""East""",\N"#]);
+
+        // the string equals to the token is quoted
+        rs.tuples[0][1].1 = DataValue::Str("NULL".into());
+        let lines = rs.to_csv(',', false, "NULL");
+        assert_eq!(lines, vec![r#"d001,"NULL",NULL"#]);
+        Ok(())
+    }
 
-        let tuples = rec_list
-            .iter()
-            .map(|rec| {
-                rec.row_data
-                    .data_list
-                    .iter()
-                    .map(|d| {
-                        if d.opx == PAGE_NONE as usize {
-                            return (
-                                "NODE_PTR".to_string(),
-                                DataValue::PageNo(u32_val(&d.rbuf.clone().unwrap(), 0)),
-                            );
-                        }
-                        let col = &tabdef.col_defs[d.opx];
-                        let val = match &d.rbuf {
-                            Some(b) => match col.hidden {
-                                HiddenTypes::HT_VISIBLE => match col.dd_type {
-                                    ColumnTypes::LONG => DataValue::I32(unpack_i32_val(b)),
-                                    ColumnTypes::LONGLONG => DataValue::I64(unpack_i64_val(b)),
-                                    ColumnTypes::NEWDATE => DataValue::Date(
-                                        unpack_newdate_val(b)
-                                            .unwrap_or_else(|| panic!("日期格式错误: {:?}", &d)),
-                                    ),
-                                    ColumnTypes::DATETIME2 => DataValue::DateTime(
-                                        unpack_datetime2_val(b)
-                                            .unwrap_or_else(|| panic!("时间格式错误: {:?}", &d)),
-                                    ),
-                                    ColumnTypes::TIMESTAMP2 => {
-                                        DataValue::Timestamp(unpack_timestamp2_val(b))
-                                    }
-                                    ColumnTypes::VARCHAR
-                                    | ColumnTypes::VAR_STRING
-                                    | ColumnTypes::STRING => {
-                                        let text = String::from_utf8(b.to_vec())
-                                            .unwrap_or_else(|_| panic!("字符串格式错误: {:?}", &d));
-                                        DataValue::Str(text)
-                                    }
-                                    ColumnTypes::ENUM => DataValue::Enum(unpack_enum_val(b)),
-                                    _ => {
-                                        warn!("不支持解析的类型: {:?}", &col);
-                                        DataValue::Unknown(b.clone())
-                                    }
-                                },
-                                HiddenTypes::HT_HIDDEN_SE => match col.col_name.as_str() {
-                                    "DB_ROW_ID" => DataValue::RowId(unpack_u48_val(b)),
-                                    "DB_TRX_ID" => DataValue::TrxId(unpack_u48_val(b)),
-                                    "DB_ROLL_PTR" => {
-                                        DataValue::RbPtr(RollPtr::new(unpack_u56_val(b)))
-                                    }
-                                    _ => todo!("不支持的隐藏字段名称: {:?}", col),
-                                },
-                                _ => todo!("不支持的隐藏字段类型: {:?}", col),
-                            },
-                            None => DataValue::Null,
-                        };
-                        (col.col_name.clone(), val)
-                    })
-                    .collect()
-            })
-            .collect();
+    #[test]
+    fn read_page_from_bytes() -> Result<(), Error> {
+        util::init_unit_test();
+        let data = std::fs::read(IBD_DEPT)?;
+        let page = &data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+
+        let index_page: BasePage<IndexPageBody> = BasePage::from_slice(page)?;
+        assert_eq!(index_page.fil_hdr.page_type, PageTypes::INDEX);
+        assert_eq!(index_page.page_body.idx_hdr.page_n_recs, 9);
+        assert!(BasePage::<IndexPageBody>::from_slice(&page[1..]).is_err());
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::copy_from_slice(page))?;
+        assert_eq!(fact.page_count(), 1);
+        assert_eq!(fact.read_fil_hdr(0)?.page_type, PageTypes::INDEX);
+        let index_page: BasePage<IndexPageBody> = fact.read_page(0)?;
+        assert_eq!(index_page.page_body.idx_hdr.page_n_recs, 9);
+        assert!(fact.read_fil_hdr(1).is_err());
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert_eq!(fact.page_count(), 8);
+        assert_eq!(fact.file_buffer()?.len(), data.len());
+        assert_eq!(fact.unpack_index_page(4, false)?.tuples.len(), 9);
+        Ok(())
+    }
 
-        Ok(ResultSet {
-            garbage,
-            tabdef: tabdef.clone(),
-            records: rec_list,
-            tuples,
-        })
+    #[test]
+    fn xdes_entry_cache_stats() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
+        let boffset = fsp_page.page_body.xdes_ent_list[0].flst_node.addr as u16;
+        assert_eq!(fact.cache_stats(), CacheStats::default());
+
+        let first = fact.read_xdes_entry(0, boffset)?;
+        let stats = fact.cache_stats();
+        assert_eq!((stats.extent_hits, stats.extent_misses), (0, 1));
+        assert!(stats.extent_entries > 0);
+
+        let second = fact.read_xdes_entry(0, boffset)?;
+        assert_eq!(second.xdes_seq, first.xdes_seq);
+        let stats = fact.cache_stats();
+        assert_eq!((stats.extent_hits, stats.extent_misses), (1, 1));
+        assert_eq!((stats.inode_hits, stats.inode_misses), (0, 0));
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod factory_tests {
+    #[test]
+    fn read_page_compressed() -> Result<(), Error> {
+        use std::io::Write;
 
-    use std::path::PathBuf;
+        use flate2::{write::ZlibEncoder, Compression};
 
-    use anyhow::Error;
-    use bytes::Bytes;
-    use log::{debug, info};
+        util::init_unit_test();
+        let mut data = std::fs::read(IBD_DEPT)?;
+        let page = &mut data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+
+        // page-level compression of the index page 4 with zlib
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&page[FIL_HEADER_SIZE..])?;
+        let comped = encoder.finish()?;
+        let original_size = (UNIV_PAGE_SIZE - FIL_HEADER_SIZE) as u16;
+        page[24..26].copy_from_slice(&(PageTypes::COMPRESSED as u16).to_be_bytes());
+        page[26] = 1; // version
+        page[27] = 1; // zlib
+        page[28..30].copy_from_slice(&(PageTypes::INDEX as u16).to_be_bytes());
+        page[30..32].copy_from_slice(&original_size.to_be_bytes());
+        page[32..34].copy_from_slice(&(comped.len() as u16).to_be_bytes());
+        page[FIL_HEADER_SIZE..].fill(0);
+        page[FIL_HEADER_SIZE..FIL_HEADER_SIZE + comped.len()].copy_from_slice(&comped);
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert_eq!(fact.read_fil_hdr(4)?.page_type, PageTypes::COMPRESSED);
+        let index_page: BasePage<IndexPageBody> = fact.read_page(4)?;
+        assert_eq!(index_page.fil_hdr.page_type, PageTypes::INDEX);
+        let rs = fact.unpack_index_page(4, false)?;
+        assert_eq!(rs.tuples.len(), 9);
+        assert_eq!(rs.tuples[0][0].1, DataValue::Str("d001".into()));
 
-    use crate::{
-        factory::{DataValue, DatafileFactory},
-        ibd::page::{BasePage, FileSpaceHeaderPageBody, PageTypes, FIL_HEADER_SIZE},
-        util,
-    };
+        // unsupported algorithm
+        data[UNIV_PAGE_SIZE * 4 + 27] = 3;
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        assert!(fact.read_page::<IndexPageBody>(4).is_err());
+        Ok(())
+    }
 
-    // employee schema
-    const IBD_DEPT: &str = "data/departments.ibd";
-    const IBD_DEPT_MGR: &str = "data/dept_manager.ibd";
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn read_page_compressed_lz4() -> Result<(), Error> {
+        use crate::ibd::page::{CompressionTypes, PageCompressionHeader};
 
-    // tb_row_version.sql
-    const IBD_RV_0: &str = "data/tb_row_version_0.ibd";
-    const IBD_RV_1: &str = "data/tb_row_version_1.ibd";
-    const IBD_RV_2: &str = "data/tb_row_version_2.ibd";
-    const IBD_RV_3: &str = "data/tb_row_version_3.ibd";
-    const IBD_RV_4: &str = "data/tb_row_version_4.ibd";
+        util::init_unit_test();
+        let mut data = std::fs::read(IBD_DEPT)?;
+        let page = &mut data[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5];
+
+        // page-level compression of the index page 4 with lz4, the fixture is
+        // compressed by liblz4, the holes after the compressed data are zeros
+        // like the punched file
+        let comped = std::fs::read("data/lz4_page_04.lz4")?;
+        assert!(comped.len() < UNIV_PAGE_SIZE / 2);
+        let original_size = (UNIV_PAGE_SIZE - FIL_HEADER_SIZE) as u16;
+        page[24..26].copy_from_slice(&(PageTypes::COMPRESSED as u16).to_be_bytes());
+        page[26] = 1; // version
+        page[27] = 2; // lz4
+        page[28..30].copy_from_slice(&(PageTypes::INDEX as u16).to_be_bytes());
+        page[30..32].copy_from_slice(&original_size.to_be_bytes());
+        page[32..34].copy_from_slice(&(comped.len() as u16).to_be_bytes());
+        page[FIL_HEADER_SIZE..].fill(0);
+        page[FIL_HEADER_SIZE..FIL_HEADER_SIZE + comped.len()].copy_from_slice(&comped);
+
+        let mut fact = DatafileFactory::from_bytes(Bytes::from(data.clone()))?;
+        let comp_hdr = PageCompressionHeader::new(0, fact.fil_hdr_buffer(4)?);
+        assert_eq!(comp_hdr.algorithm, CompressionTypes::LZ4);
+        let rs = fact.unpack_index_page(4, false)?;
+        assert_eq!(rs.tuples.len(), 9);
+        assert_eq!(rs.tuples[0][0].1, DataValue::Str("d001".into()));
+        Ok(())
+    }
 
-    // tb_instant_col.sql
-    const IBD_IC_0: &str = "data/tb_instant_col_0.ibd";
-    const IBD_IC_1: &str = "data/tb_instant_col_1.ibd";
-    const IBD_IC_2: &str = "data/tb_instant_col_2.ibd";
+    #[test]
+    fn load_tablespace_object() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let tbs = fact.load_tablespace_object()?;
+        assert_eq!(tbs.dd_object.name, "employees/departments");
+        assert_eq!(tbs.dd_object.engine, "InnoDB");
+        let priv_data = util::conv_strdata_to_map(&tbs.dd_object.se_private_data);
+        assert_eq!(priv_data.get("id"), Some(&"3".to_string()));
+        Ok(())
+    }
 
     #[test]
-    fn load_buffer() -> Result<(), Error> {
+    fn load_sdi_compact() -> Result<(), Error> {
         util::init_unit_test();
         let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
-        let buf = fact.fil_hdr_buffer(0)?;
-        assert_eq!(buf.len(), FIL_HEADER_SIZE);
+        let pretty = fact.load_sdi_string(false)?;
+        let compact = fact.load_sdi_string(true)?;
+        assert_eq!(pretty.len(), compact.len());
+        for (p, c) in pretty.iter().zip(compact.iter()) {
+            assert!(serde_json::from_str::<serde_json::Value>(c).is_ok());
+            assert!(c.len() < p.len());
+            assert!(!c.contains('\n'));
+        }
         Ok(())
     }
 
     #[test]
-    fn read_fsp_hdr_page() -> Result<(), Error> {
+    fn load_split_datafiles() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
+        let data = fact.file_buffer()?;
+        let half = fact.page_count() / 2 * UNIV_PAGE_SIZE;
+
+        let part1 = util::TempFile::new("split", &data[..half])?;
+        let part2 = util::TempFile::new("split", &data[half..])?;
+
+        let mut split = DatafileFactory::from_files(vec![part1.path.clone(), part2.path.clone()])?;
+        assert_eq!(split.page_count(), fact.page_count());
+        for page_no in 0..fact.page_count() {
+            assert_eq!(split.page_buffer(page_no)?, fact.page_buffer(page_no)?);
+        }
+        assert_eq!(split.file_buffer()?, data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_fsp_flags() -> Result<(), Error> {
         util::init_unit_test();
         let mut fact = DatafileFactory::from_file(PathBuf::from(IBD_DEPT))?;
         let fsp_page: BasePage<FileSpaceHeaderPageBody> = fact.read_page(0)?;
-        // info!("fsp_page={:#?}", fsp_page);
-        assert_eq!(fsp_page.fil_hdr.page_type, PageTypes::FSP_HDR);
-        assert_eq!(fsp_page.fil_hdr.server_version(), 80037);
-        assert_eq!(fsp_page.fil_hdr.space_version(), 1);
+        let flags = &fsp_page.page_body.fsp_hdr.fsp_flags;
+        assert!(flags.sdi);
+        assert_eq!(flags.page_size(), 16384);
+        assert_eq!(flags.zip_size(), 0);
         Ok(())
     }
 
@@ -838,12 +2679,14 @@ mod factory_tests_run {
     use std::path::PathBuf;
 
     use anyhow::Error;
+    use log::debug;
 
     use crate::{
         factory::DatafileFactory,
         ibd::{
-            page::{BasePage, IndexPageBody, PageNumber, PageTypes},
-            redo::Blocks,
+            page::{BasePage, IndexPageBody, PageNumber, PageTypes, UNIV_PAGE_SIZE},
+            redo::{Blocks, LogFile, LogHeaderFlags, RedoRecordPayloads},
+            undo::{UndoRecordPayloads, UndoTypes},
         },
         util,
     };
@@ -860,8 +2703,16 @@ mod factory_tests_run {
 
         if let Blocks::FileHeader(hdr) = b0 {
             assert_eq!(hdr.creator, "MySQL 8.0.37");
+            assert!(!hdr.log_hdr_flags.no_logging);
         }
 
+        let flags = LogHeaderFlags::new(0b1010);
+        assert!(!flags.no_logging);
+        assert!(flags.crash_unsafe);
+        assert!(!flags.not_initialized);
+        assert!(flags.file_full);
+        assert_eq!(flags.unused, 0);
+
         let b1 = fact.read_block(1)?;
         assert!(matches!(b1, Blocks::Checkpoint(_)));
 
@@ -869,8 +2720,123 @@ mod factory_tests_run {
         assert!(matches!(b4, Blocks::Block(_)));
         if let Blocks::Block(blk) = b4 {
             assert_eq!(blk.data_len, 512);
+            assert!(blk.checksum_ok);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn iterate_redo_log_records() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(REDO_1))?;
+        let log_file = LogFile::new(0, fact.file_buffer()?);
+        let n_first = log_file
+            .log_block_list
+            .iter()
+            .filter(|blk| matches!(blk, Blocks::Block(b) if b.log_record.is_some()))
+            .count();
+        let mut stats = std::collections::BTreeMap::new();
+        let mut n_recs = 0;
+        for rec in log_file.records() {
+            *stats
+                .entry(rec.log_rec_hdr.log_rec_type.clone())
+                .or_insert(0) += 1;
+            n_recs += 1;
+        }
+        debug!("n_first={}, n_recs={}, stats={:?}", n_first, n_recs, stats);
+        assert!(n_recs > n_first);
+        Ok(())
+    }
+
+    #[test]
+    fn view_redo_table_dynamic_meta() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(REDO_1))?;
+        let b52 = fact.read_block(52)?;
+        if let Blocks::Block(blk) = b52 {
+            let rec = blk.log_record.unwrap();
+            if let RedoRecordPayloads::TableMeta(meta) = rec.redo_rec_data {
+                assert_eq!(meta.table_id, 21);
+                assert_eq!(meta.version, 0);
+                assert_eq!(meta.auto_inc, Some(73));
+                assert_eq!(meta.total_bytes, 4);
+            } else {
+                panic!("expect MLOG_TABLE_DYNAMIC_META");
+            }
+        } else {
+            panic!("expect log block");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn view_redo_sec_delete_mark() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(REDO_1))?;
+        let log_file = LogFile::new(0, fact.file_buffer()?);
+        let marks: Vec<_> = log_file
+            .records()
+            .filter_map(|rec| match rec.redo_rec_data {
+                RedoRecordPayloads::RecSecIndexDeleteMark(mark) => Some(mark),
+                _ => None,
+            })
+            .collect();
+        debug!("marks[0]={:?}", marks.first());
+        assert!(!marks.is_empty());
+        for mark in marks {
+            assert!(mark.index_info.is_none());
+            assert_eq!(mark.total_bytes, 3);
+            assert!(mark.value <= 1);
+            assert!((mark.offset as usize) < UNIV_PAGE_SIZE);
         }
+        Ok(())
+    }
+
+    #[test]
+    fn view_redo_page_create() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(REDO_1))?;
+        let log_file = LogFile::new(0, fact.file_buffer()?);
+        let kinds: Vec<_> = log_file
+            .records()
+            .filter_map(|rec| match rec.redo_rec_data {
+                RedoRecordPayloads::PageCreate(kind) => Some(kind),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(kinds.len(), 12);
+        assert!(kinds.iter().all(|kind| *kind == PageTypes::INDEX));
+        Ok(())
+    }
 
+    #[test]
+    fn view_redo_undo_insert() -> Result<(), Error> {
+        util::init_unit_test();
+        let mut fact = DatafileFactory::from_file(PathBuf::from(REDO_1))?;
+        let log_file = LogFile::new(0, fact.file_buffer()?);
+        let inserts: Vec<_> = log_file
+            .records()
+            .filter_map(|rec| match rec.redo_rec_data {
+                RedoRecordPayloads::UndoInsert(ins) => Some(ins),
+                _ => None,
+            })
+            .collect();
+        assert!(!inserts.is_empty());
+        let mut n_insert_recs = 0;
+        for ins in &inserts {
+            assert_eq!(ins.data.len(), ins.data_len as usize);
+            match &ins.undo_rec.undo_rec_data {
+                UndoRecordPayloads::Insert(r) => {
+                    assert_eq!(ins.undo_rec.undo_rec_hdr.type_info, UndoTypes::INSERT_REC);
+                    assert!(r.table_id > 0);
+                    assert_eq!(r.key_fields.len(), 1);
+                    n_insert_recs += 1;
+                }
+                _ => assert_ne!(ins.undo_rec.undo_rec_hdr.type_info, UndoTypes::INSERT_REC),
+            }
+        }
+        assert!(n_insert_recs > 0);
         Ok(())
     }
 
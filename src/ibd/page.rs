@@ -1,5 +1,6 @@
 use core::fmt;
 use std::{
+    collections::HashSet,
     fmt::{Debug, Display, Formatter},
     sync::Arc,
 };
@@ -8,7 +9,7 @@ use anyhow::{Error, Result};
 use bytes::Bytes;
 use colored::Colorize;
 use derivative::Derivative;
-use log::{debug, info};
+use log::{debug, info, warn};
 use num_enum::FromPrimitive;
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use strum::{Display, EnumString};
@@ -16,7 +17,7 @@ use strum::{Display, EnumString};
 use super::{sdi::SdiRecord, undo::UndoLog};
 use crate::{
     ibd::{
-        record::{Record, RecordHeader, RowData, RowInfo},
+        record::{Record, RecordHeader, RecordStatus, RowData, RowInfo},
         sdi::SdiDataHeader,
     },
     meta::def::TableDef,
@@ -43,6 +44,8 @@ pub const FSP_TRX_SYS_PAGE_NO: usize = 5;
 pub const FSP_FIRST_RSEG_PAGE_NO: usize = 6;
 /// data dictionary header page, in tablespace 0
 pub const FSP_DICT_HDR_PAGE_NO: usize = 7;
+/// rollback segment array page, in the undo tablespace
+pub const FSP_RSEG_ARRAY_PAGE_NO: usize = 3;
 
 // file
 pub const FIL_HEADER_SIZE: usize = 38;
@@ -72,6 +75,19 @@ pub const INF_PAGE_BYTE_OFF: usize = 99;
 pub const SUP_PAGE_BYTE_OFF: usize = 112;
 pub const RECORD_HEADER_SIZE: usize = 5;
 
+// rtree
+pub const RTREE_MBR_SIZE: usize = 32;
+pub const RTREE_SPLIT_SEQ_NUM_OFF: usize = 26;
+
+// lob
+pub const LOB_INDEX_ENTRY_SIZE: usize = 60;
+pub const LOB_FIRST_INDEX_ENTRY_COUNT: usize = 10;
+pub const LOB_FIRST_INDEX_BEGIN: usize = 58;
+pub const LOB_FIRST_DATA_BEGIN: usize =
+    LOB_FIRST_INDEX_BEGIN + LOB_FIRST_INDEX_ENTRY_COUNT * LOB_INDEX_ENTRY_SIZE;
+pub const LOB_INDEX_PAGE_BEGIN: usize = 1;
+pub const LOB_DATA_PAGE_BEGIN: usize = 11;
+
 // TRX_SYS transaction system page
 pub const TRX_SYS_N_RSEGS: usize = 128;
 
@@ -161,6 +177,24 @@ impl From<u32> for SpaceId {
     }
 }
 
+impl From<&SpaceId> for u32 {
+    fn from(value: &SpaceId) -> u32 {
+        match value {
+            SpaceId::SystemSpace => TRX_SYS_SPACE_ID,
+            SpaceId::Invalid => INVALID_SPACE_ID,
+            SpaceId::DdDictSpace => 1,
+            SpaceId::DdSysSpace => 2,
+            SpaceId::DdTempSpace => 3,
+            SpaceId::RedoSpace => REDO_LOG_SPACE_ID,
+            SpaceId::UndoSpace(num) => UNDO_SPACE_ID_MAX - num + 1,
+            SpaceId::DictSpace => DICT_SPACE_ID,
+            SpaceId::InnoTempSpace => INNODB_TEMP_SPACE_ID,
+            SpaceId::TempSpace(num) => TEMP_SPACE_ID_MAX - num + 1,
+            SpaceId::Space(val) => *val,
+        }
+    }
+}
+
 impl Display for SpaceId {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -246,13 +280,27 @@ impl<B> BasePage<B>
 where
     B: BasePageBody,
 {
+    /// parse a page from the in-memory bytes of exactly one page
+    pub fn from_slice(data: &[u8]) -> Result<BasePage<B>> {
+        if data.len() != UNIV_PAGE_SIZE {
+            return Err(Error::msg(format!(
+                "页面数据长度错误: len={}, page_size={}",
+                data.len(),
+                UNIV_PAGE_SIZE
+            )));
+        }
+        Ok(Self::new(0, Arc::new(Bytes::copy_from_slice(data))))
+    }
+
     pub fn new(addr: usize, buf: Arc<Bytes>) -> BasePage<B> {
         let header = FilePageHeader::new(0, buf.clone());
         let trailer = FilePageTrailer::new(buf.len() - FIL_TRAILER_SIZE, buf.clone());
-        assert_eq!(
-            header.check_sum, trailer.check_sum,
-            "fil_hdr 和 trl_hdr 校验和不一致"
-        );
+        util::check(header.check_sum == trailer.check_sum, || {
+            format!(
+                "fil_hdr 和 trl_hdr 校验和不一致: page_no={}, hdr={}, trl={}",
+                header.page_no, header.check_sum, trailer.check_sum
+            )
+        });
 
         let body = BasePageBody::new(FIL_HEADER_SIZE, buf.clone());
 
@@ -421,6 +469,9 @@ pub struct FilePageHeader {
 
 impl FilePageHeader {
     pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        if util::is_assume_le() {
+            return Self::new_le(addr, buf);
+        }
         Self {
             check_sum: util::u32_val(&buf, addr),
             page_no: util::u32_val(&buf, addr + 4).into(),
@@ -435,6 +486,22 @@ impl FilePageHeader {
         }
     }
 
+    /// read the header in little-endian, see `--assume-le`
+    pub fn new_le(addr: usize, buf: Arc<Bytes>) -> Self {
+        Self {
+            check_sum: util::u32_val_le(&buf, addr),
+            page_no: util::u32_val_le(&buf, addr + 4).into(),
+            prev_page: util::u32_val_le(&buf, addr + 8),
+            next_page: util::u32_val_le(&buf, addr + 12),
+            lsn: util::u64_val_le(&buf, addr + 16),
+            page_type: util::u16_val_le(&buf, addr + 24).into(),
+            flush_lsn: util::u64_val_le(&buf, addr + 26),
+            space_id: util::u32_val_le(&buf, addr + 34).into(),
+            buf: buf.clone(),
+            addr,
+        }
+    }
+
     pub fn server_version(&self) -> u32 {
         self.prev_page
     }
@@ -467,9 +534,78 @@ pub struct FilePageTrailer {
 
 impl FilePageTrailer {
     pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        let u32_val = if util::is_assume_le() {
+            util::u32_val_le
+        } else {
+            util::u32_val
+        };
         Self {
-            check_sum: util::u32_val(&buf, addr),
-            lsn_low32bit: util::u32_val(&buf, addr + 4),
+            check_sum: u32_val(&buf, addr),
+            lsn_low32bit: u32_val(&buf, addr + 4),
+            buf: buf.clone(),
+            addr,
+        }
+    }
+}
+
+/// page-level compression algorithm, see Compression::Type in file.h
+#[repr(u8)]
+#[derive(Debug, Display, Default, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[derive(Deserialize_repr, Serialize_repr, EnumString, FromPrimitive)]
+pub enum CompressionTypes {
+    /// No compression
+    NONE = 0,
+
+    /// Use ZLib
+    ZLIB = 1,
+
+    /// Use LZ4
+    LZ4 = 2,
+
+    #[default]
+    UNKNOWN,
+}
+
+/// page-level compression (transparent page compression) header, the fields
+/// overlap FIL_PAGE_FILE_FLUSH_LSN of the FIL header, see
+/// Compression::deserialize(...)
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct PageCompressionHeader {
+    /// page address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (1 byte) compression format version, FIL_PAGE_VERSION
+    pub version: u8,
+
+    /// (1 byte) compression algorithm, FIL_PAGE_ALGORITHM_V1
+    #[derivative(Debug(format_with = "util::fmt_enum"))]
+    pub algorithm: CompressionTypes,
+
+    /// (2 bytes) original page type, FIL_PAGE_ORIGINAL_TYPE_V1
+    #[derivative(Debug(format_with = "util::fmt_enum"))]
+    pub original_type: PageTypes,
+
+    /// (2 bytes) original data size excluding FIL header, FIL_PAGE_ORIGINAL_SIZE_V1
+    pub original_size: u16,
+
+    /// (2 bytes) compressed data size, FIL_PAGE_COMPRESS_SIZE_V1
+    pub compress_size: u16,
+}
+
+impl PageCompressionHeader {
+    pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        Self {
+            version: util::u8_val(&buf, addr + 26),
+            algorithm: util::u8_val(&buf, addr + 27).into(),
+            original_type: util::u16_val(&buf, addr + 28).into(),
+            original_size: util::u16_val(&buf, addr + 30),
+            compress_size: util::u16_val(&buf, addr + 32),
             buf: buf.clone(),
             addr,
         }
@@ -632,6 +768,24 @@ impl FileSpaceFlags {
             unused: (flags >> 15) & 0x3ffff,
         }
     }
+
+    /// logical page size in bytes, page_ssize = 0 means UNIV_PAGE_SIZE_ORIG
+    pub fn page_size(&self) -> usize {
+        if self.page_ssize == 0 {
+            UNIV_PAGE_SIZE
+        } else {
+            512 << self.page_ssize
+        }
+    }
+
+    /// compressed page size in bytes, 0 means not compressed
+    pub fn zip_size(&self) -> usize {
+        if self.zip_ssize == 0 {
+            0
+        } else {
+            512 << self.zip_ssize
+        }
+    }
 }
 
 /// FSP Header, see fsp0fsp.h
@@ -743,6 +897,13 @@ impl FileSpaceHeaderPageBody {
     // static constexpr size_t INFO_MAX_SIZE = INFO_SIZE + sizeof(uint32);
     const INFO_MAX_SIZE: usize = 115;
 
+    /// encryption info stored after the XDES array, only meaningful if the
+    /// tablespace has encryption flag
+    pub fn encryption_info(&self) -> EncryptionInfo {
+        let info_addr = self.addr + FSP_HEADER_SIZE + XDES_ENTRY_MAX_COUNT * XDES_ENTRY_SIZE;
+        EncryptionInfo::new(info_addr, self.buf.clone())
+    }
+
     pub fn sdi_meta(&self) -> SdiMetaData {
         // sdi_addr, page offset = 10505
         let sdi_addr = self.addr
@@ -940,6 +1101,65 @@ impl XDesEntry {
     }
 }
 
+/// Encryption Info, see os0enc.h, the key and iv are encrypted by master key
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct EncryptionInfo {
+    /// page address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (3 bytes) magic, lCA => v1, lCB => v2, lCC => v3
+    pub magic: String,
+
+    /// (4 bytes) master key id
+    pub master_key_id: u32,
+
+    /// (36 bytes) server uuid, not present in v1
+    pub server_uuid: String,
+
+    /// (64 bytes) encrypted tablespace key and iv
+    #[derivative(Debug = "ignore")]
+    pub key_iv: Bytes,
+
+    /// (4 bytes) checksum of the tablespace key and iv
+    pub checksum: u32,
+}
+
+impl EncryptionInfo {
+    pub const MAGIC_V1: &'static str = "lCA";
+    pub const MAGIC_V2: &'static str = "lCB";
+    pub const MAGIC_V3: &'static str = "lCC";
+
+    pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        let magic = String::from_utf8_lossy(&buf[addr..addr + 3]).to_string();
+        let (server_uuid, key_addr) = if magic == Self::MAGIC_V1 {
+            (String::new(), addr + 7)
+        } else {
+            let uuid = &buf[addr + 7..addr + 43];
+            (String::from_utf8_lossy(uuid).to_string(), addr + 43)
+        };
+        Self {
+            master_key_id: util::u32_val(&buf, addr + 3),
+            server_uuid,
+            key_iv: buf.slice(key_addr..key_addr + 64),
+            checksum: util::u32_val(&buf, key_addr + 64),
+            magic,
+            buf: buf.clone(),
+            addr,
+        }
+    }
+
+    /// the magic is one of known versions
+    pub fn is_valid(&self) -> bool {
+        [Self::MAGIC_V1, Self::MAGIC_V2, Self::MAGIC_V3].contains(&self.magic.as_str())
+    }
+}
+
 /// SDI Meta Data
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
@@ -1074,6 +1294,43 @@ impl INodeEntry {
             addr,
         }
     }
+
+    /// total pages of the segment, including the extents and fragment pages
+    pub fn total_pages(&self) -> usize {
+        let n_extents = self.fseg_free.len + self.fseg_not_full.len + self.fseg_full.len;
+        n_extents as usize * XDES_PAGE_COUNT + self.fseg_frag_arr.len()
+    }
+
+    /// check the fragment page numbers are inside the tablespace and unique,
+    /// return the violations
+    pub fn check_frag_pages(&self, page_count: usize) -> Vec<String> {
+        let mut errs = vec![];
+        let mut seen = HashSet::new();
+        for page_no in &self.fseg_frag_arr {
+            if *page_no as usize >= page_count {
+                errs.push(format!(
+                    "碎片页码越界: page_no={}, page_count={}",
+                    page_no, page_count
+                ));
+            }
+            if !seen.insert(*page_no) {
+                errs.push(format!("碎片页码重复: page_no={}", page_no));
+            }
+        }
+        for err in &errs {
+            warn!("fseg_id={}, {}", self.fseg_id, err);
+        }
+        errs
+    }
+}
+
+/// which list of the index page a record is read from
+#[derive(Debug, Display, Eq, PartialEq, Clone, Copy)]
+pub enum RecordSource {
+    /// live record in the user record list
+    User,
+    /// deleted record in the garbage list, maybe recoverable
+    Free,
 }
 
 /// Index Page
@@ -1117,11 +1374,9 @@ impl BasePageBody for IndexPageBody {
     fn new(addr: usize, buf: Arc<Bytes>) -> Self {
         let idx_hdr = IndexHeader::new(addr, buf.clone());
         debug!("idx_hdr={:?}", &idx_hdr);
-        assert_eq!(
-            idx_hdr.page_format,
-            PageFormats::COMPACT,
-            "只支持 COMPACT 行记录格式"
-        );
+        util::check(idx_hdr.page_format == PageFormats::COMPACT, || {
+            format!("只支持 COMPACT 行记录格式: {:?}", idx_hdr.page_format)
+        });
 
         // Infimum
         let inf = RecordHeader::new(
@@ -1130,21 +1385,25 @@ impl BasePageBody for IndexPageBody {
         );
 
         // Parse Record Headers
-        let mut free_addr = (INF_PAGE_BYTE_OFF as i16 + inf.next_rec_offset) as usize;
-        let data_hdrs = (0..idx_hdr.page_n_recs)
-            .map(|_nrec| {
-                let rec_hdr = RecordHeader::new(free_addr - RECORD_HEADER_SIZE, buf.clone());
-                free_addr = rec_hdr.next_addr();
-                rec_hdr
-            })
-            .collect();
+        let mut free_addr = inf.next_addr();
+        let mut data_hdrs = vec![];
+        for _nrec in 0..idx_hdr.page_n_recs {
+            if !Self::is_valid_rec_addr(free_addr) {
+                warn!("错误的记录地址, 停止解析记录头: addr={}", free_addr);
+                break;
+            }
+            let rec_hdr = RecordHeader::new(free_addr - RECORD_HEADER_SIZE, buf.clone());
+            free_addr = rec_hdr.next_addr();
+            data_hdrs.push(rec_hdr);
+        }
 
         let mut free_hdrs = vec![];
+        let n_heap = idx_hdr.heap_count();
         if idx_hdr.page_garbage > 0 {
             let mut free_addr = idx_hdr.page_free as usize;
             loop {
                 // if addr is invalid, just break
-                if free_addr < SUP_PAGE_BYTE_OFF {
+                if !Self::is_valid_rec_addr(free_addr) || free_hdrs.len() >= n_heap {
                     break;
                 }
 
@@ -1173,15 +1432,13 @@ impl BasePageBody for IndexPageBody {
             .collect();
         debug!("slots={:?}", &slots);
 
-        assert_eq!(
-            buf.slice(INF_PAGE_BYTE_OFF..INF_PAGE_BYTE_OFF + 8).to_vec(),
-            vec![b'i', b'n', b'f', b'i', b'm', b'u', b'm', 0],
-            "infimum string checking"
+        util::check(
+            &buf[INF_PAGE_BYTE_OFF..INF_PAGE_BYTE_OFF + 8] == b"infimum\0",
+            || "infimum string checking".into(),
         );
-        assert_eq!(
-            buf.slice(SUP_PAGE_BYTE_OFF..SUP_PAGE_BYTE_OFF + 8).to_vec(),
-            vec![b's', b'u', b'p', b'r', b'e', b'm', b'u', b'm'],
-            "supremum string checking"
+        util::check(
+            &buf[SUP_PAGE_BYTE_OFF..SUP_PAGE_BYTE_OFF + 8] == b"supremum",
+            || "supremum string checking".into(),
         );
 
         Self {
@@ -1203,26 +1460,200 @@ impl BasePageBody for IndexPageBody {
 }
 
 impl IndexPageBody {
+    /// resolve page directory slots to the record header they own, the slot
+    /// offset points to the record origin, slots[0] owns infimum
+    pub fn dir_slot_records(&self) -> Vec<RecordHeader> {
+        self.page_dirs
+            .iter()
+            .map(|&off| {
+                RecordHeader::new(
+                    self.addr + off as usize - FIL_HEADER_SIZE - RECORD_HEADER_SIZE,
+                    self.buf.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// all records in the heap ordered by heap_no, including infimum and
+    /// supremum, the flag tells whether the record is out of the user list.
+    /// With the index layout the heap is scanned from supremum to heap top,
+    /// so the records unlinked from both lists are found, otherwise only the
+    /// records in the user and garbage lists are known
+    pub fn heap_records(
+        &self,
+        layout: Option<(Arc<TableDef>, usize)>,
+    ) -> Vec<(RecordHeader, bool)> {
+        let mut heap: Vec<(RecordHeader, bool)> = [&self.infimum, &self.supremum]
+            .into_iter()
+            .chain(self.data_rec_hdrs.iter())
+            .map(|h| (h.clone(), false))
+            .chain(self.free_rec_hdrs.iter().map(|h| (h.clone(), true)))
+            .collect();
+        if let Some((tabdef, index_pos)) = layout {
+            for rec_hdr in self.scan_heap(tabdef, index_pos) {
+                if heap.iter().all(|(h, _)| h.addr != rec_hdr.addr) {
+                    heap.push((rec_hdr, true));
+                }
+            }
+        }
+        heap.sort_by_key(|(h, _)| h.heap_no);
+        heap
+    }
+
+    /// walk the heap from the first byte after supremum up to PAGE_HEAP_TOP,
+    /// each record is decoded in turn. The space left by a shorter record
+    /// reusing a garbage record is skipped to the next record in the lists
+    fn scan_heap(&self, tabdef: Arc<TableDef>, index_pos: usize) -> Vec<RecordHeader> {
+        let heap_top = self.idx_hdr.page_heap_top as usize;
+        let n_heap = self.idx_hdr.heap_count();
+        let mut listed: Vec<usize> = self
+            .data_rec_hdrs
+            .iter()
+            .chain(self.free_rec_hdrs.iter())
+            .map(|h| h.addr + RECORD_HEADER_SIZE)
+            .collect();
+        listed.sort();
+
+        let mut recs = vec![];
+        let mut extra_addr = SUP_PAGE_BYTE_OFF + 8;
+        while extra_addr < heap_top && recs.len() + 2 < n_heap {
+            let found = self.locate_record(extra_addr, tabdef.clone(), index_pos);
+            let (rec_hdr, end) = match found {
+                Some(val) => val,
+                None => {
+                    let next = listed
+                        .iter()
+                        .find(|&&addr| addr > extra_addr)
+                        .and_then(|&addr| {
+                            self.record_extent(addr, tabdef.clone(), index_pos)
+                                .map(|(rec_hdr, _, end)| (rec_hdr, end))
+                        });
+                    match next {
+                        Some(val) => val,
+                        None => break,
+                    }
+                }
+            };
+            recs.push(rec_hdr);
+            extra_addr = end;
+        }
+        recs
+    }
+
+    /// probe the record origin after the extra bytes beginning at the address,
+    /// the record is found when its extra bytes resolve back to the address
+    fn locate_record(
+        &self,
+        extra_addr: usize,
+        tabdef: Arc<TableDef>,
+        index_pos: usize,
+    ) -> Option<(RecordHeader, usize)> {
+        // version byte, null bitmap and at most 2 length bytes for each field
+        let n_cols = tabdef.col_defs.len();
+        let max_extra = RECORD_HEADER_SIZE + 1 + n_cols.div_ceil(8) + 2 * n_cols;
+        (extra_addr + RECORD_HEADER_SIZE..=extra_addr + max_extra)
+            .filter_map(|addr| self.record_extent(addr, tabdef.clone(), index_pos))
+            .find(|(_, beg, _)| *beg == extra_addr)
+            .map(|(rec_hdr, _, end)| (rec_hdr, end))
+    }
+
+    /// the header, the beginning of extra bytes and the end of data of the
+    /// record at the origin, None if the header is not a plausible user record
+    fn record_extent(
+        &self,
+        rec_addr: usize,
+        tabdef: Arc<TableDef>,
+        index_pos: usize,
+    ) -> Option<(RecordHeader, usize, usize)> {
+        let heap_top = self.idx_hdr.page_heap_top as usize;
+        if rec_addr + RECORD_HEADER_SIZE > heap_top {
+            return None;
+        }
+        let rec_hdr = RecordHeader::new(rec_addr - RECORD_HEADER_SIZE, self.buf.clone());
+        let status = if self.idx_hdr.page_level > 0 {
+            RecordStatus::NODE_PTR
+        } else {
+            RecordStatus::ORDINARY
+        };
+        if rec_hdr.rec_status != status
+            || !(2..self.idx_hdr.heap_count()).contains(&(rec_hdr.heap_no as usize))
+        {
+            return None;
+        }
+        let row_info = RowInfo::new(&rec_hdr, tabdef, index_pos, self.idx_hdr.page_level);
+        let (metas, beg) = row_info.resolve_layout().ok()?;
+        let end = metas.last().map(|m| m.addr + m.length).unwrap_or(rec_addr);
+        if end > heap_top {
+            return None;
+        }
+        Some((rec_hdr, beg, end))
+    }
+
+    /// user record address must be after supremum and before page directory
+    fn is_valid_rec_addr(addr: usize) -> bool {
+        (SUP_PAGE_BYTE_OFF + 8..UNIV_PAGE_SIZE - FIL_TRAILER_SIZE - PAGE_DIR_ENTRY_SIZE)
+            .contains(&addr)
+    }
+
     pub fn read_user_records(
         &self,
         tabdef: Arc<TableDef>,
         index_pos: usize,
     ) -> Result<Vec<Record>, Error> {
-        let inf = &self.infimum;
-        let mut rec_addr = (INF_PAGE_BYTE_OFF as i16 + inf.next_rec_offset) as usize;
+        self.walk_user_records(|rec_addr| self.parse_record(rec_addr, tabdef.clone(), index_pos))
+    }
 
+    /// read the user records without decoding the row data, for the pages
+    /// whose index is missing in the table definition
+    pub fn read_raw_user_records(
+        &self,
+        tabdef: Arc<TableDef>,
+        index_pos: usize,
+    ) -> Result<Vec<Record>, Error> {
+        self.walk_user_records(|rec_addr| {
+            Ok(self.parse_raw_record(rec_addr, tabdef.clone(), index_pos))
+        })
+    }
+
+    fn walk_user_records<F>(&self, parse: F) -> Result<Vec<Record>, Error>
+    where
+        F: Fn(usize) -> Result<Record, Error>,
+    {
+        let mut rec_addr = self.infimum.next_addr();
+
+        // the heap contains infimum and supremum, cap the loop to avoid cycle
+        let max_recs = self.idx_hdr.heap_count();
         let mut records = vec![];
-        for nrec in 0..self.idx_hdr.page_n_recs {
-            let rec = self.parse_record(rec_addr, tabdef.clone(), index_pos)?;
-            info!("nrec={}, rec={:?}", nrec.to_string().green(), &rec);
+        while rec_addr != SUP_PAGE_BYTE_OFF {
+            if records.len() >= max_recs {
+                return Err(Error::msg(format!(
+                    "记录链表存在环, 超过最大记录数: n_heap={}, addr={}",
+                    max_recs, rec_addr
+                )));
+            }
+            if !Self::is_valid_rec_addr(rec_addr) {
+                let prev = records
+                    .last()
+                    .map(|r: &Record| r.addr)
+                    .unwrap_or(INF_PAGE_BYTE_OFF);
+                return Err(Error::msg(format!(
+                    "错误的记录地址: rec_addr={}, prev_addr={}",
+                    rec_addr, prev
+                )));
+            }
+            let rec = parse(rec_addr)?;
+            info!("nrec={}, rec={:?}", records.len().to_string().green(), &rec);
             rec_addr = rec.rec_hdr.next_addr();
             records.push(rec);
         }
 
-        assert_eq!(
-            rec_addr, SUP_PAGE_BYTE_OFF,
-            "记录地址 rec_addr 应该到达上确界"
-        );
+        if records.len() != self.idx_hdr.page_n_recs as usize {
+            warn!(
+                "记录数与页头不一致: n_recs={}, page_n_recs={}",
+                records.len(),
+                self.idx_hdr.page_n_recs
+            );
+        }
         Ok(records)
     }
 
@@ -1231,6 +1662,24 @@ impl IndexPageBody {
         tabdef: Arc<TableDef>,
         index_pos: usize,
     ) -> Result<Vec<Record>, Error> {
+        self.walk_free_records(|rec_addr| self.parse_record(rec_addr, tabdef.clone(), index_pos))
+    }
+
+    /// read the garbage records without decoding the row data
+    pub fn read_raw_free_records(
+        &self,
+        tabdef: Arc<TableDef>,
+        index_pos: usize,
+    ) -> Result<Vec<Record>, Error> {
+        self.walk_free_records(|rec_addr| {
+            Ok(self.parse_raw_record(rec_addr, tabdef.clone(), index_pos))
+        })
+    }
+
+    fn walk_free_records<F>(&self, parse: F) -> Result<Vec<Record>, Error>
+    where
+        F: Fn(usize) -> Result<Record, Error>,
+    {
         let mut rec_addr = self.idx_hdr.page_free as usize;
         let mut free_records = vec![];
         loop {
@@ -1240,7 +1689,7 @@ impl IndexPageBody {
             }
 
             // parse the garbage record
-            let rec = self.parse_record(rec_addr, tabdef.clone(), index_pos)?;
+            let rec = parse(rec_addr)?;
             let next_addr = rec.rec_hdr.next_addr();
             free_records.push(rec);
 
@@ -1254,7 +1703,40 @@ impl IndexPageBody {
         Ok(free_records)
     }
 
-    fn parse_record(
+    /// the bytes of the record from its origin to the next record in the heap,
+    /// or the heap top. The extra bytes of the next record are included, since
+    /// the field layout is unknown without the index definition
+    pub fn raw_record_bytes(&self, rec_addr: usize) -> Bytes {
+        let end = self
+            .heap_records(None)
+            .iter()
+            .map(|(h, _)| h.addr)
+            .filter(|&addr| addr + RECORD_HEADER_SIZE > rec_addr)
+            .min()
+            .unwrap_or(self.idx_hdr.page_heap_top as usize)
+            .max(rec_addr);
+        self.buf.slice(rec_addr..end)
+    }
+
+    fn parse_raw_record(&self, rec_addr: usize, tabdef: Arc<TableDef>, index_pos: usize) -> Record {
+        let rec_hdr = RecordHeader::new(rec_addr - RECORD_HEADER_SIZE, self.buf.clone());
+        let row_info = Arc::new(RowInfo::new(
+            &rec_hdr,
+            tabdef,
+            index_pos,
+            self.idx_hdr.page_level,
+        ));
+        let row_data = RowData {
+            addr: rec_addr,
+            buf: self.buf.clone(),
+            row_info: row_info.clone(),
+            meta_list: vec![],
+            data_list: vec![],
+        };
+        Record::new(rec_addr, self.buf.clone(), rec_hdr, row_info, row_data)
+    }
+
+    pub fn parse_record(
         &self,
         rec_addr: usize,
         tabdef: Arc<TableDef>,
@@ -1263,16 +1745,13 @@ impl IndexPageBody {
         // Record Header
         let rec_hdr = RecordHeader::new(rec_addr - RECORD_HEADER_SIZE, self.buf.clone());
 
-        // if rec_hdr.is_instant() {
-        //     return Err(Error::msg(format!("不支持解析 INSTANT 标记的记录: {:?}", &rec_hdr)));
-        // }
-
-        // if rec_hdr.is_version() {
-        //     return Err(Error::msg(format!("不支持解析 VERSION 标记的记录: {:?}", &rec_hdr)));
-        // }
-
         // Row Info: depends on table definition
-        let row_info = Arc::new(RowInfo::new(&rec_hdr, tabdef.clone(), index_pos));
+        let row_info = Arc::new(RowInfo::new(
+            &rec_hdr,
+            tabdef.clone(),
+            index_pos,
+            self.idx_hdr.page_level,
+        ));
 
         // Row Data: depends on table definition for unpack row
         let row_data = RowData::new(rec_addr, self.buf.clone(), row_info.clone());
@@ -1375,6 +1854,29 @@ pub struct IndexHeader {
 }
 
 impl IndexHeader {
+    /// number of records in the heap, without the page format flag
+    pub fn heap_count(&self) -> usize {
+        (self.page_n_heap & 0x7fff) as usize
+    }
+
+    /// summary of the insert pattern from the last insert direction stats
+    pub fn insert_pattern(&self) -> String {
+        if self.page_last_insert == 0 {
+            return "unknown, reset by deletion".into();
+        }
+        let n = self.page_n_direction;
+        match self.page_direction {
+            PageDirections::PAGE_RIGHT => {
+                format!("{} sequential right inserts, append-only", n)
+            }
+            PageDirections::PAGE_LEFT => format!("{} sequential left inserts, descending", n),
+            PageDirections::PAGE_SAME_REC => format!("{} inserts after the same record", n),
+            PageDirections::PAGE_SAME_PAGE => format!("{} inserts in the same page", n),
+            PageDirections::PAGE_NO_DIRECTION => "random inserts".into(),
+            PageDirections::UNDEF => "undefined".into(),
+        }
+    }
+
     pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
         let n_heap = util::u16_val(&buf, addr + 4);
         let fmt_flag = ((n_heap & 0x8000) >> 15) as u8;
@@ -1471,6 +1973,15 @@ impl SdiPageBody {
         Ok(sdi_str)
     }
 
+    pub fn get_tablespace_str(&self) -> Result<String> {
+        let sdi_objects = self.read_sdi_objects()?;
+        sdi_objects
+            .iter()
+            .find(|obj| obj.sdi_hdr.data_type == EntryTypes::Tablespace)
+            .map(|obj| obj.sdi_str.clone())
+            .ok_or(Error::msg("未找到表空间的 SDI 对象"))
+    }
+
     pub fn read_sdi_objects(&self) -> Result<Vec<SdiRecord>, Error> {
         let inf = &self.index.infimum;
         let mut rec_addr = (INF_PAGE_BYTE_OFF as i16 + inf.next_rec_offset) as usize;
@@ -1482,10 +1993,31 @@ impl SdiPageBody {
                 rec
             })
             .collect();
-        assert_eq!(rec_addr, SUP_PAGE_BYTE_OFF, "rec_addr 没有到达 supremum");
+        util::check(rec_addr == SUP_PAGE_BYTE_OFF, || {
+            format!("rec_addr 没有到达 supremum: rec_addr={}", rec_addr)
+        });
         Ok(records)
     }
 
+    /// the record header, SDI data header and extern flag of each record,
+    /// without decompressing the data
+    pub fn read_sdi_headers(&self) -> Result<Vec<(RecordHeader, SdiDataHeader, bool)>, Error> {
+        let inf = &self.index.infimum;
+        let mut rec_addr = (INF_PAGE_BYTE_OFF as i16 + inf.next_rec_offset) as usize;
+        let mut headers = vec![];
+        for _ in 0..self.index.idx_hdr.page_n_recs {
+            let rec_hdr = RecordHeader::new(rec_addr - RECORD_HEADER_SIZE, self.buf.clone());
+            let hdr = SdiDataHeader::new(rec_addr, self.buf.clone());
+            let (_, is_extern) = SdiRecord::local_len(rec_addr, &self.buf);
+            rec_addr = rec_hdr.next_addr();
+            headers.push((rec_hdr, hdr, is_extern));
+        }
+        util::check(rec_addr == SUP_PAGE_BYTE_OFF, || {
+            format!("rec_addr 没有到达 supremum: rec_addr={}", rec_addr)
+        });
+        Ok(headers)
+    }
+
     fn parse_sdi_record(&self, rec_addr: usize) -> SdiRecord {
         // Record Header
         let rec_hdr = RecordHeader::new(rec_addr - RECORD_HEADER_SIZE, self.buf.clone());
@@ -1498,6 +2030,150 @@ impl SdiPageBody {
     }
 }
 
+/// Insert Buffer Free List Page, the free pages reserved for the change buffer
+/// tree, linked by the list node at PAGE_BTR_IBUF_FREE_LIST_NODE, see ibuf0ibuf.cc
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct IbufFreeListPageBody {
+    /// page address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (36 bytes) index header, left by the former ibuf tree page
+    pub idx_hdr: IndexHeader,
+
+    /// (12 bytes) free list node, stored in place of PAGE_BTR_SEG_TOP
+    pub free_list_node: FlstNode,
+
+    /// the index page body, only if the page still holds COMPACT records
+    pub index: Option<IndexPageBody>,
+}
+
+impl BasePageBody for IbufFreeListPageBody {
+    fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        let idx_hdr = IndexHeader::new(addr, buf.clone());
+        let has_records = idx_hdr.page_format == PageFormats::COMPACT
+            && &buf[INF_PAGE_BYTE_OFF..INF_PAGE_BYTE_OFF + 8] == b"infimum\0"
+            && &buf[SUP_PAGE_BYTE_OFF..SUP_PAGE_BYTE_OFF + 8] == b"supremum";
+        Self {
+            idx_hdr,
+            free_list_node: FlstNode::new(addr + 46, buf.clone()),
+            index: has_records.then(|| IndexPageBody::new(addr, buf.clone())),
+            buf: buf.clone(),
+            addr,
+        }
+    }
+}
+
+/// R-tree Page, an index page with MBR keys, see gis0rtree.h
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct RtreePageBody {
+    /// page address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (8 bytes) split sequence number, stored in FIL_PAGE_FILE_FLUSH_LSN
+    pub split_seq_num: u64,
+
+    pub index: IndexPageBody, // common Index Page
+
+    /// user records with the MBR keys
+    pub mbr_records: Vec<RtreeRecord>,
+}
+
+impl BasePageBody for RtreePageBody {
+    fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        let index = IndexPageBody::new(addr, buf.clone());
+        let is_leaf = index.idx_hdr.page_level == 0;
+        let mbr_records = index
+            .data_rec_hdrs
+            .iter()
+            .map(|rec_hdr| RtreeRecord::new(rec_hdr.clone(), buf.clone(), is_leaf))
+            .collect();
+        Self {
+            split_seq_num: util::u64_val(&buf, RTREE_SPLIT_SEQ_NUM_OFF),
+            index,
+            mbr_records,
+            buf: buf.clone(),
+            addr,
+        }
+    }
+}
+
+/// R-tree record, the MBR key and the child page number on non-leaf pages
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct RtreeRecord {
+    /// record address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (5 bytes) record header
+    #[derivative(Debug(format_with = "util::fmt_oneline"))]
+    pub rec_hdr: RecordHeader,
+
+    /// (32 bytes) minimum bounding rectangle
+    #[derivative(Debug(format_with = "util::fmt_oneline"))]
+    pub mbr: Mbr,
+
+    /// (4 bytes) child page number, only on non-leaf pages
+    pub child_page: Option<PageNumber>,
+}
+
+impl RtreeRecord {
+    pub fn new(rec_hdr: RecordHeader, buf: Arc<Bytes>, is_leaf: bool) -> Self {
+        let addr = rec_hdr.addr + RECORD_HEADER_SIZE;
+        Self {
+            mbr: Mbr::new(addr, buf.clone()),
+            child_page: if is_leaf {
+                None
+            } else {
+                Some(util::u32_val(&buf, addr + RTREE_MBR_SIZE).into())
+            },
+            rec_hdr,
+            buf: buf.clone(),
+            addr,
+        }
+    }
+}
+
+/// Minimum Bounding Rectangle, the doubles are stored in little-endian, see
+/// mach_double_read
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mbr {
+    pub xmin: f64,
+    pub xmax: f64,
+    pub ymin: f64,
+    pub ymax: f64,
+}
+
+impl Mbr {
+    pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        let f64_le = |off: usize| {
+            f64::from_le_bytes(buf[off..off + 8].try_into().expect("ERR_READ_VALUE_f64"))
+        };
+        Self {
+            xmin: f64_le(addr),
+            xmax: f64_le(addr + 8),
+            ymin: f64_le(addr + 16),
+            ymax: f64_le(addr + 24),
+        }
+    }
+}
+
 /// Transaction System Page, see trx0sys.h
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
@@ -1652,6 +2328,13 @@ pub struct DoubleWriteBufferInfo {
 }
 
 impl DoubleWriteBufferInfo {
+    /// check the magic numbers of both blocks and the stored space id flag
+    pub fn is_valid(&self) -> bool {
+        self.a_magic_number == TRX_SYS_DOUBLEWRITE_MAGIC_N
+            && self.b_magic_number == TRX_SYS_DOUBLEWRITE_MAGIC_N
+            && self.space_id_stored_magic_number == TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED_N
+    }
+
     pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
         let info = Self {
             fseg_hdr: FSegHeader::new(addr, buf.clone()),
@@ -1666,18 +2349,12 @@ impl DoubleWriteBufferInfo {
             addr,
         };
 
-        assert_eq!(
-            info.a_magic_number, TRX_SYS_DOUBLEWRITE_MAGIC_N,
-            "TRX_SYS_DOUBLEWRITE_MAGIC_N 数值错误"
-        );
-        assert_eq!(
-            info.b_magic_number, TRX_SYS_DOUBLEWRITE_MAGIC_N,
-            "TRX_SYS_DOUBLEWRITE_MAGIC_N 数值错误"
-        );
-        assert_eq!(
-            info.space_id_stored_magic_number, TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED_N,
-            "TRX_SYS_DOUBLEWRITE_SPACE_ID_STORED_N 数值错误"
-        );
+        if !info.is_valid() {
+            warn!(
+                "doublewrite 魔数错误: a_magic={}, b_magic={}, space_id_stored_magic={}",
+                info.a_magic_number, info.b_magic_number, info.space_id_stored_magic_number
+            );
+        }
 
         info
     }
@@ -1744,6 +2421,77 @@ impl RSegArrayPageBody {
     pub const RSEG_ARRAY_BASE_VERSION: u32 = 0x52534547;
 }
 
+/// Data Dictionary Header Page, see dict0boot.h
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct DictHeaderPageBody {
+    /// page address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (8 bytes) the latest assigned row id, DICT_HDR_ROW_ID
+    pub row_id: u64,
+
+    /// (8 bytes) the latest assigned table id, DICT_HDR_TABLE_ID
+    pub table_id: u64,
+
+    /// (8 bytes) the latest assigned index id, DICT_HDR_INDEX_ID
+    pub index_id: u64,
+
+    /// (4 bytes) the latest assigned space id, DICT_HDR_MAX_SPACE_ID
+    pub max_space_id: u32,
+
+    /// (4 bytes) obsolete, always DICT_HDR_FIRST_ID, DICT_HDR_MIX_ID_LOW
+    pub mix_id_low: u32,
+
+    /// (4 bytes) root of SYS_TABLES clust index, DICT_HDR_TABLES
+    #[derivative(Debug(format_with = "util::fmt_enum_3"))]
+    pub tables_root: PageNumber,
+
+    /// (4 bytes) root of SYS_TABLE_IDS sec index, DICT_HDR_TABLE_IDS
+    #[derivative(Debug(format_with = "util::fmt_enum_3"))]
+    pub table_ids_root: PageNumber,
+
+    /// (4 bytes) root of SYS_COLUMNS clust index, DICT_HDR_COLUMNS
+    #[derivative(Debug(format_with = "util::fmt_enum_3"))]
+    pub columns_root: PageNumber,
+
+    /// (4 bytes) root of SYS_INDEXES clust index, DICT_HDR_INDEXES
+    #[derivative(Debug(format_with = "util::fmt_enum_3"))]
+    pub indexes_root: PageNumber,
+
+    /// (4 bytes) root of SYS_FIELDS clust index, DICT_HDR_FIELDS
+    #[derivative(Debug(format_with = "util::fmt_enum_3"))]
+    pub fields_root: PageNumber,
+
+    /// (10 bytes) segment header for the tablespace segment, DICT_HDR_FSEG_HEADER
+    pub fseg_hdr: FSegHeader,
+}
+
+impl BasePageBody for DictHeaderPageBody {
+    fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        Self {
+            row_id: util::u64_val(&buf, addr),
+            table_id: util::u64_val(&buf, addr + 8),
+            index_id: util::u64_val(&buf, addr + 16),
+            max_space_id: util::u32_val(&buf, addr + 24),
+            mix_id_low: util::u32_val(&buf, addr + 28),
+            tables_root: util::u32_val(&buf, addr + 32).into(),
+            table_ids_root: util::u32_val(&buf, addr + 36).into(),
+            columns_root: util::u32_val(&buf, addr + 40).into(),
+            indexes_root: util::u32_val(&buf, addr + 44).into(),
+            fields_root: util::u32_val(&buf, addr + 48).into(),
+            fseg_hdr: FSegHeader::new(addr + 56, buf.clone()),
+            buf: buf.clone(),
+            addr,
+        }
+    }
+}
+
 /// Rollback Segment Header Page, see trx0rseg.h
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
@@ -2006,6 +2754,260 @@ impl UndoSegmentHeader {
     }
 }
 
+/// LOB First Page, see lob0first.h
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct LobFirstPageBody {
+    /// page address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (1 byte) LOB format version
+    pub version: u8,
+
+    /// (1 byte) LOB flags
+    #[derivative(Debug(format_with = "util::fmt_bin8"))]
+    pub flags: u8,
+
+    /// (4 bytes) LOB version
+    pub lob_version: u32,
+
+    /// (6 bytes) transaction id that last modified the LOB
+    #[derivative(Debug(format_with = "util::fmt_hex48"))]
+    pub last_trx_id: u64,
+
+    /// (4 bytes) undo number of the last modification
+    pub last_undo_no: u32,
+
+    /// (4 bytes) length of the data stored in this page
+    pub data_len: u32,
+
+    /// (6 bytes) transaction id that created the LOB
+    #[derivative(Debug(format_with = "util::fmt_hex48"))]
+    pub trx_id: u64,
+
+    /// (16 bytes) list of LOB index entries
+    pub index_list: FlstBaseNode,
+
+    /// (16 bytes) list of free LOB index entries
+    pub index_free_nodes: FlstBaseNode,
+
+    /// (600 bytes) LOB index entry array
+    pub index_entries: Vec<LobIndexEntry>,
+
+    /// LOB data in this page
+    #[derivative(Debug = "ignore")]
+    pub data: Bytes,
+}
+
+impl BasePageBody for LobFirstPageBody {
+    fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        let data_len = util::u32_val(&buf, addr + 16);
+        let beg = addr + LOB_FIRST_DATA_BEGIN;
+        let end = (beg + data_len as usize).min(buf.len() - FIL_TRAILER_SIZE);
+        if beg + data_len as usize > end {
+            warn!("LOB 首页数据长度越界: data_len={}", data_len);
+        }
+
+        Self {
+            version: util::u8_val(&buf, addr),
+            flags: util::u8_val(&buf, addr + 1),
+            lob_version: util::u32_val(&buf, addr + 2),
+            last_trx_id: util::u48_val(&buf, addr + 6),
+            last_undo_no: util::u32_val(&buf, addr + 12),
+            data_len,
+            trx_id: util::u48_val(&buf, addr + 20),
+            index_list: FlstBaseNode::new(addr + 26, buf.clone()),
+            index_free_nodes: FlstBaseNode::new(addr + 42, buf.clone()),
+            index_entries: (0..LOB_FIRST_INDEX_ENTRY_COUNT)
+                .map(|i| {
+                    LobIndexEntry::new(
+                        addr + LOB_FIRST_INDEX_BEGIN + i * LOB_INDEX_ENTRY_SIZE,
+                        buf.clone(),
+                    )
+                })
+                .collect(),
+            data: buf.slice(beg..end),
+            buf: buf.clone(),
+            addr,
+        }
+    }
+}
+
+impl LobFirstPageBody {
+    /// index entries linked in the index list, which reside in this page
+    pub fn used_entries(&self) -> Vec<&LobIndexEntry> {
+        let mut entries = Vec::new();
+        let mut next = &self.index_list.first;
+        while let PageNumber::Page(_) = next.page_no {
+            match self
+                .index_entries
+                .iter()
+                .find(|ent| ent.addr == next.boffset as usize)
+            {
+                Some(ent) if entries.len() < self.index_entries.len() => {
+                    entries.push(ent);
+                    next = &ent.next;
+                }
+                _ => break,
+            }
+        }
+        entries
+    }
+}
+
+/// LOB Index Page, see lob0index.h
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct LobIndexPageBody {
+    /// page address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (1 byte) LOB format version
+    pub version: u8,
+
+    /// LOB index entry array
+    pub index_entries: Vec<LobIndexEntry>,
+}
+
+impl BasePageBody for LobIndexPageBody {
+    fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        let beg = addr + LOB_INDEX_PAGE_BEGIN;
+        let cnt = (buf.len() - FIL_TRAILER_SIZE - beg) / LOB_INDEX_ENTRY_SIZE;
+        Self {
+            version: util::u8_val(&buf, addr),
+            index_entries: (0..cnt)
+                .map(|i| LobIndexEntry::new(beg + i * LOB_INDEX_ENTRY_SIZE, buf.clone()))
+                .collect(),
+            buf: buf.clone(),
+            addr,
+        }
+    }
+}
+
+/// LOB Data Page, see lob0pages.h
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct LobDataPageBody {
+    /// page address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (1 byte) LOB format version
+    pub version: u8,
+
+    /// (4 bytes) length of the data stored in this page
+    pub data_len: u32,
+
+    /// (6 bytes) transaction id that created the data page
+    #[derivative(Debug(format_with = "util::fmt_hex48"))]
+    pub trx_id: u64,
+
+    /// LOB data in this page
+    #[derivative(Debug = "ignore")]
+    pub data: Bytes,
+}
+
+impl BasePageBody for LobDataPageBody {
+    fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        let data_len = util::u32_val(&buf, addr + 1);
+        let beg = addr + LOB_DATA_PAGE_BEGIN;
+        let end = (beg + data_len as usize).min(buf.len() - FIL_TRAILER_SIZE);
+        if beg + data_len as usize > end {
+            warn!("LOB 数据页长度越界: data_len={}", data_len);
+        }
+
+        Self {
+            version: util::u8_val(&buf, addr),
+            data_len,
+            trx_id: util::u48_val(&buf, addr + 5),
+            data: buf.slice(beg..end),
+            buf: buf.clone(),
+            addr,
+        }
+    }
+}
+
+/// LOB Index Entry, see lob0index.h
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct LobIndexEntry {
+    /// entry address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (6 bytes) previous index entry
+    #[derivative(Debug(format_with = "util::fmt_oneline"))]
+    pub prev: FilAddr,
+
+    /// (6 bytes) next index entry
+    #[derivative(Debug(format_with = "util::fmt_oneline"))]
+    pub next: FilAddr,
+
+    /// (16 bytes) list of older versions of this entry
+    pub versions: FlstBaseNode,
+
+    /// (6 bytes) transaction id that created this entry
+    #[derivative(Debug(format_with = "util::fmt_hex48"))]
+    pub trx_id: u64,
+
+    /// (6 bytes) transaction id that modified this entry
+    #[derivative(Debug(format_with = "util::fmt_hex48"))]
+    pub trx_id_modifier: u64,
+
+    /// (4 bytes) undo number of the creator
+    pub undo_no: u32,
+
+    /// (4 bytes) undo number of the modifier
+    pub undo_no_modifier: u32,
+
+    /// (4 bytes) page number of the LOB data page
+    #[derivative(Debug(format_with = "util::fmt_enum_3"))]
+    pub page_no: PageNumber,
+
+    /// (4 bytes) length of the data in the LOB data page
+    pub data_len: u32,
+
+    /// (4 bytes) LOB version
+    pub lob_version: u32,
+}
+
+impl LobIndexEntry {
+    pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        Self {
+            prev: FilAddr::new(addr, buf.clone()),
+            next: FilAddr::new(addr + 6, buf.clone()),
+            versions: FlstBaseNode::new(addr + 12, buf.clone()),
+            trx_id: util::u48_val(&buf, addr + 28),
+            trx_id_modifier: util::u48_val(&buf, addr + 34),
+            undo_no: util::u32_val(&buf, addr + 40),
+            undo_no_modifier: util::u32_val(&buf, addr + 44),
+            page_no: util::u32_val(&buf, addr + 48).into(),
+            data_len: util::u32_val(&buf, addr + 52),
+            lob_version: util::u32_val(&buf, addr + 56),
+            buf: buf.clone(),
+            addr,
+        }
+    }
+}
+
 #[cfg(test)]
 mod page_tests {
 
@@ -2018,4 +3020,64 @@ mod page_tests {
         let page = PageNumber::Page(10);
         info!("page={:?}", page);
     }
+
+    #[test]
+    fn index_insert_pattern() {
+        util::init_unit_test();
+        let buf = Arc::new(Bytes::from(std::fs::read("data/departments.ibd").unwrap()));
+        let page = buf.slice(UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5);
+        let idx_hdr = IndexHeader::new(FIL_HEADER_SIZE, Arc::new(page));
+        assert_eq!(idx_hdr.page_direction, PageDirections::PAGE_RIGHT);
+        assert_eq!(idx_hdr.page_direction.to_string(), "PAGE_RIGHT");
+        assert_eq!(
+            idx_hdr.insert_pattern(),
+            "8 sequential right inserts, append-only"
+        );
+
+        let page = buf.slice(UNIV_PAGE_SIZE * 5..UNIV_PAGE_SIZE * 6);
+        let idx_hdr = IndexHeader::new(FIL_HEADER_SIZE, Arc::new(page));
+        assert_eq!(idx_hdr.page_direction, PageDirections::PAGE_NO_DIRECTION);
+        assert_eq!(idx_hdr.insert_pattern(), "random inserts");
+    }
+
+    #[test]
+    fn parse_ibuf_free_list_page() {
+        util::init_unit_test();
+        let mut page = vec![0u8; UNIV_PAGE_SIZE];
+        page[24..26].copy_from_slice(&(PageTypes::IBUF_FREE_LIST as u16).to_be_bytes());
+        let node = FIL_HEADER_SIZE + 46;
+        page[node..node + 4].copy_from_slice(&PAGE_NONE.to_be_bytes());
+        page[node + 6..node + 10].copy_from_slice(&9u32.to_be_bytes());
+        let page: BasePage<IbufFreeListPageBody> = BasePage::new(0, Arc::new(Bytes::from(page)));
+        let body = &page.page_body;
+        assert_eq!(body.free_list_node.prev.page_no, PageNumber::None);
+        assert_eq!(body.free_list_node.next.page_no, PageNumber::Page(9));
+        assert!(body.index.is_none());
+
+        // a former ibuf tree page keeps its records
+        let buf = std::fs::read("data/departments.ibd").unwrap();
+        let page = Bytes::from(buf[UNIV_PAGE_SIZE * 4..UNIV_PAGE_SIZE * 5].to_vec());
+        let body = IbufFreeListPageBody::new(FIL_HEADER_SIZE, Arc::new(page));
+        let index = body.index.expect("index body");
+        assert_eq!(index.data_rec_hdrs.len(), body.idx_hdr.page_n_recs as usize);
+    }
+
+    #[test]
+    fn space_id_roundtrip() {
+        util::init_unit_test();
+        for val in [
+            0,
+            1,
+            3,
+            42,
+            UNDO_SPACE_ID_MAX,
+            UNDO_SPACE_ID_MIN,
+            TEMP_SPACE_ID_MAX,
+            DICT_SPACE_ID,
+            INNODB_TEMP_SPACE_ID,
+            INVALID_SPACE_ID,
+        ] {
+            assert_eq!(u32::from(&SpaceId::from(val)), val);
+        }
+    }
 }
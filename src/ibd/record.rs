@@ -1,15 +1,17 @@
 use std::{
     collections::{BTreeMap, HashSet},
+    fmt,
     sync::Arc,
 };
 
 use anyhow::Error;
 use bytes::Bytes;
-use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
 use colored::Colorize;
 use derivative::Derivative;
 use log::{debug, info};
 use num_enum::FromPrimitive;
+use serde_json::{json, Map, Value};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use strum::{Display, EnumString};
 
@@ -19,7 +21,7 @@ use crate::{
         record::RecordStatus::NODE_PTR,
         undo::RollPtr,
     },
-    meta::def::{HiddenTypes, TableDef},
+    meta::def::{ColumnTypes, HiddenTypes, TableDef},
     util,
     util::align8,
 };
@@ -133,8 +135,10 @@ impl RecordHeader {
         }
     }
 
+    /// next record address, a negative address turns into a huge value which
+    /// is rejected by the bounds check of the caller
     pub fn next_addr(&self) -> usize {
-        ((self.addr as i16) + self.next_rec_offset) as usize + RECORD_HEADER_SIZE
+        (self.addr as isize + self.next_rec_offset as isize + RECORD_HEADER_SIZE as isize) as usize
     }
 
     /// Info bit denoting the predefined minimum record: this bit is set if and
@@ -169,6 +173,25 @@ impl RecordHeader {
     pub fn is_instant(&self) -> bool {
         (self.info_byte & Self::REC_INFO_INSTANT_FLAG) > 0
     }
+
+    /// row version in the byte before the record header if VERSION flag is on
+    pub fn row_version(&self) -> Option<u8> {
+        if self.is_version() {
+            Some(self.buf[self.addr - 1])
+        } else {
+            None
+        }
+    }
+
+    /// number of fields in the byte before the record header if INSTANT flag
+    /// is on
+    pub fn n_instant_col(&self) -> Option<u8> {
+        if self.is_instant() {
+            Some(self.buf[self.addr - 1])
+        } else {
+            None
+        }
+    }
 }
 
 /// Field metadata
@@ -234,6 +257,9 @@ pub struct RowInfo {
     /// record status
     pub rec_status: RecordStatus,
 
+    /// level of the page, non-leaf page only contains node pointer records
+    pub page_level: u16,
+
     /// table definition
     #[derivative(Debug = "ignore")]
     pub table_def: Arc<TableDef>,
@@ -243,30 +269,22 @@ pub struct RowInfo {
 }
 
 impl RowInfo {
-    pub fn new(rec_hdr: &RecordHeader, tabdef: Arc<TableDef>, index_pos: usize) -> Self {
+    pub fn new(
+        rec_hdr: &RecordHeader,
+        tabdef: Arc<TableDef>,
+        index_pos: usize,
+        page_level: u16,
+    ) -> Self {
         let buf = rec_hdr.buf.clone();
 
-        // Handle the row version
-        let row_ver = if rec_hdr.is_version() {
-            buf[rec_hdr.addr - 1]
-        } else {
-            0
-        };
-
-        // Handle the row version
-        let n_ins_col = if rec_hdr.is_instant() {
-            buf[rec_hdr.addr - 1]
-        } else {
-            0
-        };
-
         Self {
             table_def: tabdef.clone(),
             index_pos,
             instant_flag: rec_hdr.is_instant(),
-            n_instant_col: n_ins_col,
-            row_version: row_ver,
+            n_instant_col: rec_hdr.n_instant_col().unwrap_or(0),
+            row_version: rec_hdr.row_version().unwrap_or(0),
             rec_status: rec_hdr.rec_status.clone(),
+            page_level,
             buf: buf.clone(),
             addr: rec_hdr.addr,
         }
@@ -294,7 +312,13 @@ impl RowInfo {
     }
 
     pub fn resolve_metadata(&self) -> Result<Vec<FieldMeta>, Error> {
-        if self.rec_status == NODE_PTR {
+        Ok(self.resolve_layout()?.0)
+    }
+
+    /// resolve the field metadata and the address where the extra bytes begin,
+    /// the extra bytes are the variable field lengths, null bitmap and header
+    pub fn resolve_layout(&self) -> Result<(Vec<FieldMeta>, usize), Error> {
+        if self.page_level > 0 {
             self.resolve_node_ptr_metadata()
         } else {
             self.resolve_ordinary_metadata()
@@ -302,7 +326,7 @@ impl RowInfo {
     }
 
     /// resolve ordinary record
-    pub fn resolve_ordinary_metadata(&self) -> Result<Vec<FieldMeta>, Error> {
+    pub fn resolve_ordinary_metadata(&self) -> Result<(Vec<FieldMeta>, usize), Error> {
         let row_ver = self.row_version as u32;
         let eles = &self.table_def.clone().idx_defs[self.index_pos].elements;
         let cols = &self.table_def.clone().col_defs;
@@ -434,36 +458,54 @@ impl RowInfo {
             fldaddr += vlen;
         }
 
-        assert_eq!(nilfld_nth, n_nilfld, "所有可空的字段都应访问到");
+        util::check(nilfld_nth == n_nilfld, || {
+            format!(
+                "所有可空的字段都应访问到: nilfld_nth={}, n_nilfld={}",
+                nilfld_nth, n_nilfld
+            )
+        });
         for (i, meta) in row_meta_list.iter().enumerate() {
             debug!("meta[{}]={:?}, {}", i, meta, &cols[meta.opx].col_name);
         }
 
-        Ok(row_meta_list)
+        Ok((row_meta_list, varptr))
     }
 
     /// resolve node_ptr
-    pub fn resolve_node_ptr_metadata(&self) -> Result<Vec<FieldMeta>, Error> {
+    pub fn resolve_node_ptr_metadata(&self) -> Result<(Vec<FieldMeta>, usize), Error> {
         let eles = &self.table_def.clone().idx_defs[self.index_pos].elements;
         let cols = &self.table_def.clone().col_defs;
 
-        // resolve physical layout
-        let phy_layout = eles
+        // the key prefix: all elements of secondary index, or the primary key
+        // of clustered index which stops at DB_TRX_ID
+        let key_cols = eles
             .iter()
-            .enumerate()
-            .map(|(phy_pos, ele)| (phy_pos, (ele.column_opx, true, true)))
-            .collect::<BTreeMap<usize, _>>();
+            .map(|ele| ele.column_opx)
+            .take_while(|opx| {
+                let col = &cols[*opx];
+                col.hidden != HiddenTypes::HT_HIDDEN_SE || col.col_name == "DB_ROW_ID"
+            })
+            .collect::<Vec<_>>();
+
+        // the null bitmap covers the nullable fields of whole index, see
+        // rec_init_offsets() in rem0rec.cc
+        let n_nilfld = eles.iter().filter(|ele| cols[ele.column_opx].isnil).count();
+        let niladdr = self.addr;
+        let varaddr = niladdr - align8(n_nilfld);
 
         let mut row_meta_list = vec![];
-        let mut varptr = self.addr;
+        let mut nilfld_nth = 0;
+        let mut varptr = varaddr;
         let mut fldaddr = self.addr + RECORD_HEADER_SIZE;
-        for (_, (col_pos, phy_exist, log_exist)) in phy_layout {
+        for col_pos in key_cols {
             let col = &cols[col_pos];
-            if col.hidden == HiddenTypes::HT_HIDDEN_SE && col.col_name != "DB_ROW_ID" {
-                break;
+            let mut null = false;
+            if col.isnil {
+                null = self.is_null(niladdr, nilfld_nth);
+                nilfld_nth += 1;
             }
             let mut vlen = 0;
-            if phy_exist {
+            if !null {
                 if col.isvar {
                     let (nbyte, len) = self.varfld_len(varptr, col.data_len);
                     varptr -= nbyte;
@@ -475,10 +517,10 @@ impl RowInfo {
             row_meta_list.push(FieldMeta {
                 addr: fldaddr,
                 opx: col_pos,
-                isnull: false,
+                isnull: null,
                 length: vlen,
-                phy_exist,
-                log_exist,
+                phy_exist: true,
+                log_exist: true,
             });
             fldaddr += vlen;
         }
@@ -499,7 +541,7 @@ impl RowInfo {
             debug!("meta[{}]={:?}, {}", i, meta, &cols[meta.opx].col_name);
         }
 
-        Ok(row_meta_list)
+        Ok((row_meta_list, varptr))
     }
 
     fn is_null(&self, niladdr: usize, nilfld_nth: usize) -> bool {
@@ -677,6 +719,37 @@ impl Record {
             total_size: va_size + na_size + rv_size + RECORD_HEADER_SIZE + pd_size,
         }
     }
+
+    /// the byte range of each field in the page, in the physical order
+    pub fn field_layout(&self) -> Vec<FieldLayout> {
+        let cols = &self.row_info.table_def.clone().col_defs;
+        self.row_data
+            .meta_list
+            .iter()
+            .map(|m| FieldLayout {
+                col_name: if m.opx == PAGE_NONE as usize {
+                    "CHILD_PAGE".into()
+                } else {
+                    cols[m.opx].col_name.clone()
+                },
+                start: m.addr,
+                end: if m.phy_exist {
+                    m.addr + m.length
+                } else {
+                    m.addr
+                },
+                isnull: m.isnull,
+                phy_exist: m.phy_exist,
+            })
+            .collect()
+    }
+
+    /// the raw record body, from the record origin forward by the physical
+    /// data size of the layout
+    pub fn raw_body(&self) -> Bytes {
+        let size = self.calc_layout().phy_data_size;
+        self.buf.slice(self.addr..self.addr + size)
+    }
 }
 
 /// Record Layout
@@ -698,6 +771,28 @@ pub struct RecordLayout {
     pub total_size: usize,
 }
 
+/// Field Layout, the byte range [start, end) of a field in the page
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct FieldLayout {
+    /// column name
+    pub col_name: String,
+
+    /// start page offset
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub start: usize,
+
+    /// end page offset, exclusive
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub end: usize,
+
+    /// is null value, no data bytes
+    pub isnull: bool,
+
+    /// physical exists, otherwise the instant default value is used
+    pub phy_exist: bool,
+}
+
 /// row data value
 #[derive(Clone, Derivative, Eq, PartialEq)]
 #[derivative(Debug)]
@@ -708,19 +803,283 @@ pub enum DataValue {
     PageNo(u32),
     I32(i32),
     I64(i64),
+    U32(u32),
+    U64(u64),
     Str(String),
     Enum(u16),
     Date(NaiveDate),
     DateTime(NaiveDateTime),
-    Timestamp(DateTime<Local>),
+    Timestamp(#[derivative(Debug(format_with = "util::fmt_timestamp"))] DateTime<FixedOffset>),
+    Bytes(#[derivative(Debug(format_with = "util::fmt_bytes_hex"))] Bytes),
     Unknown(Bytes),
     Null,
 }
 
+impl DataValue {
+    /// apply the fixed timezone offset on TIMESTAMP value, other values are
+    /// returned as is
+    pub fn with_tz(&self, tz: &FixedOffset) -> DataValue {
+        match self {
+            DataValue::Timestamp(ts) => DataValue::Timestamp(ts.with_timezone(tz)),
+            _ => self.clone(),
+        }
+    }
+
+    /// render the bare value, the unknown bytes are rendered as raw hex if
+    /// raw_bytes is set
+    pub fn render(&self, raw_bytes: bool) -> String {
+        match self {
+            DataValue::Unknown(b) if raw_bytes => util::raw_bytes_str(b),
+            _ => self.to_string(),
+        }
+    }
+
+    /// SQL literal of the value, strings and dates are quoted
+    pub fn to_sql_literal(&self) -> String {
+        match self {
+            DataValue::RowId(v) | DataValue::TrxId(v) => v.to_string(),
+            DataValue::RbPtr(p) => p.value.to_string(),
+            DataValue::PageNo(v) => v.to_string(),
+            DataValue::I32(v) => v.to_string(),
+            DataValue::I64(v) => v.to_string(),
+            DataValue::U32(v) => v.to_string(),
+            DataValue::U64(v) => v.to_string(),
+            DataValue::Str(v) => quote_sql_str(v),
+            DataValue::Enum(v) => v.to_string(),
+            DataValue::Date(v) => quote_sql_str(&v.format("%Y-%m-%d").to_string()),
+            DataValue::DateTime(v) => quote_sql_str(&v.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+            DataValue::Timestamp(v) => quote_sql_str(&v.format("%Y-%m-%d %H:%M:%S").to_string()),
+            DataValue::Bytes(b) | DataValue::Unknown(b) => format!("X'{}'", hex::encode(b)),
+            DataValue::Null => "NULL".to_string(),
+        }
+    }
+
+    /// JSON value, dates are formatted as strings and unknown bytes as hex
+    pub fn to_json_value(&self) -> Value {
+        match self {
+            DataValue::RowId(v) | DataValue::TrxId(v) => json!(v),
+            DataValue::RbPtr(p) => json!(p.value),
+            DataValue::PageNo(v) => json!(v),
+            DataValue::I32(v) => json!(v),
+            DataValue::I64(v) => json!(v),
+            DataValue::U32(v) => json!(v),
+            DataValue::U64(v) => json!(v),
+            DataValue::Str(v) => json!(v),
+            DataValue::Enum(v) => json!(v),
+            DataValue::Date(v) => json!(v.format("%Y-%m-%d").to_string()),
+            DataValue::DateTime(v) => json!(v.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+            DataValue::Timestamp(v) => json!(v.to_rfc3339()),
+            DataValue::Bytes(b) | DataValue::Unknown(b) => json!(hex::encode(b)),
+            DataValue::Null => Value::Null,
+        }
+    }
+}
+
+impl fmt::Display for DataValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataValue::RowId(v) | DataValue::TrxId(v) => write!(f, "{}", v),
+            DataValue::RbPtr(p) => write!(f, "0x{:014x}", p.value),
+            DataValue::PageNo(v) => write!(f, "{}", v),
+            DataValue::I32(v) => write!(f, "{}", v),
+            DataValue::I64(v) => write!(f, "{}", v),
+            DataValue::U32(v) => write!(f, "{}", v),
+            DataValue::U64(v) => write!(f, "{}", v),
+            DataValue::Str(v) => write!(f, "{}", v),
+            DataValue::Enum(v) => write!(f, "{}", v),
+            DataValue::Date(v) => write!(f, "{}", v.format("%Y-%m-%d")),
+            DataValue::DateTime(v) => write!(f, "{}", v.format("%Y-%m-%d %H:%M:%S%.f")),
+            DataValue::Timestamp(v) => util::fmt_timestamp(v, f),
+            DataValue::Bytes(b) | DataValue::Unknown(b) => write!(f, "0x{}", hex::encode(b)),
+            DataValue::Null => write!(f, "NULL"),
+        }
+    }
+}
+
+/// quote string literal, escape the embedded quotes and backslashes
+fn quote_sql_str(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+/// node pointer record in the non-leaf page, the key prefix plus a 4-byte
+/// child page number
+#[derive(Debug, Clone)]
+pub struct NodePtr {
+    /// the key prefix, the minimum key of child page
+    pub key: Vec<(String, DataValue)>,
+
+    /// (4 bytes) child page number
+    pub child_page: usize,
+}
+
 #[derive(Debug)]
 pub struct ResultSet {
-    pub garbage: bool,
+    /// whether each record is read from the garbage list
+    pub garbage: Vec<bool>,
     pub tabdef: Arc<TableDef>,
+    pub idx_name: String,
     pub records: Vec<Record>,
     pub tuples: Vec<Vec<(String, DataValue)>>,
 }
+
+impl ResultSet {
+    /// keep the named columns in each tuple, in the order of given columns
+    pub fn project(&mut self, columns: &[String]) -> Result<(), Error> {
+        for name in columns {
+            if !self.tabdef.col_defs.iter().any(|c| &c.col_name == name) {
+                return Err(Error::msg(format!("未找到列: {}", name)));
+            }
+        }
+
+        for tuple in self.tuples.iter_mut() {
+            *tuple = columns
+                .iter()
+                .filter_map(|name| tuple.iter().find(|ent| &ent.0 == name).cloned())
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// right-trim the space padding of CHAR columns, like the SELECT shows,
+    /// VARCHAR columns are untouched
+    pub fn trim_char_columns(&mut self) {
+        let char_cols = self
+            .tabdef
+            .col_defs
+            .iter()
+            .filter(|c| c.dd_type == ColumnTypes::STRING)
+            .map(|c| c.col_name.as_str())
+            .collect::<HashSet<_>>();
+        for tuple in self.tuples.iter_mut() {
+            for ent in tuple.iter_mut() {
+                if let DataValue::Str(s) = &mut ent.1 {
+                    if char_cols.contains(ent.0.as_str()) {
+                        s.truncate(s.trim_end_matches(' ').len());
+                    }
+                }
+            }
+        }
+    }
+
+    /// the node pointers of a non-leaf page, error if any record is not a
+    /// node pointer record
+    pub fn node_ptrs(&self) -> Result<Vec<NodePtr>, Error> {
+        self.records
+            .iter()
+            .zip(self.tuples.iter())
+            .map(|(rec, tuple)| {
+                if rec.rec_hdr.rec_status != NODE_PTR {
+                    return Err(Error::msg(format!(
+                        "不是节点指针记录: addr={}, rec_status={:?}",
+                        rec.addr, rec.rec_hdr.rec_status
+                    )));
+                }
+                match tuple.split_last() {
+                    Some(((name, DataValue::PageNo(page_no)), key)) if name == "NODE_PTR" => {
+                        Ok(NodePtr {
+                            key: key.to_vec(),
+                            child_page: *page_no as usize,
+                        })
+                    }
+                    _ => Err(Error::msg(format!(
+                        "节点指针记录缺少子页号: addr={}",
+                        rec.addr
+                    ))),
+                }
+            })
+            .collect()
+    }
+
+    /// keep the records modified by the given transaction, matched by the
+    /// DB_TRX_ID hidden column
+    pub fn filter_trx_id(&mut self, trx_id: u64) {
+        let matched: Vec<bool> = self
+            .tuples
+            .iter()
+            .map(|tuple| {
+                tuple
+                    .iter()
+                    .any(|ent| matches!(ent.1, DataValue::TrxId(v) if v == trx_id))
+            })
+            .collect();
+        let mut it = matched.iter();
+        self.tuples.retain(|_| *it.next().unwrap());
+        let mut it = matched.iter();
+        self.records.retain(|_| *it.next().unwrap());
+        let mut it = matched.iter();
+        self.garbage.retain(|_| *it.next().unwrap());
+    }
+
+    /// generate INSERT statement for each tuple, the hidden columns are skipped
+    pub fn to_sql_inserts(&self, table_name: &str) -> Vec<String> {
+        let visible = |name: &str| {
+            self.tabdef
+                .col_defs
+                .iter()
+                .find(|c| c.col_name == name)
+                .map(|c| c.hidden != HiddenTypes::HT_HIDDEN_SE)
+                .unwrap_or(false)
+        };
+
+        self.tuples
+            .iter()
+            .map(|tuple| {
+                let (cols, vals): (Vec<_>, Vec<_>) = tuple
+                    .iter()
+                    .filter(|ent| visible(&ent.0))
+                    .map(|ent| (format!("`{}`", ent.0), ent.1.to_sql_literal()))
+                    .unzip();
+                format!(
+                    "INSERT INTO `{}` ({}) VALUES ({});",
+                    table_name,
+                    cols.join(", "),
+                    vals.join(", ")
+                )
+            })
+            .collect()
+    }
+
+    /// CSV lines per RFC 4180, the header line comes first if `header` is set.
+    /// NULL is rendered as the unquoted `null_as` token, an empty string or a
+    /// value equal to the token is quoted to keep it apart from NULL
+    pub fn to_csv(&self, delim: char, header: bool, null_as: &str) -> Vec<String> {
+        let quote = |s: String| {
+            if s.is_empty() || s == null_as || s.contains([delim, '"', '\n', '\r']) {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s
+            }
+        };
+        let sep = delim.to_string();
+
+        let mut lines = vec![];
+        if header {
+            if let Some(tuple) = self.tuples.first() {
+                let names: Vec<_> = tuple.iter().map(|ent| quote(ent.0.clone())).collect();
+                lines.push(names.join(&sep));
+            }
+        }
+        for tuple in &self.tuples {
+            let vals: Vec<_> = tuple
+                .iter()
+                .map(|ent| match &ent.1 {
+                    DataValue::Null => null_as.to_string(),
+                    val => quote(val.to_string()),
+                })
+                .collect();
+            lines.push(vals.join(&sep));
+        }
+        lines
+    }
+
+    /// one JSON object per tuple, generated lazily for streaming output
+    pub fn to_json_lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.tuples.iter().map(|tuple| {
+            let obj: Map<String, Value> = tuple
+                .iter()
+                .map(|ent| (ent.0.clone(), ent.1.to_json_value()))
+                .collect();
+            Value::Object(obj).to_string()
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 
 use bytes::Bytes;
 use derivative::Derivative;
@@ -7,9 +7,12 @@ use num_enum::FromPrimitive;
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use strum::{Display, EnumString};
 
-use super::page::{PageNumber, SpaceId};
+use super::page::{PageNumber, PageTypes, SpaceId};
 use crate::{
-    ibd::{record::DATA_ROLL_PTR_LEN, undo::RollPtr},
+    ibd::{
+        record::DATA_ROLL_PTR_LEN,
+        undo::{RollPtr, UndoRecord},
+    },
     util,
 };
 
@@ -21,6 +24,12 @@ use crate::{
 /// failure in the log.
 pub const OS_FILE_LOG_BLOCK_SIZE: usize = 512;
 
+/// Size of the log block header in bytes.
+pub const LOG_BLOCK_HDR_SIZE: usize = 12;
+
+/// Size of the log block trailer (checksum) in bytes.
+pub const LOG_BLOCK_TRL_SIZE: usize = 4;
+
 /// Maximum allowed block's number (stored in hdr_no) increased by 1.
 pub const LOG_BLOCK_MAX_NO: usize = 0x3FFFFFFF + 1;
 
@@ -91,10 +100,94 @@ impl LogFile {
             addr,
         }
     }
+
+    /// iterate all log records in LSN order, the records may span blocks
+    pub fn records(&self) -> LogRecordIter {
+        let mut body = vec![];
+        let mut group_offsets = vec![];
+        for blk in &self.log_block_list {
+            if let Blocks::Block(block) = blk {
+                let data_len = block.data_len as usize;
+                if data_len <= LOG_BLOCK_HDR_SIZE {
+                    break;
+                }
+                let first_rec = block.first_rec_offset as usize;
+                if first_rec >= LOG_BLOCK_HDR_SIZE && first_rec < data_len {
+                    group_offsets.push(body.len() + first_rec - LOG_BLOCK_HDR_SIZE);
+                }
+                let end = data_len.min(OS_FILE_LOG_BLOCK_SIZE - LOG_BLOCK_TRL_SIZE);
+                body.extend_from_slice(
+                    &block.buf[block.addr + LOG_BLOCK_HDR_SIZE..block.addr + end],
+                );
+                if data_len < OS_FILE_LOG_BLOCK_SIZE {
+                    // the last written block
+                    break;
+                }
+            }
+        }
+
+        let body_len = body.len();
+        // padding zeros, avoid the broken tail record reading out of bounds
+        body.resize(body_len + OS_FILE_LOG_BLOCK_SIZE, 0);
+
+        LogRecordIter {
+            ptr: group_offsets.first().cloned().unwrap_or(body_len),
+            body: Arc::new(Bytes::from(body)),
+            body_len,
+            group_offsets,
+        }
+    }
+}
+
+/// log record iterator, the log block header and trailer are stripped, so the
+/// record address is the offset in the concatenated block bodies
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct LogRecordIter {
+    /// concatenated block bodies
+    #[derivative(Debug = "ignore")]
+    pub body: Arc<Bytes>,
+
+    /// length of valid data in body
+    pub body_len: usize,
+
+    /// offsets of the first record group of each block
+    #[derivative(Debug = "ignore")]
+    pub group_offsets: Vec<usize>,
+
+    /// current record offset
+    pub ptr: usize,
+}
+
+impl Iterator for LogRecordIter {
+    type Item = LogRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ptr >= self.body_len {
+            return None;
+        }
+
+        let rec = LogRecord::new(self.ptr, self.body.clone());
+        match rec.total_bytes() {
+            Some(n) if n > 0 => self.ptr += n,
+            _ => {
+                // unknown record length, resync from next record group
+                let ptr = self.ptr;
+                self.ptr = self
+                    .group_offsets
+                    .iter()
+                    .find(|off| **off > ptr)
+                    .cloned()
+                    .unwrap_or(self.body_len);
+            }
+        }
+        Some(rec)
+    }
 }
 
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Blocks {
     FileHeader(LogFileHeader),
     Checkpoint(LogCheckpoint),
@@ -163,24 +256,65 @@ pub struct LogFileHeader {
     pub creator: String,
 
     /// (4 bytes) 32 BITs flag, log header flags
-    pub log_hdr_flags: u32,
+    #[derivative(Debug(format_with = "util::fmt_bin32"))]
+    pub log_hdr_flags_bytes: u32,
+    pub log_hdr_flags: LogHeaderFlags,
 }
 
 impl LogFileHeader {
     pub fn new(addr: usize, buf: Arc<Bytes>, block_no: usize) -> Self {
+        let flags = util::u32_val(&buf, addr + 16 + 32);
         Self {
             block_no,
             log_group_id: util::u32_val(&buf, addr),
             log_uuid: util::u32_val(&buf, addr + 4),
             start_lsn: util::u64_val(&buf, addr + 8),
             creator: util::str_val(&buf, addr + 16, 32),
-            log_hdr_flags: util::u32_val(&buf, addr + 16 + 32),
+            log_hdr_flags_bytes: flags,
+            log_hdr_flags: LogHeaderFlags::new(flags),
             buf: buf.clone(),
             addr,
         }
     }
 }
 
+/// log header flags, see log0constants.h, LOG_HEADER_FLAG_xxx, the flag N is
+/// stored in the bit (N - 1)
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct LogHeaderFlags {
+    /// (1 bit) LOG_HEADER_FLAG_NO_LOGGING, redo logging is disabled, the data
+    /// directory can not be recovered after a crash
+    pub no_logging: bool,
+
+    /// (1 bit) LOG_HEADER_FLAG_CRASH_UNSAFE, the server was started with redo
+    /// logging disabled and crashed
+    pub crash_unsafe: bool,
+
+    /// (1 bit) LOG_HEADER_FLAG_NOT_INITIALIZED, the log file has not been
+    /// fully initialized yet
+    pub not_initialized: bool,
+
+    /// (1 bit) LOG_HEADER_FLAG_FILE_FULL, the log file is full and no more
+    /// records could be written
+    pub file_full: bool,
+
+    /// (28 bit) the UNUSED bits
+    pub unused: u32,
+}
+
+impl LogHeaderFlags {
+    pub fn new(flags: u32) -> Self {
+        Self {
+            no_logging: (flags & 0x1) > 0,
+            crash_unsafe: ((flags >> 1) & 1) > 0,
+            not_initialized: ((flags >> 2) & 1) > 0,
+            file_full: ((flags >> 3) & 1) > 0,
+            unused: flags >> 4,
+        }
+    }
+}
+
 /// log checkpoint, see log0constants.h
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
@@ -280,6 +414,9 @@ pub struct LogBlock {
     /// (4 bytes) last checksum
     #[derivative(Debug(format_with = "util::fmt_hex32"))]
     pub checksum: u32,
+
+    /// whether the checksum matches the crc32 of the block
+    pub checksum_ok: bool,
 }
 
 impl LogBlock {
@@ -302,6 +439,7 @@ impl LogBlock {
 
         let hdr_no = b0 & (!Self::LOG_BLOCK_FLUSH_BIT_MASK);
         let epoch_no = util::u32_val(&buf, addr + 8);
+        let checksum = util::u32_val(&buf, addr + OS_FILE_LOG_BLOCK_SIZE - 4);
 
         Self {
             block_no,
@@ -312,7 +450,9 @@ impl LogBlock {
             epoch_no,
             guessed_lsn: log_block_guess_lsn(hdr_no, epoch_no),
             log_record: rec,
-            checksum: util::u32_val(&buf, addr + OS_FILE_LOG_BLOCK_SIZE - 4),
+            checksum,
+            checksum_ok: util::log_block_crc32c(&buf[addr..addr + OS_FILE_LOG_BLOCK_SIZE])
+                == checksum,
             buf: buf.clone(),
             addr,
         }
@@ -343,7 +483,7 @@ impl LogRecord {
         debug!(
             "LogRecord: addr={}, peek={:?}",
             addr,
-            buf.slice(addr..addr + 16).to_vec()
+            buf.slice(addr..buf.len().min(addr + 16)).to_vec()
         );
 
         let hdr = LogRecordHeader::new(addr, buf.clone());
@@ -360,23 +500,51 @@ impl LogRecord {
             LogRecordTypes::MLOG_FILE_DELETE => RedoRecordPayloads::DeleteFile(
                 RedoRecForFileDelete::new(addr + hdr.total_bytes, buf.clone(), &hdr),
             ),
-            LogRecordTypes::MLOG_REC_INSERT => RedoRecordPayloads::RecInsert(
+            LogRecordTypes::MLOG_REC_INSERT
+            | LogRecordTypes::MLOG_REC_INSERT_8027
+            | LogRecordTypes::MLOG_COMP_REC_INSERT_8027 => RedoRecordPayloads::RecInsert(
                 RedoRecForRecordInsert::new(addr + hdr.total_bytes, buf.clone(), &hdr),
             ),
-            LogRecordTypes::MLOG_REC_DELETE => RedoRecordPayloads::RecDelete(
+            LogRecordTypes::MLOG_REC_DELETE
+            | LogRecordTypes::MLOG_REC_DELETE_8027
+            | LogRecordTypes::MLOG_COMP_REC_DELETE_8027 => RedoRecordPayloads::RecDelete(
                 RedoRecForRecordDelete::new(addr + hdr.total_bytes, buf.clone(), &hdr),
             ),
-            LogRecordTypes::MLOG_REC_UPDATE_IN_PLACE => RedoRecordPayloads::RecUpdateInPlace(
-                RedoRecForRecordUpdateInPlace::new(addr + hdr.total_bytes, buf.clone(), &hdr),
-            ),
-            LogRecordTypes::MLOG_REC_CLUST_DELETE_MARK => RedoRecordPayloads::RecClusterDeleteMark(
-                RedoRecForRecordClusterDeleteMark::new(addr + hdr.total_bytes, buf.clone(), &hdr),
-            ),
-            LogRecordTypes::MLOG_REC_SEC_DELETE_MARK => RedoRecordPayloads::RecSecIndexDeleteMark(
-                RedoRecForRecordSecIndexDeleteMark::new(addr + hdr.total_bytes, buf.clone(), &hdr),
-            ),
+            LogRecordTypes::MLOG_REC_UPDATE_IN_PLACE
+            | LogRecordTypes::MLOG_REC_UPDATE_IN_PLACE_8027
+            | LogRecordTypes::MLOG_COMP_REC_UPDATE_IN_PLACE_8027 => {
+                RedoRecordPayloads::RecUpdateInPlace(RedoRecForRecordUpdateInPlace::new(
+                    addr + hdr.total_bytes,
+                    buf.clone(),
+                    &hdr,
+                ))
+            }
+            LogRecordTypes::MLOG_REC_CLUST_DELETE_MARK
+            | LogRecordTypes::MLOG_REC_CLUST_DELETE_MARK_8027
+            | LogRecordTypes::MLOG_COMP_REC_CLUST_DELETE_MARK_8027 => {
+                RedoRecordPayloads::RecClusterDeleteMark(RedoRecForRecordClusterDeleteMark::new(
+                    addr + hdr.total_bytes,
+                    buf.clone(),
+                    &hdr,
+                ))
+            }
+            LogRecordTypes::MLOG_REC_SEC_DELETE_MARK
+            | LogRecordTypes::MLOG_COMP_REC_SEC_DELETE_MARK => {
+                RedoRecordPayloads::RecSecIndexDeleteMark(RedoRecForRecordSecIndexDeleteMark::new(
+                    addr + hdr.total_bytes,
+                    buf.clone(),
+                    &hdr,
+                ))
+            }
             LogRecordTypes::MLOG_PAGE_CREATE | LogRecordTypes::MLOG_COMP_PAGE_CREATE => {
-                RedoRecordPayloads::Empty
+                RedoRecordPayloads::PageCreate(PageTypes::INDEX)
+            }
+            LogRecordTypes::MLOG_PAGE_CREATE_RTREE
+            | LogRecordTypes::MLOG_COMP_PAGE_CREATE_RTREE => {
+                RedoRecordPayloads::PageCreate(PageTypes::RTREE)
+            }
+            LogRecordTypes::MLOG_PAGE_CREATE_SDI | LogRecordTypes::MLOG_COMP_PAGE_CREATE_SDI => {
+                RedoRecordPayloads::PageCreate(PageTypes::SDI)
             }
             LogRecordTypes::MLOG_INIT_FILE_PAGE | LogRecordTypes::MLOG_INIT_FILE_PAGE2 => {
                 RedoRecordPayloads::Empty
@@ -408,6 +576,13 @@ impl LogRecord {
             addr,
         }
     }
+
+    /// total bytes of header and payload, None if the payload is unknown
+    pub fn total_bytes(&self) -> Option<usize> {
+        self.redo_rec_data
+            .total_bytes()
+            .map(|n| n + self.log_rec_hdr.total_bytes)
+    }
 }
 
 /// types of a redo log record
@@ -616,6 +791,39 @@ pub enum LogRecordTypes {
     UNDEF,
 }
 
+/// redo log record type filter, like MLOG_REC_INSERT,MLOG_REC_DELETE, ALL
+/// matches every decoded log record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogTypeFilter {
+    All,
+    Only(Vec<LogRecordTypes>),
+}
+
+impl LogTypeFilter {
+    pub fn matches(&self, log_rec_type: &LogRecordTypes) -> bool {
+        match self {
+            LogTypeFilter::All => *log_rec_type != LogRecordTypes::UNDEF,
+            LogTypeFilter::Only(types) => types.contains(log_rec_type),
+        }
+    }
+}
+
+impl FromStr for LogTypeFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("ALL") {
+            return Ok(LogTypeFilter::All);
+        }
+        s.split(',')
+            .map(|t| {
+                LogRecordTypes::from_str(t.trim()).map_err(|_| format!("未知的日志类型: {}", t))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(LogTypeFilter::Only)
+    }
+}
+
 /// log record header, see mtr0log.ic, mlog_write_initial_log_record_low(...)
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
@@ -706,10 +914,33 @@ pub enum RedoRecordPayloads {
     UndoPageHeader(RedoRecForUndoPageHeader),
     UndoInsert(RedoRecForUndoInsert),
     TableMeta(RedoRecForTableDynamicMeta),
+    /// page create without body, the kind of created page is from the type
+    PageCreate(PageTypes),
     Empty,
     Unknown,
 }
 
+impl RedoRecordPayloads {
+    /// payload bytes, None if the payload is unknown
+    pub fn total_bytes(&self) -> Option<usize> {
+        match self {
+            RedoRecordPayloads::NByte(r) => Some(r.total_bytes),
+            RedoRecordPayloads::DeleteFile(r) => Some(r.total_bytes),
+            RedoRecordPayloads::RecInsert(r) => Some(r.total_bytes),
+            RedoRecordPayloads::RecDelete(r) => Some(r.total_bytes),
+            RedoRecordPayloads::RecUpdateInPlace(r) => Some(r.total_bytes),
+            RedoRecordPayloads::RecClusterDeleteMark(r) => Some(r.total_bytes),
+            RedoRecordPayloads::RecSecIndexDeleteMark(r) => Some(r.total_bytes),
+            RedoRecordPayloads::UndoPageHeader(r) => Some(r.total_bytes),
+            RedoRecordPayloads::UndoInsert(r) => Some(r.total_bytes),
+            RedoRecordPayloads::TableMeta(r) => Some(r.total_bytes),
+            RedoRecordPayloads::PageCreate(_) => Some(0),
+            RedoRecordPayloads::Empty => Some(0),
+            RedoRecordPayloads::Unknown => None,
+        }
+    }
+}
+
 /// log record payload for nByte, see mlog_parse_nbytes(...)
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
@@ -725,23 +956,31 @@ pub struct RedoRecForNByte {
     /// (2 bytes) page offset
     pub page_offset: u16,
 
-    /// (1..4 bytes) value
+    /// (1..11 bytes) value, in compressed form
     pub value: u64,
+
+    /// total bytes
+    #[derivative(Debug = "ignore")]
+    pub total_bytes: usize,
 }
 
 impl RedoRecForNByte {
     pub fn new(addr: usize, buf: Arc<Bytes>, hdr: &LogRecordHeader) -> Self {
         let offset = util::u16_val(&buf, addr);
-        let value = match hdr.log_rec_type {
-            LogRecordTypes::MLOG_1BYTE => util::u8_val(&buf, addr + 2).into(),
-            LogRecordTypes::MLOG_2BYTES => util::u16_val(&buf, addr + 2).into(),
-            LogRecordTypes::MLOG_4BYTES => util::u32_val(&buf, addr + 2).into(),
-            LogRecordTypes::MLOG_8BYTES => util::u64_val(&buf, addr + 2),
+        let (size, value) = match hdr.log_rec_type {
+            LogRecordTypes::MLOG_1BYTE
+            | LogRecordTypes::MLOG_2BYTES
+            | LogRecordTypes::MLOG_4BYTES => {
+                let val = util::u32_compressed(addr + 2, buf.clone());
+                (val.0, val.1 as u64)
+            }
+            LogRecordTypes::MLOG_8BYTES => util::u64_compressed(addr + 2, buf.clone()),
             _ => panic!("未知的 MLOG_nBYTES 类型"),
         };
         Self {
             page_offset: offset,
             value,
+            total_bytes: 2 + size,
             buf: buf.clone(),
             addr,
         }
@@ -766,15 +1005,20 @@ pub struct RedoRecForFileDelete {
     /// (??? bytes) file name
     #[derivative(Debug(format_with = "util::fmt_str"))]
     pub file_name: String,
+
+    /// total bytes
+    #[derivative(Debug = "ignore")]
+    pub total_bytes: usize,
 }
 
 impl RedoRecForFileDelete {
     pub fn new(addr: usize, buf: Arc<Bytes>, _hdr: &LogRecordHeader) -> Self {
         let len = util::u16_val(&buf, addr);
-        assert!(len >= 5);
+        util::check(len >= 5, || format!("错误的文件名长度: len={}", len));
         Self {
             length: len,
             file_name: util::str_val(&buf, addr + 2, len as usize),
+            total_bytes: 2 + len as usize,
             buf: buf.clone(),
             addr,
         }
@@ -852,6 +1096,91 @@ impl RedoLogIndexInfo {
     const VERSION_FLAG: u8 = 0x02;
     const INSTANT_FLAG: u8 = 0x04;
 
+    /// parse the index info in the format of given log record type, the
+    /// record types before 8.0.27 have no index log version and flags
+    pub fn from_hdr(addr: usize, buf: Arc<Bytes>, hdr: &LogRecordHeader) -> Self {
+        match hdr.log_rec_type {
+            LogRecordTypes::MLOG_REC_INSERT_8027
+            | LogRecordTypes::MLOG_REC_CLUST_DELETE_MARK_8027
+            | LogRecordTypes::MLOG_REC_UPDATE_IN_PLACE_8027
+            | LogRecordTypes::MLOG_REC_DELETE_8027 => Self::new_8027(addr, buf, false),
+            LogRecordTypes::MLOG_COMP_REC_INSERT_8027
+            | LogRecordTypes::MLOG_COMP_REC_CLUST_DELETE_MARK_8027
+            | LogRecordTypes::MLOG_COMP_REC_UPDATE_IN_PLACE_8027
+            | LogRecordTypes::MLOG_COMP_REC_DELETE_8027 => Self::new_8027(addr, buf, true),
+            _ => Self::new(addr, buf),
+        }
+    }
+
+    /// index info before 8.0.27, the redundant format has no index info, the
+    /// highest bit of n marks the instant added columns in compact format
+    pub fn new_8027(addr: usize, buf: Arc<Bytes>, comp: bool) -> Self {
+        let mut ptr = addr;
+
+        let mut flags = vec![];
+        let mut flags_byte = 0;
+        let mut n = 1;
+        let mut n_uniq = 1;
+        let mut inst_cols = 0;
+        let mut index_fields = vec![];
+        if comp {
+            flags.push(IndexInfoFlags::COMPACT);
+            flags_byte |= Self::COMPACT_FLAG;
+
+            n = util::u16_val(&buf, ptr);
+            ptr += 2;
+            if (n & 0x8000) > 0 {
+                flags.push(IndexInfoFlags::INSTANT);
+                flags_byte |= Self::INSTANT_FLAG;
+                n &= 0x7fff;
+                inst_cols = util::u16_val(&buf, ptr);
+                ptr += 2;
+            }
+            n_uniq = util::u16_val(&buf, ptr);
+            ptr += 2;
+
+            util::check(n_uniq <= n, || {
+                format!("错误的索引信息: n_uniq={}, n={}", n_uniq, n)
+            });
+
+            for _ in 0..n {
+                let data = util::u16_val(&buf, ptr);
+                ptr += 2;
+                index_fields.push(Self::parse_field(data));
+            }
+        }
+
+        Self {
+            index_log_version: 0,
+            index_flags: flags,
+            index_flags_byte: flags_byte,
+            n,
+            n_uniq,
+            inst_cols,
+            index_fields,
+            total_bytes: ptr - addr,
+            buf: buf.clone(),
+            addr,
+        }
+    }
+
+    /// The high-order bit of data is the NOT NULL flag;
+    /// the rest is 0 or 0x7fff for variable-length fields,
+    /// and 1..0x7ffe for fixed-length fields.
+    fn parse_field(data: u16) -> (u16, IndexFieldNullable, IndexFieldFixed) {
+        let fixed = if (((data as u32) + 1) & 0x7fff) > 1 {
+            IndexFieldFixed::Fixed
+        } else {
+            IndexFieldFixed::NotFixed
+        };
+        let nullable = if (data & 0x8000) > 0 {
+            IndexFieldNullable::Null
+        } else {
+            IndexFieldNullable::NotNull
+        };
+        (data & 0x7fff, nullable, fixed)
+    }
+
     pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
         let mut ptr = addr;
 
@@ -904,29 +1233,16 @@ impl RedoLogIndexInfo {
             ptr = addr;
         }
 
-        assert!(n_uniq <= n);
+        util::check(n_uniq <= n, || {
+            format!("错误的索引信息: n_uniq={}, n={}", n_uniq, n)
+        });
 
         // parse index field
         let mut index_fields = vec![];
         for _ in 0..n {
             let data = util::u16_val(&buf, ptr);
             ptr += 2;
-
-            // The high-order bit of data is the NOT NULL flag;
-            // the rest is 0 or 0x7fff for variable-length fields,
-            // and 1..0x7ffe for fixed-length fields.
-            let fixed = if (((data as u32) + 1) & 0x7fff) > 1 {
-                IndexFieldFixed::Fixed
-            } else {
-                IndexFieldFixed::NotFixed
-            };
-            let nullable = if (data & 0x8000) > 0 {
-                IndexFieldNullable::Null
-            } else {
-                IndexFieldNullable::NotNull
-            };
-            let length = data & 0x7fff;
-            index_fields.push((length, nullable, fixed));
+            index_fields.push(Self::parse_field(data));
         }
 
         Self {
@@ -992,9 +1308,9 @@ pub struct RedoRecForRecordInsert {
 }
 
 impl RedoRecForRecordInsert {
-    pub fn new(addr: usize, buf: Arc<Bytes>, _hdr: &LogRecordHeader) -> Self {
+    pub fn new(addr: usize, buf: Arc<Bytes>, hdr: &LogRecordHeader) -> Self {
         let mut ptr = addr;
-        let index = RedoLogIndexInfo::new(ptr, buf.clone());
+        let index = RedoLogIndexInfo::from_hdr(ptr, buf.clone(), hdr);
         ptr += index.total_bytes;
 
         let offset = util::u16_val(&buf, ptr);
@@ -1066,14 +1382,14 @@ pub struct RedoRecForRecordDelete {
 }
 
 impl RedoRecForRecordDelete {
-    pub fn new(addr: usize, buf: Arc<Bytes>, _hdr: &LogRecordHeader) -> Self {
+    pub fn new(addr: usize, buf: Arc<Bytes>, hdr: &LogRecordHeader) -> Self {
         debug!(
             "RedoRecForRecordDelete::new() addr={}, peek={:?}",
             addr,
             buf.slice(addr..addr + 8).to_vec()
         );
         let mut ptr = addr;
-        let index = RedoLogIndexInfo::new(ptr, buf.clone());
+        let index = RedoLogIndexInfo::from_hdr(ptr, buf.clone(), hdr);
         ptr += index.total_bytes;
 
         debug!("index = {:?}", &index);
@@ -1254,9 +1570,9 @@ pub struct RedoRecForRecordUpdateInPlace {
 }
 
 impl RedoRecForRecordUpdateInPlace {
-    pub fn new(addr: usize, buf: Arc<Bytes>, _hdr: &LogRecordHeader) -> Self {
+    pub fn new(addr: usize, buf: Arc<Bytes>, hdr: &LogRecordHeader) -> Self {
         let mut ptr = addr;
-        let index = RedoLogIndexInfo::new(ptr, buf.clone());
+        let index = RedoLogIndexInfo::from_hdr(ptr, buf.clone(), hdr);
         ptr += index.total_bytes;
 
         let b0 = util::u8_val(&buf, ptr);
@@ -1351,9 +1667,9 @@ pub struct RedoRecForRecordClusterDeleteMark {
 }
 
 impl RedoRecForRecordClusterDeleteMark {
-    pub fn new(addr: usize, buf: Arc<Bytes>, _hdr: &LogRecordHeader) -> Self {
+    pub fn new(addr: usize, buf: Arc<Bytes>, hdr: &LogRecordHeader) -> Self {
         let mut ptr = addr;
-        let index = RedoLogIndexInfo::new(ptr, buf.clone());
+        let index = RedoLogIndexInfo::from_hdr(ptr, buf.clone(), hdr);
         ptr += index.total_bytes;
 
         let b0 = util::u8_val(&buf, ptr);
@@ -1403,6 +1719,9 @@ pub struct RedoRecForRecordSecIndexDeleteMark {
     #[derivative(Debug = "ignore")]
     pub buf: Arc<Bytes>,
 
+    /// redo log index info, only for MLOG_COMP_REC_SEC_DELETE_MARK
+    pub index_info: Option<RedoLogIndexInfo>,
+
     /// (1 byte) value
     pub value: u8,
 
@@ -1415,9 +1734,18 @@ pub struct RedoRecForRecordSecIndexDeleteMark {
 }
 
 impl RedoRecForRecordSecIndexDeleteMark {
-    pub fn new(addr: usize, buf: Arc<Bytes>, _hdr: &LogRecordHeader) -> Self {
+    pub fn new(addr: usize, buf: Arc<Bytes>, hdr: &LogRecordHeader) -> Self {
         let mut ptr = addr;
 
+        let index = match hdr.log_rec_type {
+            LogRecordTypes::MLOG_COMP_REC_SEC_DELETE_MARK => {
+                let index = RedoLogIndexInfo::new(ptr, buf.clone());
+                ptr += index.total_bytes;
+                Some(index)
+            }
+            _ => None,
+        };
+
         let value = util::u8_val(&buf, ptr);
         ptr += 1;
 
@@ -1425,6 +1753,7 @@ impl RedoRecForRecordSecIndexDeleteMark {
         ptr += 2;
 
         Self {
+            index_info: index,
             value,
             offset,
             total_bytes: ptr - addr,
@@ -1496,6 +1825,9 @@ pub struct RedoRecForUndoInsert {
     #[derivative(Debug(format_with = "util::fmt_bytes_vec"))]
     pub data: Bytes,
 
+    /// undo record parsed from the content data
+    pub undo_rec: UndoRecord,
+
     /// total bytes
     #[derivative(Debug = "ignore")]
     pub total_bytes: usize,
@@ -1516,9 +1848,12 @@ impl RedoRecForUndoInsert {
         let data = buf.slice(ptr..ptr + (data_len as usize));
         ptr += data_len as usize;
 
+        let undo_rec = UndoRecord::from_log_bytes(&data, None);
+
         Self {
             data_len,
             data,
+            undo_rec,
             total_bytes: ptr - addr,
             buf: buf.clone(),
             addr,
@@ -1548,6 +1883,7 @@ pub enum PersistentTypes {
 }
 
 /// log record payload for log for some persistent dynamic metadata change, see
+/// Persisters::write_log(...)
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
 pub struct RedoRecForTableDynamicMeta {
@@ -1569,7 +1905,12 @@ pub struct RedoRecForTableDynamicMeta {
     #[derivative(Debug(format_with = "util::fmt_enum"))]
     pub persistent_type: PersistentTypes,
 
-    // MetadataRecover::parseMetadataLog()
+    /// corrupted indexes, list of (space_id, index_id), see CorruptedIndexPersister::read(...)
+    pub corrupted_indexes: Vec<(u32, u64)>,
+
+    /// (much compressed) autoinc counter, see AutoIncPersister::read(...)
+    pub auto_inc: Option<u64>,
+
     /// total bytes
     #[derivative(Debug = "ignore")]
     pub total_bytes: usize,
@@ -1580,27 +1921,125 @@ impl RedoRecForTableDynamicMeta {
         let mut ptr = addr;
 
         let id = util::u64_much_compressed(ptr, buf.clone());
-        ptr += 2;
+        ptr += id.0;
 
         let version = util::u64_much_compressed(ptr, buf.clone());
-        ptr += 2;
+        ptr += version.0;
 
         let b = util::u8_val(&buf, ptr);
         ptr += 1;
 
         let persistent_type: PersistentTypes = b.into();
 
-        // TODO: will parse metadata in those funciton, see dict0dict.cc
-        //   1. CorruptedIndexPersister::read(...)
-        //   2. AutoIncPersister::read(...)
+        let mut corrupted_indexes = vec![];
+        let mut auto_inc = None;
+        match persistent_type {
+            PersistentTypes::PM_INDEX_CORRUPTED => {
+                let num = util::u8_val(&buf, ptr);
+                ptr += 1;
+                for _ in 0..num {
+                    let space_id = util::u32_compressed(ptr, buf.clone());
+                    ptr += space_id.0;
+                    let index_id = util::u64_much_compressed(ptr, buf.clone());
+                    ptr += index_id.0;
+                    corrupted_indexes.push((space_id.1, index_id.1));
+                }
+            }
+            PersistentTypes::PM_TABLE_AUTO_INC => {
+                let autoinc = util::u64_much_compressed(ptr, buf.clone());
+                ptr += autoinc.0;
+                auto_inc = Some(autoinc.1);
+            }
+            _ => {}
+        }
 
         Self {
             table_id: id.1,
             version: version.1,
             persistent_type,
+            corrupted_indexes,
+            auto_inc,
             total_bytes: ptr - addr,
             buf: buf.clone(),
             addr,
         }
     }
 }
+
+#[cfg(test)]
+mod redo_tests {
+
+    use super::*;
+    use crate::util;
+
+    #[test]
+    fn parse_comp_rec_insert_8027() {
+        util::init_unit_test();
+        let buf = Arc::new(Bytes::from_static(&[
+            38, 0x05, 0x04, // type, space_id, page_no
+            0x80, 0x03, 0x00, 0x02, 0x00, 0x01, // n with instant flag, inst_cols, n_uniq
+            0x80, 0x04, 0x00, 0x06, 0x7f, 0xff, // index fields
+            0x00, 0x63, // offset
+            0x07, // end_seg_len, data_len=3 and end_seg_flag
+            0x00, 0x05, 0x00, // info_bits, origin_offset, mismatch_index
+            0xaa, 0xbb, 0xcc, // data
+        ]));
+        let rec = LogRecord::new(0, buf.clone());
+        assert_eq!(
+            rec.log_rec_hdr.log_rec_type,
+            LogRecordTypes::MLOG_COMP_REC_INSERT_8027
+        );
+        assert_eq!(rec.total_bytes(), Some(buf.len()));
+        match &rec.redo_rec_data {
+            RedoRecordPayloads::RecInsert(r) => {
+                assert_eq!(r.index_info.index_log_version, 0);
+                assert_eq!(r.index_info.n, 3);
+                assert_eq!(r.index_info.n_uniq, 1);
+                assert_eq!(r.index_info.inst_cols, 2);
+                assert_eq!(r.index_info.index_fields[0].0, 4);
+                assert_eq!(r.offset, 0x63);
+                assert_eq!(r.origin_offset, 5);
+                assert_eq!(r.data.to_vec(), vec![0xaa, 0xbb, 0xcc]);
+            }
+            other => panic!("unexpected payload: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_rec_delete_8027() {
+        util::init_unit_test();
+        // redundant format has no index info
+        let buf = Arc::new(Bytes::from_static(&[14, 0x05, 0x04, 0x00, 0x63]));
+        let rec = LogRecord::new(0, buf.clone());
+        assert_eq!(rec.total_bytes(), Some(buf.len()));
+        match &rec.redo_rec_data {
+            RedoRecordPayloads::RecDelete(r) => {
+                assert_eq!(r.index_info.total_bytes, 0);
+                assert_eq!(r.offset, 0x63);
+            }
+            other => panic!("unexpected payload: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_page_create_kind() {
+        util::init_unit_test();
+        for (rec_type, page_kind) in [
+            (LogRecordTypes::MLOG_COMP_PAGE_CREATE, PageTypes::INDEX),
+            (
+                LogRecordTypes::MLOG_COMP_PAGE_CREATE_RTREE,
+                PageTypes::RTREE,
+            ),
+            (LogRecordTypes::MLOG_PAGE_CREATE_SDI, PageTypes::SDI),
+        ] {
+            let buf = Arc::new(Bytes::from(vec![rec_type.clone() as u8, 0x05, 0x04]));
+            let rec = LogRecord::new(0, buf.clone());
+            assert_eq!(rec.log_rec_hdr.log_rec_type, rec_type);
+            assert_eq!(rec.total_bytes(), Some(buf.len()));
+            match &rec.redo_rec_data {
+                RedoRecordPayloads::PageCreate(kind) => assert_eq!(kind, &page_kind),
+                other => panic!("unexpected payload: {:?}", other),
+            }
+        }
+    }
+}
@@ -1,12 +1,19 @@
 use std::sync::Arc;
 
+use anyhow::{Error, Result};
 use bytes::Bytes;
 use derivative::Derivative;
 
-use crate::{ibd::record::RecordHeader, sdi::record::EntryTypes, util};
+use crate::{
+    ibd::{page::RECORD_HEADER_SIZE, record::RecordHeader},
+    sdi::record::EntryTypes,
+    util,
+};
 
 // sdi
 pub const SDI_DATA_HEADER_SIZE: usize = 33;
+pub const BTR_EXTERN_FIELD_REF_SIZE: usize = 20;
+pub const BTR_BLOB_HDR_SIZE: usize = 8;
 
 /// SDI Record
 #[derive(Clone, Derivative)]
@@ -27,24 +34,123 @@ pub struct SdiRecord {
     /// SDI Data Header
     pub sdi_hdr: SdiDataHeader,
 
-    /// SDI Data String, uncompressed string
+    /// SDI Data String, uncompressed string, empty if the data is stored
+    /// externally and not resolved yet
     pub sdi_str: String,
+
+    /// (N bytes) the in-record prefix of compressed data
+    #[derivative(Debug(format_with = "util::fmt_bytes_hex"))]
+    pub sdi_prefix: Bytes,
+
+    /// (20 bytes) the reference of externally stored compressed data
+    pub sdi_ext: Option<SdiExternRef>,
 }
 
 impl SdiRecord {
     pub fn new(addr: usize, buf: Arc<Bytes>, rec_hdr: RecordHeader, hdr: SdiDataHeader) -> Self {
+        let (local_len, is_extern) = Self::local_len(addr, &buf);
+
         let beg = addr + SDI_DATA_HEADER_SIZE;
+        if is_extern {
+            let end = beg + local_len - BTR_EXTERN_FIELD_REF_SIZE;
+            return Self {
+                rec_hdr,
+                sdi_hdr: hdr,
+                sdi_str: String::new(),
+                sdi_prefix: buf.slice(beg..end),
+                sdi_ext: Some(SdiExternRef::new(end, buf.clone())),
+                buf: buf.clone(),
+                addr,
+            };
+        }
+
         let comped_data = buf.slice(beg..beg + (hdr.comp_len as usize));
-        let uncomped_data = util::zlib_uncomp(comped_data).unwrap();
-        assert_eq!(uncomped_data.len(), hdr.uncomp_len as usize);
+        let uncomped_data = util::zlib_uncomp(comped_data.clone()).unwrap();
+        util::check(uncomped_data.len() == hdr.uncomp_len as usize, || {
+            format!(
+                "SDI 解压长度不一致: expect={}, actual={}",
+                hdr.uncomp_len,
+                uncomped_data.len()
+            )
+        });
         Self {
             rec_hdr,
             sdi_hdr: hdr,
             sdi_str: uncomped_data,
+            sdi_prefix: comped_data,
+            sdi_ext: None,
             buf: buf.clone(),
             addr,
         }
     }
+
+    /// the in-record length of data and the extern flag, the data is the only
+    /// variable length field, the length bytes is stored before the record
+    /// header in reverse order
+    pub fn local_len(addr: usize, buf: &[u8]) -> (usize, bool) {
+        let len_addr = addr - RECORD_HEADER_SIZE - 1;
+        if buf[len_addr] & 0x80 > 0 {
+            let len = (((buf[len_addr] & 0x3f) as usize) << 8) | buf[len_addr - 1] as usize;
+            (len, buf[len_addr] & 0x40 > 0)
+        } else {
+            (buf[len_addr] as usize, false)
+        }
+    }
+
+    /// uncompress the data after the external part is read
+    pub fn resolve(&mut self, ext_data: &[u8]) -> Result<()> {
+        let mut comped_data = self.sdi_prefix.to_vec();
+        comped_data.extend_from_slice(ext_data);
+        if comped_data.len() != self.sdi_hdr.comp_len as usize {
+            return Err(Error::msg(format!(
+                "SDI 外部数据长度不一致: expect={}, actual={}",
+                self.sdi_hdr.comp_len,
+                comped_data.len()
+            )));
+        }
+        self.sdi_str = util::zlib_uncomp(comped_data.into())?;
+        Ok(())
+    }
+}
+
+/// Externally stored field reference, see lob0lob.h
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct SdiExternRef {
+    /// page address
+    #[derivative(Debug(format_with = "util::fmt_addr"))]
+    pub addr: usize,
+
+    /// page data buffer
+    #[derivative(Debug = "ignore")]
+    pub buf: Arc<Bytes>,
+
+    /// (4 bytes) space id where stored
+    pub space_id: u32,
+    /// (4 bytes) page number of the first BLOB page
+    pub page_no: u32,
+    /// (4 bytes) byte offset on the first BLOB page
+    pub offset: u32,
+    /// (8 bytes) length of externally stored part, the high bits are flags
+    pub length: u64,
+}
+
+impl SdiExternRef {
+    pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
+        Self {
+            space_id: util::u32_val(&buf, addr),
+            page_no: util::u32_val(&buf, addr + 4),
+            offset: util::u32_val(&buf, addr + 8),
+            length: util::u64_val(&buf, addr + 12),
+            buf: buf.clone(),
+            addr,
+        }
+    }
+
+    /// length of externally stored part, without the flag bits
+    pub fn ext_len(&self) -> usize {
+        (self.length & 0xffffffff) as usize
+    }
 }
 
 /// SDI Data Header
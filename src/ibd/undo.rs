@@ -15,6 +15,18 @@ use crate::{ibd::dict, util};
 /// XID data size
 pub const XIDDATASIZE: usize = 128;
 
+/// TRX_UNDO_LOG_GTID_VERSION, offset of the GTID version in undo log header
+pub const TRX_UNDO_LOG_GTID_VERSION: usize = 186;
+
+/// TRX_UNDO_LOG_GTID, offset of the GTID in undo log header
+pub const TRX_UNDO_LOG_GTID: usize = 187;
+
+/// TRX_UNDO_LOG_XA_GTID, offset of the XA PREPARE GTID in undo log header
+pub const TRX_UNDO_LOG_XA_GTID: usize = TRX_UNDO_LOG_GTID + TRX_UNDO_LOG_GTID_LEN;
+
+/// TRX_UNDO_LOG_GTID_LEN, the GTID is stored as `uuid:gno` text, padded with 0
+pub const TRX_UNDO_LOG_GTID_LEN: usize = 64;
+
 /// undo log, see trx0undo.h
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
@@ -129,6 +141,15 @@ pub struct UndoLogHeader {
 
     /// (140 bytes) XA
     pub xa_trx_info: Option<XaTrxInfo>,
+
+    /// (1 byte) TRX_UNDO_LOG_GTID_VERSION, defined if any GTID flag is set
+    pub gtid_version: Option<u8>,
+
+    /// (64 bytes) TRX_UNDO_LOG_GTID, GTID from replication
+    pub gtid: Option<String>,
+
+    /// (64 bytes) TRX_UNDO_LOG_XA_GTID, GTID for XA PREPARE
+    pub xa_gtid: Option<String>,
 }
 
 impl UndoLogHeader {
@@ -166,11 +187,35 @@ impl UndoLogHeader {
             prev_log: util::u16_val(&buf, addr + 32),
             history_node: FlstNode::new(addr + 34, buf.clone()),
             xa_trx_info: xa,
+            gtid_version: if (b0 & (Self::TRX_UNDO_FLAG_GTID | Self::TRX_UNDO_FLAG_XA_PREPARE_GTID))
+                > 0
+            {
+                Some(util::u8_val(&buf, addr + TRX_UNDO_LOG_GTID_VERSION))
+            } else {
+                None
+            },
+            gtid: if (b0 & Self::TRX_UNDO_FLAG_GTID) > 0 {
+                Some(Self::read_gtid(&buf, addr + TRX_UNDO_LOG_GTID))
+            } else {
+                None
+            },
+            xa_gtid: if (b0 & Self::TRX_UNDO_FLAG_XA_PREPARE_GTID) > 0 {
+                Some(Self::read_gtid(&buf, addr + TRX_UNDO_LOG_XA_GTID))
+            } else {
+                None
+            },
             buf: buf.clone(),
             addr,
         }
     }
 
+    /// the serialized GTID text, trailing 0 bytes removed
+    fn read_gtid(buf: &[u8], addr: usize) -> String {
+        let data = &buf[addr..addr + TRX_UNDO_LOG_GTID_LEN];
+        let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+        String::from_utf8_lossy(&data[..len]).to_string()
+    }
+
     /// true if undo log header includes X/Open XA transaction identification XID
     const TRX_UNDO_FLAG_XID: u8 = 0x01;
 
@@ -324,9 +369,9 @@ impl UndoRecord {
                 UndoPageTypes::TRX_UNDO_INSERT => {
                     UndoRecordPayloads::Insert(UndoRecForInsert::new(addr + 3, buf.clone(), n_uniq))
                 }
-                UndoPageTypes::TRX_UNDO_UPDATE => {
-                    UndoRecordPayloads::Update(UndoRecForUpdate::new(addr + 3, buf.clone(), n_uniq))
-                }
+                UndoPageTypes::TRX_UNDO_UPDATE => UndoRecordPayloads::Update(
+                    UndoRecForUpdate::new(addr + 3, buf.clone(), &hdr, n_uniq),
+                ),
                 UndoPageTypes::UNDEF => UndoRecordPayloads::Nothing,
             },
         };
@@ -343,6 +388,35 @@ impl UndoRecord {
         let page_hdr = UndoPageHeader::new(addr + FIL_HEADER_SIZE, buf.clone());
         Self::new(addr + boffset, buf.clone(), &page_hdr, Some(n_uniq))
     }
+
+    /// parse the undo record logged by MLOG_UNDO_INSERT, the logged bytes
+    /// start from the type_cmpl byte, the next/prev record offsets are not
+    /// logged. Only the insert undo record is decoded, the update undo record
+    /// needs the exact n_uniq to locate the updated fields, which is unknown
+    /// in redo log
+    pub fn from_log_bytes(data: &Bytes, n_uniq: Option<usize>) -> Self {
+        // prev_rec_offset(2) + next_rec_offset(2) + data + padding
+        let mut raw = vec![0u8; 4];
+        raw.extend_from_slice(data);
+        raw.extend_from_slice(&[0u8; 16]);
+        let buf = Arc::new(Bytes::from(raw));
+
+        let addr = 2;
+        let hdr = UndoRecordHeader::new(addr, buf.clone());
+        let payload = match hdr.type_info {
+            UndoTypes::INSERT_REC => {
+                UndoRecordPayloads::Insert(UndoRecForInsert::new(addr + 3, buf.clone(), n_uniq))
+            }
+            _ => UndoRecordPayloads::Nothing,
+        };
+
+        Self {
+            undo_rec_hdr: hdr,
+            undo_rec_data: payload,
+            buf,
+            addr,
+        }
+    }
 }
 
 /// undo record header
@@ -384,7 +458,7 @@ impl UndoRecordHeader {
     pub fn new(addr: usize, buf: Arc<Bytes>) -> Self {
         let type_cmpl = util::u8_val(&buf, addr + 2);
 
-        let cmpl_info_bits = (type_cmpl >> 4) & 0x03;
+        let cmpl_info_bits = (type_cmpl / Self::TRX_UNDO_CMPL_INFO_MULT) & 0x03;
         let mut cmpl_info = vec![];
         if (cmpl_info_bits & Self::UPD_NODE_NO_ORD_CHANGE) > 0 {
             cmpl_info.push(CmplInfos::NO_ORD_CHANGE);
@@ -394,9 +468,6 @@ impl UndoRecordHeader {
         }
 
         let mut extra_flags = vec![];
-        if (type_cmpl & Self::TRX_UNDO_CMPL_INFO_MULT) > 0 {
-            extra_flags.push(UndoExtraFlags::CMPL_INFO_MULT);
-        }
         if (type_cmpl & Self::TRX_UNDO_MODIFY_BLOB) > 0 {
             extra_flags.push(UndoExtraFlags::MODIFY_BLOB);
         }
@@ -426,6 +497,27 @@ impl UndoRecordHeader {
         self.next_rec_offset as usize
     }
 
+    /// the update changes ordering fields, the undo record contains the
+    /// ordering fields after the update vector
+    pub fn has_ord_change(&self) -> bool {
+        !self.cmpl_info.contains(&CmplInfos::NO_ORD_CHANGE)
+    }
+
+    /// the update does not change any field size
+    pub fn has_size_change(&self) -> bool {
+        !self.cmpl_info.contains(&CmplInfos::NO_SIZE_CHANGE)
+    }
+
+    /// the update touches external storage fields
+    pub fn has_extern(&self) -> bool {
+        self.extra_flags.contains(&UndoExtraFlags::UPD_EXTERN)
+    }
+
+    /// the undo record has the new 1-byte flag after type_cmpl
+    pub fn has_modify_blob(&self) -> bool {
+        self.extra_flags.contains(&UndoExtraFlags::MODIFY_BLOB)
+    }
+
     // Compilation info flags: these must fit within 2 bits; see trx0rec.h
 
     /// no secondary index record will be changed in the update and no ordering
@@ -499,9 +591,6 @@ pub enum CmplInfos {
 #[derive(Debug, Display, Eq, PartialEq, Ord, PartialOrd, Clone)]
 #[derive(Deserialize_repr, Serialize_repr, EnumString)]
 pub enum UndoExtraFlags {
-    /// compilation info is multiplied by this and ORed to the type above
-    CMPL_INFO_MULT,
-
     /// If this bit is set in type_cmpl, then the undo log record has support
     /// for partial update of BLOBs. Also to make the undo log format
     /// extensible, introducing a new flag next to the type_cmpl flag.
@@ -584,7 +673,7 @@ pub struct UndoRecForUpdate {
     #[derivative(Debug = "ignore")]
     pub buf: Arc<Bytes>,
 
-    /// introducing a change in undo log format
+    /// introducing a change in undo log format, exists if MODIFY_BLOB is set
     pub new1byte: u8,
 
     /// (1..11 bytes) undo number, in much compressed form
@@ -610,15 +699,33 @@ pub struct UndoRecForUpdate {
 
     /// updated fields
     pub upd_fields: Vec<UndoRecUpdatedField>,
+
+    /// (2 bytes) ordering fields total bytes, exists if has_ord_change
+    pub n_ord_bytes: u16,
+
+    /// ordering fields, the old values of all ordering fields
+    pub ord_fields: Vec<UndoRecUpdatedField>,
+
+    /// total bytes
+    #[derivative(Debug = "ignore")]
+    pub total_bytes: usize,
 }
 
 impl UndoRecForUpdate {
-    pub fn new(addr: usize, buf: Arc<Bytes>, n_uniq: Option<usize>) -> Self {
+    pub fn new(
+        addr: usize,
+        buf: Arc<Bytes>,
+        hdr: &UndoRecordHeader,
+        n_uniq: Option<usize>,
+    ) -> Self {
         let mut ptr = addr;
 
         // info!("peek={:?}", buf.slice(ptr..ptr + 20).to_vec());
-        let new1byte = util::u8_val(&buf, ptr);
-        ptr += 1;
+        let mut new1byte = 0;
+        if hdr.has_modify_blob() {
+            new1byte = util::u8_val(&buf, ptr);
+            ptr += 1;
+        }
 
         let undo_no = util::u64_much_compressed(ptr, buf.clone());
         ptr += undo_no.0;
@@ -650,11 +757,26 @@ impl UndoRecForUpdate {
         // updated fields
         let mut upd_fields = vec![];
         for i in 0..(n_updated.1 as usize) {
-            let fld = UndoRecUpdatedField::new(ptr, buf.clone(), i);
+            let fld = UndoRecUpdatedField::new(ptr, buf.clone(), i, hdr.has_extern());
             ptr += fld.total_bytes;
             upd_fields.push(fld);
         }
 
+        // ordering fields, see trx_undo_rec_get_partial_row(...)
+        let mut n_ord_bytes = 0;
+        let mut ord_fields = vec![];
+        if hdr.type_info != UndoTypes::DEL_MARK_REC && hdr.has_ord_change() {
+            n_ord_bytes = util::u16_val(&buf, ptr);
+            let end = ptr + n_ord_bytes as usize;
+            ptr += 2;
+            while ptr < end {
+                let fld =
+                    UndoRecUpdatedField::new(ptr, buf.clone(), ord_fields.len(), hdr.has_extern());
+                ptr += fld.total_bytes;
+                ord_fields.push(fld);
+            }
+        }
+
         Self {
             new1byte,
             undo_no: undo_no.1,
@@ -665,6 +787,9 @@ impl UndoRecForUpdate {
             key_fields,
             n_fields: n_updated.1,
             upd_fields,
+            n_ord_bytes,
+            ord_fields,
+            total_bytes: ptr - addr,
             buf: buf.clone(),
             addr,
         }
@@ -718,6 +843,12 @@ impl UndoRecKeyField {
     }
 }
 
+/// SQL NULL length, see univ.i
+pub const UNIV_SQL_NULL: u32 = 0xFFFFFFFF;
+
+/// the extern storage field flag, UNIV_SQL_NULL - UNIV_PAGE_SIZE_DEF
+pub const UNIV_EXTERN_STORAGE_FIELD: u32 = UNIV_SQL_NULL - UNIV_PAGE_SIZE as u32;
+
 /// undo record updated fields
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
@@ -742,13 +873,23 @@ pub struct UndoRecUpdatedField {
     /// (field_len bytes) field data, see length for total size
     pub field_data: Bytes,
 
+    /// the field is SQL NULL
+    pub is_null: bool,
+
+    /// the field is stored externally, the data is the local prefix
+    pub is_extern: bool,
+
+    /// (1-5 bytes) original field length of external field
+    pub orig_len: usize,
+
     /// total bytes
     #[derivative(Debug = "ignore")]
     pub total_bytes: usize,
 }
 
 impl UndoRecUpdatedField {
-    pub fn new(addr: usize, buf: Arc<Bytes>, seq: usize) -> Self {
+    /// see trx_undo_rec_get_col_val(...)
+    pub fn new(addr: usize, buf: Arc<Bytes>, seq: usize, has_extern: bool) -> Self {
         let mut ptr = addr;
 
         let field_no = util::u32_compressed(ptr, buf.clone());
@@ -757,14 +898,39 @@ impl UndoRecUpdatedField {
         let length = util::u32_compressed(ptr, buf.clone());
         ptr += length.0;
 
-        let data = buf.slice(ptr..ptr + (length.1 as usize));
-        ptr += length.1 as usize;
+        let mut len = length.1;
+        let mut orig_len = 0;
+        let mut is_extern = false;
+        if has_extern && len == UNIV_EXTERN_STORAGE_FIELD {
+            let orig = util::u32_compressed(ptr, buf.clone());
+            ptr += orig.0;
+            orig_len = orig.1 as usize;
+            let real = util::u32_compressed(ptr, buf.clone());
+            ptr += real.0;
+            len = real.1;
+            is_extern = true;
+        } else if len != UNIV_SQL_NULL && len >= UNIV_EXTERN_STORAGE_FIELD {
+            len -= UNIV_EXTERN_STORAGE_FIELD;
+            is_extern = true;
+        }
+
+        let is_null = len == UNIV_SQL_NULL;
+        let data = if is_null {
+            Bytes::new()
+        } else {
+            let data = buf.slice(ptr..ptr + (len as usize));
+            ptr += len as usize;
+            data
+        };
 
         Self {
             sequence: seq,
             field_no: field_no.1,
-            field_len: length.1 as usize,
+            field_len: if is_null { 0 } else { len as usize },
             field_data: data,
+            is_null,
+            is_extern,
+            orig_len,
             total_bytes: ptr - addr,
             buf: buf.clone(),
             addr,
@@ -785,6 +951,34 @@ mod undo_tests {
     const REDO_1: &str = "data/redo_block_01";
     const UNDO_1: &str = "data/undo_log_01";
 
+    #[test]
+    fn test_undo_log_gtid() -> Result<()> {
+        util::init_unit_test();
+
+        // no GTID tagged transaction in the fixture
+        let mut fact = DatafileFactory::from_file(PathBuf::from(UNDO_1))?;
+        let buf = fact.page_buffer(188)?;
+        let last_log = util::u16_val(&buf, FIL_HEADER_SIZE + 18 + 2) as usize;
+        let hdr = UndoLogHeader::new(last_log, buf.clone());
+        assert!(!hdr.is_gtid());
+        assert_eq!(hdr.gtid_version, None);
+        assert_eq!(hdr.gtid, None);
+
+        let gtid = "3e11fa47-71ca-11e1-9e33-c80aa9429562:23";
+        let mut data = buf.to_vec();
+        data[last_log + 20] |= UndoLogHeader::TRX_UNDO_FLAG_GTID;
+        data[last_log + TRX_UNDO_LOG_GTID_VERSION] = 1;
+        let beg = last_log + TRX_UNDO_LOG_GTID;
+        data[beg..beg + TRX_UNDO_LOG_GTID_LEN].fill(0);
+        data[beg..beg + gtid.len()].copy_from_slice(gtid.as_bytes());
+        let hdr = UndoLogHeader::new(last_log, Arc::new(Bytes::from(data)));
+        assert!(hdr.undo_flags.contains(&UndoFlags::GTID));
+        assert_eq!(hdr.gtid_version, Some(1));
+        assert_eq!(hdr.gtid.as_deref(), Some(gtid));
+        assert_eq!(hdr.xa_gtid, None);
+        Ok(())
+    }
+
     #[test]
     fn test_read_undo_record() -> Result<()> {
         util::init_unit_test();
@@ -799,4 +993,46 @@ mod undo_tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_undo_update_flags() -> Result<()> {
+        util::init_unit_test();
+
+        let mut fact = DatafileFactory::from_file(PathBuf::from(UNDO_1))?;
+
+        // type_cmpl = 0x5c, UPD_EXIST_REC with NO_ORD_CHANGE
+        let buf = fact.page_buffer(134)?;
+        let rec = UndoRecord::read(0, buf.clone(), 272, 1);
+        let hdr = &rec.undo_rec_hdr;
+        assert_eq!(hdr.type_info, UndoTypes::UPD_EXIST_REC);
+        assert_eq!(hdr.cmpl_info, vec![CmplInfos::NO_ORD_CHANGE]);
+        assert!(!hdr.has_ord_change());
+        assert!(hdr.has_modify_blob());
+        assert!(!hdr.has_extern());
+        if let UndoRecordPayloads::Update(upd) = &rec.undo_rec_data {
+            assert!(upd.ord_fields.is_empty());
+            assert_eq!(rec.addr + 3 + upd.total_bytes + 2, hdr.next_addr());
+        } else {
+            panic!("expect update undo record");
+        }
+
+        // type_cmpl = 0x4c, UPD_EXIST_REC with ordering fields changed, table has 2 unique keys
+        let rec = UndoRecord::read(0, buf.clone(), 304, 2);
+        let hdr = &rec.undo_rec_hdr;
+        assert!(hdr.has_ord_change());
+        if let UndoRecordPayloads::Update(upd) = &rec.undo_rec_data {
+            assert_eq!(upd.n_fields, 1);
+            assert_eq!(upd.ord_fields.len(), 3);
+            assert_eq!(rec.addr + 3 + upd.total_bytes + 2, hdr.next_addr());
+        } else {
+            panic!("expect update undo record");
+        }
+
+        // type_cmpl = 0xdc, UPD_EXIST_REC with UPD_EXTERN
+        let buf = fact.page_buffer(138)?;
+        let rec = UndoRecord::read(0, buf, 272, 1);
+        assert!(rec.undo_rec_hdr.has_extern());
+
+        Ok(())
+    }
 }
@@ -6,8 +6,10 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
+use app::DumpFormats;
+use chrono::FixedOffset;
 use clap::{Parser, Subcommand};
-use ibd::redo::LogRecordTypes;
+use ibd::redo::LogTypeFilter;
 use log::info;
 
 mod app;
@@ -23,6 +25,28 @@ pub struct Args {
     /// Input innodb datafile. for example departments.ibd
     input: PathBuf,
 
+    /// Treat the input as the system tablespace (ibdata1), skip SDI loading
+    #[arg(long, default_value_t = false)]
+    system: bool,
+
+    /// Downgrade the structural checks to warnings, best-effort reading of damaged files
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+
+    /// Decode the records as raw bytes if the index of the page is not found
+    /// in the table definition, e.g. orphaned or dropped index pages
+    #[arg(long, default_value_t = false)]
+    best_effort: bool,
+
+    /// Debug only, read the FIL header and trailer in little-endian instead of
+    /// the on-disk big-endian, for inspecting exported raw structures
+    #[arg(long, default_value_t = false)]
+    assume_le: bool,
+
+    /// Increase log verbosity, -v info, -vv debug, -vvv trace
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -30,7 +54,14 @@ pub struct Args {
 #[derive(Debug, Subcommand)]
 enum Commands {
     /// Print basic information.
-    Info,
+    Info {
+        /// Print the information as a JSON object
+        #[arg(short, long, default_value_t = false)]
+        json: bool,
+    },
+
+    /// Print the server/space version and the format capabilities
+    Version,
 
     /// List all page. page_type, page_number and more
     List {
@@ -59,8 +90,81 @@ enum Commands {
         limit: usize,
     },
 
+    /// Print tablespace information in the FSP header page
+    Space,
+
+    /// Print the data dictionary header page of the system tablespace
+    DictHeader,
+
+    /// Print the TRX_SYS page: rollback segment slots, binlog info, doublewrite buffer
+    TrxSys {
+        /// Print the doublewrite buffer blocks and check the magic numbers
+        #[arg(short, long, default_value_t = false)]
+        dblwr: bool,
+    },
+
+    /// Print the rollback segment header page
+    Rseg {
+        /// The page number, starts from 0.
+        page_no: usize,
+    },
+
+    /// Walk the rollback segments and undo logs of an undo tablespace
+    UndoSpace,
+
+    /// List collations, the input datafile is not used
+    Collation {
+        /// Only list collations of the charset, like utf8mb4
+        #[arg(short, long)]
+        charset: Option<String>,
+    },
+
     /// Describe datafile information by sdi page
-    Desc,
+    Desc {
+        /// Only list the columns, skip parsing the indexes
+        #[arg(long, default_value_t = false)]
+        columns_only: bool,
+
+        /// Print more information, like the se_private_data of indexes
+        #[arg(long, default_value_t = false)]
+        verbose: bool,
+    },
+
+    /// Report the leaf page fragmentation of indexes
+    Frag {
+        /// The B+ tree root page number, default for all indexes in SDI
+        root: Option<usize>,
+    },
+
+    /// Report the last modified LSN of each index by the maximum page LSN
+    Lsn,
+
+    /// Map the INDEX pages to the indexes by the index id in the page
+    IndexPages,
+
+    /// Verify the space_id and checksum of all pages, and the index root pages are consistent with SDI
+    Verify {
+        /// Also check the trailer LSN low 32 bits of all pages, to detect torn pages
+        #[arg(long, default_value_t = false)]
+        strict_trailer: bool,
+    },
+
+    /// Audit the XDES free bitmap against the segment page lists
+    Audit,
+
+    /// Print the content hash of each page, for building manifests
+    Hash {
+        /// Exclude the checksum and LSN fields, logically identical pages
+        /// hash the same
+        #[arg(short, long, default_value_t = false)]
+        logical: bool,
+    },
+
+    /// Compare with another datafile page by page
+    Diff {
+        /// The other datafile
+        other: PathBuf,
+    },
 
     /// Print SDI json
     Sdi {
@@ -70,12 +174,79 @@ enum Commands {
         /// Print index root segements
         #[arg(short, long, default_value_t = false)]
         root_segments: bool,
+
+        /// Print the raw compact json, instead of pretty printed
+        #[arg(short, long, default_value_t = false)]
+        compact: bool,
+
+        /// Only print the Nth SDI object, starts from 0
+        #[arg(short, long)]
+        index: Option<usize>,
+
+        /// Print the raw SDI record headers, without decompressing the data
+        #[arg(long, default_value_t = false)]
+        raw_page: bool,
+
+        /// Write the raw SDI objects into the file in the ibd2sdi JSON format
+        #[arg(long)]
+        raw_sdi_bytes: Option<PathBuf>,
     },
 
     /// View page data with given page_no.
     View {
         /// The page number, starts from 0.
         page_no: usize,
+
+        /// Cross-check the page type against the page content
+        #[arg(short, long)]
+        strict: bool,
+
+        /// Print the INDEX page records in heap order
+        #[arg(long)]
+        heap: bool,
+
+        /// Read one page from stdin instead of the input file, the page_no is
+        /// checked against the FIL header
+        #[arg(long)]
+        stdin: bool,
+    },
+
+    /// View the INDEX page record with given heap_no
+    Record {
+        /// The page number, starts from 0.
+        page_no: usize,
+
+        /// The heap number of record, the user records start from 2
+        heap_no: u16,
+    },
+
+    /// Dump the trailer of every page, without reading the full pages
+    Trailer,
+
+    /// Print the FIL header and trailer of a page, without parsing the body
+    Fil {
+        /// The page number, starts from 0.
+        page_no: usize,
+    },
+
+    /// Locate the page of a byte offset in the datafile
+    Offset {
+        /// The byte offset in the datafile, starts from 0.
+        byte_offset: usize,
+    },
+
+    /// Extract the raw bytes of pages into a file
+    Extract {
+        /// The page number, starts from 0.
+        page_no: Option<usize>,
+
+        /// Output file
+        #[arg(short, long)]
+        out: PathBuf,
+
+        /// Extract a contiguous page range, like 3-7
+        #[arg(short, long, value_parser = util::parse_page_range)]
+        range: Option<(usize, usize)>,
     },
 
     /// Dump index page user records
@@ -83,12 +254,13 @@ enum Commands {
         /// The page number, starts from 0.
         page_no: Option<usize>,
 
-        /// Limit the total row in the dump
+        /// Limit the total row in the dump, garbage records included
         #[arg(short, long, default_value_t = 10)]
         limit: usize,
 
-        /// Dump the garbage list
-        #[arg(short, long, default_value_t = false)]
+        /// Dump the garbage list after the user records, each garbage record
+        /// is tagged
+        #[arg(short, long, visible_alias = "all-records", default_value_t = false)]
         garbage: bool,
 
         /// Print more information
@@ -98,6 +270,77 @@ enum Commands {
         /// Dump the B+ tree root
         #[arg(short, long)]
         btree_root: Option<usize>,
+
+        /// Timezone offset applied on TIMESTAMP values, like +08:00, default is UTC
+        #[arg(long, allow_hyphen_values = true, value_parser = util::parse_tz_offset)]
+        tz: Option<FixedOffset>,
+
+        /// Only dump the given columns, separated by comma, like c1,c2
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+
+        /// Render unknown type columns as raw hex bytes
+        #[arg(long, default_value_t = false)]
+        raw_bytes: bool,
+
+        /// Output format, TEXT, SQL, JSONL or CSV
+        #[arg(long, default_value_t = DumpFormats::TEXT)]
+        format: DumpFormats,
+
+        /// Only dump the records modified by the transaction id
+        #[arg(long)]
+        trx_id: Option<u64>,
+
+        /// Field delimiter of CSV format
+        #[arg(long, default_value_t = ',')]
+        csv_delim: char,
+
+        /// Omit the header line of CSV format
+        #[arg(long, default_value_t = false)]
+        no_header: bool,
+
+        /// Only count the user records in the leaf chain, without decoding
+        #[arg(long, default_value_t = false)]
+        count: bool,
+
+        /// Dump the user records of the whole leaf chain, start from the
+        /// B+ tree root, or the clustered index root by default
+        #[arg(long, default_value_t = false)]
+        full: bool,
+
+        /// Print the raw record body bytes in hex under the decoded fields
+        #[arg(long, default_value_t = false)]
+        with_raw: bool,
+
+        /// Print the start/end page offset of each field under the decoded
+        /// fields
+        #[arg(long, default_value_t = false)]
+        layout: bool,
+
+        /// Only show the leaf pages in the index header grid
+        #[arg(long, default_value_t = false, conflicts_with = "only_internal")]
+        only_leaf: bool,
+
+        /// Only show the internal (non-leaf) pages in the index header grid
+        #[arg(long, default_value_t = false)]
+        only_internal: bool,
+
+        /// Right-trim the space padding of CHAR columns, VARCHAR is untouched
+        #[arg(long, default_value_t = false)]
+        trim: bool,
+
+        /// Token rendered for NULL values in TEXT and CSV format, like \N
+        #[arg(long, default_value = "NULL")]
+        null_as: String,
+
+        /// Number of threads decoding the leaf pages of --full concurrently
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
+
+        /// Maximum depth of the B+ tree traversal, abort on a deeper or
+        /// cyclic tree
+        #[arg(long, default_value_t = 64)]
+        max_depth: usize,
     },
 
     /// Undo log print
@@ -119,17 +362,21 @@ enum Commands {
         block_no: Option<usize>,
 
         /// Dump given log_type redo blocks, log_type like MLOG_xxx, MLOG_1BYTE,
-        /// MLOG_REC_INSERT ...
+        /// MLOG_REC_INSERT ..., separated by comma, or ALL for every log type
         #[arg(short, long)]
-        dump_log_type: Option<LogRecordTypes>,
+        dump_log_type: Option<LogTypeFilter>,
     },
 }
 
 fn main() -> Result<()> {
-    util::init();
-
     let args = Args::parse();
+    util::init(args.verbose);
+    util::set_lenient(args.lenient);
+    util::set_best_effort(args.best_effort);
+    util::set_assume_le(args.assume_le);
+
     let mut app = app::App::new(args.input);
+    app.system = args.system;
 
     app.run(args.command)?;
 
@@ -2598,6 +2598,42 @@ pub fn coll_find(id: u32) -> &'static Collation {
     COLLMAP.get(&id).expect("ERR_COLLATION_NOT_FOUND")
 }
 
+/// list all collations, ordered by id
+pub fn coll_all() -> Vec<&'static Collation> {
+    let mut colls: Vec<_> = COLLMAP.values().collect();
+    colls.sort_by_key(|c| c.id);
+    colls
+}
+
+/// find collations by charset name, ordered by id
+pub fn coll_by_charset(charset: &str) -> Vec<&'static Collation> {
+    coll_all()
+        .into_iter()
+        .filter(|c| c.charset.eq_ignore_ascii_case(charset))
+        .collect()
+}
+
+/// find the default collation of the charset
+pub fn coll_default(charset: &str) -> Option<&'static Collation> {
+    coll_by_charset(charset).into_iter().find(|c| c.default)
+}
+
+/// maximum bytes of one character in the charset, see
+/// INFORMATION_SCHEMA.CHARACTER_SETS
+pub fn charset_maxlen(charset: &str) -> Option<u32> {
+    let maxlen = match charset.to_ascii_lowercase().as_str() {
+        "armscii8" | "ascii" | "binary" | "cp1250" | "cp1251" | "cp1256" | "cp1257" | "cp850"
+        | "cp852" | "cp866" | "dec8" | "geostd8" | "greek" | "hebrew" | "hp8" | "keybcs2"
+        | "koi8r" | "koi8u" | "latin1" | "latin2" | "latin5" | "latin7" | "macce" | "macroman"
+        | "swe7" | "tis620" => 1,
+        "big5" | "cp932" | "euckr" | "gb2312" | "gbk" | "sjis" | "ucs2" => 2,
+        "eucjpms" | "ujis" | "utf8mb3" | "utf8" => 3,
+        "gb18030" | "utf16" | "utf16le" | "utf32" | "utf8mb4" => 4,
+        _ => return None,
+    };
+    Some(maxlen)
+}
+
 #[cfg(test)]
 mod meta_consts_tests {
 
@@ -2611,4 +2647,30 @@ mod meta_consts_tests {
             assert_eq!(*id, coll.id);
         }
     }
+
+    #[test]
+    fn charset_maxlen_of_collations() {
+        util::init_unit_test();
+        for coll in coll_all() {
+            assert!(charset_maxlen(coll.charset).is_some(), "{}", coll.charset);
+        }
+        assert_eq!(charset_maxlen("latin1"), Some(1));
+        assert_eq!(charset_maxlen("gbk"), Some(2));
+        assert_eq!(charset_maxlen("UTF8MB3"), Some(3));
+        assert_eq!(charset_maxlen("utf32"), Some(4));
+        assert_eq!(charset_maxlen("no_such_charset"), None);
+    }
+
+    #[test]
+    fn find_collation_by_charset() {
+        util::init_unit_test();
+        let colls = coll_by_charset("utf8mb4");
+        assert!(colls.len() > 1);
+        assert!(colls.iter().all(|c| c.charset == "utf8mb4"));
+        assert_eq!(
+            coll_default("utf8mb4").map(|c| c.name),
+            Some("utf8mb4_0900_ai_ci")
+        );
+        assert!(coll_default("no_such_charset").is_none());
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use bytes::Bytes;
 use num_enum::FromPrimitive;
 use serde_repr::{Deserialize_repr, Serialize_repr};
@@ -5,7 +7,7 @@ use strum::{Display, EnumString};
 
 use super::cst::Collation;
 use crate::{
-    meta::cst::coll_find,
+    meta::cst::{charset_maxlen, coll_find},
     sdi::record::{DataDictColumn, DataDictIndex, DataDictIndexElement, TableDataDictObject},
     util::{self, conv_strdata_to_bytes},
 };
@@ -103,10 +105,15 @@ pub enum RowFormats {
 #[derive(Debug, Display, Default, Eq, PartialEq, Clone)]
 #[derive(Deserialize_repr, Serialize_repr, EnumString, FromPrimitive)]
 pub enum IndexTypes {
+    #[strum(to_string = "PRIMARY")]
     IT_PRIMARY = 1,
+    #[strum(to_string = "UNIQUE")]
     IT_UNIQUE = 2,
+    #[strum(to_string = "MULTIPLE")]
     IT_MULTIPLE = 3,
+    #[strum(to_string = "FULLTEXT")]
     IT_FULLTEXT = 4,
+    #[strum(to_string = "SPATIAL")]
     IT_SPATIAL = 5,
     #[default]
     UNDEF,
@@ -117,10 +124,15 @@ pub enum IndexTypes {
 #[derive(Debug, Display, Default, Eq, PartialEq, Clone)]
 #[derive(Deserialize_repr, Serialize_repr, EnumString, FromPrimitive)]
 pub enum IndexAlgorithm {
+    #[strum(to_string = "SE_SPECIFIC")]
     IA_SE_SPECIFIC = 1,
+    #[strum(to_string = "BTREE")]
     IA_BTREE = 2,
+    #[strum(to_string = "RTREE")]
     IA_RTREE = 3,
+    #[strum(to_string = "HASH")]
     IA_HASH = 4,
+    #[strum(to_string = "FULLTEXT")]
     IA_FULLTEXT = 5,
     #[default]
     UNDEF,
@@ -191,6 +203,59 @@ impl TableDef {
                 .unwrap_or(-1),
         }
     }
+
+    /// the clustered index, the primary index or the first index by position
+    pub fn primary_index(&self) -> Option<&IndexDef> {
+        self.idx_defs
+            .iter()
+            .find(|idx| idx.idx_type == IndexTypes::IT_PRIMARY)
+            .or_else(|| self.idx_defs.iter().min_by_key(|idx| idx.pos))
+    }
+
+    /// whether the clustered index is built on user columns, otherwise InnoDB
+    /// generates the implicit DB_ROW_ID as primary key
+    pub fn has_explicit_pk(&self) -> bool {
+        match self.primary_index() {
+            Some(idx) => idx
+                .elements
+                .first()
+                .map(|e| e.col_name != "DB_ROW_ID")
+                .unwrap_or(false),
+            None => false,
+        }
+    }
+
+    /// count the instant added and dropped columns
+    pub fn instant_stats(&self) -> InstantStats {
+        let n_added = self.col_defs.iter().filter(|c| c.version_added > 0).count();
+        let n_dropped = self
+            .col_defs
+            .iter()
+            .filter(|c| c.version_dropped > 0)
+            .count();
+        InstantStats {
+            n_added,
+            n_dropped,
+            n_current: self.col_defs.len() - n_dropped,
+            n_physical: self.col_defs.len(),
+        }
+    }
+}
+
+/// instant ADD/DROP COLUMN statistics of a table
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InstantStats {
+    /// number of columns with version_added > 0
+    pub n_added: usize,
+
+    /// number of columns with version_dropped > 0
+    pub n_dropped: usize,
+
+    /// number of columns in the current table definition
+    pub n_current: usize,
+
+    /// number of columns in the physical record, dropped columns included
+    pub n_physical: usize,
 }
 
 /// column definition
@@ -211,6 +276,9 @@ pub struct ColumnDef {
     /// is variadic field
     pub isvar: bool,
 
+    /// is unsigned integer
+    pub is_unsigned: bool,
+
     /// data dictionary type
     pub dd_type: ColumnTypes,
 
@@ -226,6 +294,12 @@ pub struct ColumnDef {
     /// comment
     pub comment: String,
 
+    /// is virtual generated column
+    pub is_virtual: bool,
+
+    /// generated column expression
+    pub gen_expr: Option<String>,
+
     /// collation
     pub coll_id: u32,
 
@@ -304,6 +378,7 @@ impl ColumnDef {
                 _ => todo!("不支持的数据长度类型: HiddenTypes::{}", ddc.hidden),
             },
             isnil: ddc.is_nullable,
+            is_unsigned: ddc.is_unsigned,
             isvar: match coll.charset {
                 "latin1" | "binary" => {
                     matches!(ddtype, ColumnTypes::VARCHAR | ColumnTypes::VAR_STRING)
@@ -316,6 +391,8 @@ impl ColumnDef {
             },
             dd_type: ddtype,
             comment: ddc.comment.clone(),
+            is_virtual: ddc.is_virtual,
+            gen_expr: Some(ddc.generation_expression_utf8.clone()).filter(|e| !e.is_empty()),
             coll_id: ddc.collation_id,
             coll_name: coll.name.into(),
             charset: coll.charset.into(),
@@ -340,6 +417,25 @@ impl ColumnDef {
             defval: default,
         }
     }
+
+    /// generated column clause, VIRTUAL columns are not stored in the record
+    pub fn gen_clause(&self) -> Option<String> {
+        self.gen_expr.as_ref().map(|expr| {
+            format!(
+                "GENERATED ALWAYS AS ({}) {}",
+                expr,
+                if self.is_virtual { "VIRTUAL" } else { "STORED" }
+            )
+        })
+    }
+
+    /// printable INSTANT default value, quoted text or hex bytes for binary
+    pub fn defval_str(&self) -> Option<String> {
+        self.defval.as_ref().map(|b| match std::str::from_utf8(b) {
+            Ok(text) if self.charset != "binary" => format!("'{}'", text),
+            _ => format!("0x{}", hex::encode(b)),
+        })
+    }
 }
 
 /// index definition
@@ -374,6 +470,9 @@ pub struct IndexDef {
 
     /// index elememts
     pub elements: Vec<IndexElementDef>,
+
+    /// all the key-values of se_private_data, like id, root, space_id, trx_id
+    pub se_private: BTreeMap<String, String>,
 }
 
 impl IndexDef {
@@ -400,8 +499,18 @@ impl IndexDef {
             algorithm: ddi.algorithm.into(),
             comment: ddi.comment.clone(),
             elements: ele_defs,
+            se_private: priv_data.into_iter().collect(),
         }
     }
+
+    /// the se_private_data in key=value format, sorted by key
+    pub fn se_private_str(&self) -> String {
+        self.se_private
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 /// index element definition
@@ -433,12 +542,16 @@ pub struct IndexElementDef {
 
     /// data length
     pub data_len: u32,
+
+    /// prefix length in characters, if only a prefix of the column is indexed
+    pub prefix_len: Option<u32>,
 }
 
 impl IndexElementDef {
     pub fn from(ele: &DataDictIndexElement, col: &ColumnDef) -> Self {
         let len = ele.length as i32;
         Self {
+            prefix_len: Self::calc_prefix_len(len, col),
             col_name: col.col_name.clone(),
             utf8_def: col.utf8_def.clone(),
             pos: ele.ordinal_position as usize,
@@ -450,4 +563,27 @@ impl IndexElementDef {
             data_len: col.data_len,
         }
     }
+
+    /// the element length is in bytes, a prefix index has a shorter length
+    /// than the full column, e.g. KEY(name(10))
+    fn calc_prefix_len(ele_len: i32, col: &ColumnDef) -> Option<u32> {
+        let full_len = match col.dd_type {
+            ColumnTypes::VARCHAR => col.data_len - if col.data_len <= 256 { 1 } else { 2 },
+            ColumnTypes::VAR_STRING | ColumnTypes::STRING => col.data_len,
+            _ => return None,
+        };
+        if ele_len < 0 || ele_len as u32 >= full_len {
+            return None;
+        }
+        let mbmaxlen = charset_maxlen(&col.charset).unwrap_or(1);
+        Some(ele_len as u32 / mbmaxlen)
+    }
+
+    /// column name with the prefix length suffix, like name(10)
+    pub fn key_part(&self) -> String {
+        match self.prefix_len {
+            Some(n) => format!("{}({})", self.col_name, n),
+            None => self.col_name.clone(),
+        }
+    }
 }
@@ -59,6 +59,12 @@ pub struct SdiTablespaceObject {
     extra: HashMap<String, Value>,
 }
 
+impl SdiTablespaceObject {
+    pub fn from_str(s: &str) -> Result<Self> {
+        Ok(serde_json::from_str(s).expect("ERR_SDI_TABLESPACE_OBJECT_FORMAT"))
+    }
+}
+
 /// Data Dictionary Object
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TablespaceDataDictObject {
@@ -122,6 +128,7 @@ pub struct DataDictColumn {
     pub hidden: HiddenTypes,
     pub char_length: u32,
     pub comment: String,
+    pub generation_expression_utf8: String,
     pub collation_id: u32,
     pub column_key: u8,
     pub column_type_utf8: String,
@@ -171,8 +178,13 @@ mod sdi_tests_run {
     use std::{fs, path::PathBuf};
 
     use anyhow::Result;
+    use serde_json::Value;
 
-    use crate::{sdi::record::SdiEntry, util};
+    use crate::{
+        meta::def::ColumnDef,
+        sdi::record::{SdiEntry, SdiTableObject},
+        util,
+    };
 
     const SDI_01: &str = "data/employees_sdi.json";
 
@@ -184,4 +196,36 @@ mod sdi_tests_run {
         dbg!(&objs);
         Ok(())
     }
+
+    #[test]
+    fn parse_generated_column() -> Result<()> {
+        util::init_unit_test();
+        let text = fs::read_to_string(PathBuf::from(SDI_01))?;
+        let text = text
+            .replacen(
+                "\"generation_expression_utf8\": \"\"",
+                "\"generation_expression_utf8\": \"concat(`first_name`,' ',`last_name`)\"",
+                1,
+            )
+            .replacen("\"is_virtual\": false", "\"is_virtual\": true", 1)
+            .replacen("\"comment\": \"\"", "\"comment\": \"employee number\"", 1);
+        let entries: Vec<Value> = serde_json::from_str(&text)?;
+        let obj = SdiTableObject::from_str(&entries[0]["object"].to_string())?;
+        let col = ColumnDef::from(&obj.dd_object.columns[0]);
+        assert_eq!(
+            col.gen_expr.as_deref(),
+            Some("concat(`first_name`,' ',`last_name`)")
+        );
+        assert!(col.is_virtual);
+        assert_eq!(col.comment, "employee number");
+        assert_eq!(
+            col.gen_clause().as_deref(),
+            Some("GENERATED ALWAYS AS (concat(`first_name`,' ',`last_name`)) VIRTUAL")
+        );
+
+        let col = ColumnDef::from(&obj.dd_object.columns[1]);
+        assert_eq!(col.gen_expr, None);
+        assert_eq!(col.gen_clause(), None);
+        Ok(())
+    }
 }
@@ -3,24 +3,90 @@ use std::{
     collections::HashMap,
     env::set_var,
     fmt::{Binary, Debug, Display, LowerHex},
-    io::{Read, Write},
-    sync::{Arc, Once},
+    io::{IsTerminal, Read, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Once,
+    },
 };
 
 use anyhow::Result;
 use bytes::Bytes;
-use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, Utc};
 use colored::{ColoredString, Colorize};
+use env_logger::Env;
 use flate2::read::ZlibDecoder;
-use log::{debug, trace};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::{debug, trace, warn, LevelFilter};
 
 static INIT_LOGGER_ONCE: Once = Once::new();
 
-pub fn init() {
+/// downgrade the structural checks to warnings, see `--lenient`
+static LENIENT: AtomicBool = AtomicBool::new(false);
+
+/// decode the pages with unknown index as raw bytes, see `--best-effort`
+static BEST_EFFORT: AtomicBool = AtomicBool::new(false);
+
+/// read the FIL header and trailer in little-endian, see `--assume-le`
+static ASSUME_LE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_lenient(lenient: bool) {
+    LENIENT.store(lenient, Ordering::Relaxed);
+}
+
+pub fn is_lenient() -> bool {
+    LENIENT.load(Ordering::Relaxed)
+}
+
+pub fn set_best_effort(best_effort: bool) {
+    BEST_EFFORT.store(best_effort, Ordering::Relaxed);
+}
+
+pub fn is_best_effort() -> bool {
+    BEST_EFFORT.load(Ordering::Relaxed)
+}
+
+pub fn set_assume_le(assume_le: bool) {
+    ASSUME_LE.store(assume_le, Ordering::Relaxed);
+}
+
+pub fn is_assume_le() -> bool {
+    ASSUME_LE.load(Ordering::Relaxed)
+}
+
+/// structural check, panic with the message if the condition fails, or log a
+/// warning in lenient mode for best-effort reading of damaged files
+#[track_caller]
+pub fn check(cond: bool, msg: impl FnOnce() -> String) {
+    if cond {
+        return;
+    }
+    if is_lenient() {
+        warn!("{}", msg());
+    } else {
+        panic!("{}", msg());
+    }
+}
+
+/// map the repeated `-v` flags to log level, None means follow `RUST_LOG`
+pub fn verbose_level(verbose: u8) -> Option<LevelFilter> {
+    match verbose {
+        0 => None,
+        1 => Some(LevelFilter::Info),
+        2 => Some(LevelFilter::Debug),
+        _ => Some(LevelFilter::Trace),
+    }
+}
+
+pub fn init(verbose: u8) {
     INIT_LOGGER_ONCE.call_once(|| {
         dotenv::dotenv().ok();
 
-        env_logger::builder()
+        let mut builder = env_logger::Builder::from_env(Env::default().default_filter_or("warn"));
+        if let Some(level) = verbose_level(verbose) {
+            builder.filter_level(level);
+        }
+        builder
             .format(|buf, record| {
                 writeln!(
                     buf,
@@ -46,6 +112,41 @@ pub fn init_unit_test() {
     });
 }
 
+/// read the fixture file and patch the bytes in memory, for unit tests
+#[cfg(test)]
+pub fn patch_fixture(path: &str, patch: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let mut data = std::fs::read(path).unwrap();
+    patch(&mut data);
+    data
+}
+
+/// temporary file for unit tests, removed on drop even if the test fails
+#[cfg(test)]
+pub struct TempFile {
+    pub path: std::path::PathBuf,
+}
+
+#[cfg(test)]
+impl TempFile {
+    /// write the data into a unique file of the temp dir, the tag is used in
+    /// the file name
+    pub fn new(tag: &str, data: &[u8]) -> std::io::Result<Self> {
+        static SEQ: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let seq = SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let name = format!("ibr_{}_{}_{}", tag, std::process::id(), seq);
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, data)?;
+        Ok(Self { path })
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
 pub fn fmt_bin8<T>(d: &T, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error>
 where
     T: Display + Binary + LowerHex,
@@ -120,6 +221,17 @@ where
     write!(f, "{}", d.to_string().green())
 }
 
+pub fn fmt_timestamp(
+    d: &DateTime<FixedOffset>,
+    f: &mut std::fmt::Formatter,
+) -> Result<(), std::fmt::Error> {
+    if d.offset().local_minus_utc() == 0 {
+        write!(f, "{} UTC", d.format("%Y-%m-%d %H:%M:%S"))
+    } else {
+        write!(f, "{}", d.format("%Y-%m-%d %H:%M:%S %:z"))
+    }
+}
+
 pub fn fmt_oneline<T>(d: &T, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error>
 where
     T: Debug,
@@ -186,6 +298,212 @@ pub fn fmt_bytes_hex(d: &Bytes, f: &mut std::fmt::Formatter) -> Result<(), std::
     }
 }
 
+/// spaced hex string with byte length, like `Raw(3 bytes: 0a 0b 0c)`
+pub fn raw_bytes_str(d: &[u8]) -> String {
+    let hex = d
+        .iter()
+        .map(|e| format!("{:02x}", e))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("Raw({} bytes: {})", d.len(), hex)
+}
+
+/// human-readable byte size, like 128.00 KiB
+pub fn humanize_bytes(n: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+    let mut val = n as f64 / 1024.0;
+    let mut unit = 0;
+    while val >= 1024.0 && unit < UNITS.len() - 1 {
+        val /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", val, UNITS[unit])
+}
+
+/// collapse the sorted page numbers into ranges, like 4-6, 9
+pub fn fmt_page_ranges(pages: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for &page_no in pages {
+        match ranges.last_mut() {
+            Some(last) if last.1 + 1 == page_no => last.1 = page_no,
+            _ => ranges.push((page_no, page_no)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|(beg, end)| {
+            if beg == end {
+                beg.to_string()
+            } else {
+                format!("{}-{}", beg, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// minimum page count to show the progress bar, 64MiB for 16K page size
+pub const PROGRESS_BAR_MIN_PAGES: usize = 4096;
+
+/// create progress bar on stderr, hidden if stderr is not a TTY or too few pages
+pub fn progress_bar(page_count: usize) -> ProgressBar {
+    if page_count < PROGRESS_BAR_MIN_PAGES || !std::io::stderr().is_terminal() {
+        return ProgressBar::hidden();
+    }
+    let pb = ProgressBar::new(page_count as u64);
+    pb.set_style(
+        ProgressStyle::with_template("[{elapsed_precise}] {bar:40} {pos}/{len} pages ({eta})")
+            .unwrap(),
+    );
+    pb
+}
+
+/// CRC32C (Castagnoli) polynomial, reversed
+const CRC32C_POLY: u32 = 0x82f6_3b78;
+
+/// lookup table for the software CRC32C
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            crc = if crc & 1 > 0 {
+                (crc >> 1) ^ CRC32C_POLY
+            } else {
+                crc >> 1
+            };
+            k += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// table based CRC32C, the fallback if the CPU has no CRC32 instruction
+pub fn crc32c_sw(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &b| {
+        CRC32C_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// whether the hardware CRC32C is available, SSE4.2 on x86_64, CRC on aarch64
+pub fn crc32c_hw_available() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        std::arch::is_x86_feature_detected!("sse4.2")
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("crc")
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
+/// CRC32C checksum, used by page and log block checksum
+pub fn crc32c(data: &[u8]) -> u32 {
+    if crc32c_hw_available() {
+        crc32c::crc32c(data)
+    } else {
+        crc32c_sw(data)
+    }
+}
+
+/// page checksum of the crc32 algorithm, see buf_calc_page_crc32(...), the
+/// checksum and flush LSN fields and the trailer are excluded
+pub fn page_crc32c(page: &[u8]) -> u32 {
+    crc32c(&page[4..26]) ^ crc32c(&page[38..page.len() - 8])
+}
+
+/// page checksum of the crc32 algorithm in the legacy big-endian byte order,
+/// see ut_crc32_legacy_big_endian(...), each 8-byte word aligned to the page
+/// start is fed in reversed byte order
+pub fn page_crc32c_legacy(page: &[u8]) -> u32 {
+    let crc = |beg: usize, end: usize| {
+        let head = end.min(beg.next_multiple_of(8));
+        let mut data = page[beg..head].to_vec();
+        let mut pos = head;
+        while pos + 8 <= end {
+            data.extend(page[pos..pos + 8].iter().rev());
+            pos += 8;
+        }
+        data.extend_from_slice(&page[pos..end]);
+        crc32c(&data)
+    };
+    crc(4, 26) ^ crc(38, page.len() - 8)
+}
+
+/// fold a pair of integers, see ut_fold_ulint_pair(...)
+fn fold_pair(n1: u64, n2: u64) -> u64 {
+    ((((n1 ^ 1653893711) << 8).wrapping_add(n2)) ^ 1463735687).wrapping_add(n1)
+}
+
+/// fold the bytes, see ut_fold_binary(...)
+fn fold_binary(data: &[u8]) -> u64 {
+    data.iter().fold(0, |fold, &b| fold_pair(fold, b as u64))
+}
+
+/// page checksum of the innodb algorithm, see buf_calc_page_new_checksum(...)
+pub fn page_innodb_checksum(page: &[u8]) -> u32 {
+    fold_binary(&page[4..26]).wrapping_add(fold_binary(&page[38..page.len() - 8])) as u32
+}
+
+/// old-style trailer checksum of the innodb algorithm, see
+/// buf_calc_page_old_checksum(...)
+pub fn page_old_checksum(page: &[u8]) -> u32 {
+    fold_binary(&page[..26]) as u32
+}
+
+/// whether the page checksum is valid with any of the crc32, innodb and none
+/// algorithms, see buf_page_is_corrupted(...). An all-zero page is valid
+pub fn page_checksum_valid(page: &[u8]) -> bool {
+    if page.iter().all(|&b| b == 0) {
+        return true;
+    }
+    let field1 = u32_val(page, 0);
+    let field2 = u32_val(page, page.len() - 8);
+
+    // none: the magic value in both fields
+    if field1 == 0xdeadbeef && field2 == 0xdeadbeef {
+        return true;
+    }
+
+    // crc32: both fields hold the same checksum
+    if field1 == field2 && (field1 == page_crc32c(page) || field1 == page_crc32c_legacy(page)) {
+        return true;
+    }
+
+    // innodb: old format stores the LSN high 32 bits in the trailer, new
+    // format leaves the header checksum 0
+    let old_ok = field2 == u32_val(page, 16) || field2 == page_old_checksum(page);
+    let new_ok = field1 == 0 || field1 == page_innodb_checksum(page);
+    old_ok && new_ok
+}
+
+/// log block checksum, see log_block_calc_checksum_crc32(...), the trailer
+/// is excluded
+pub fn log_block_crc32c(block: &[u8]) -> u32 {
+    crc32c(&block[..block.len() - 4])
+}
+
+/// 64-bit FNV-1a hash over the parts, stable across platforms and builds
+pub fn fnv1a64(parts: &[&[u8]]) -> u64 {
+    parts
+        .iter()
+        .flat_map(|part| part.iter())
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, &b| {
+            (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
 pub fn colored_page_number(page_no: usize) -> ColoredString {
     format!("#{}", page_no).cyan()
 }
@@ -202,6 +520,35 @@ pub fn zlib_uncomp(input: Bytes) -> Result<String> {
     Ok(output)
 }
 
+/// inflate a zlib stream into raw bytes, trailing bytes after the stream end
+/// are ignored
+pub fn zlib_uncomp_bytes(input: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = ZlibDecoder::new(input);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output)?;
+    Ok(output)
+}
+
+/// decompress a LZ4 block (no frame header) of page-level compression
+#[cfg(feature = "lz4")]
+pub fn lz4_uncomp_bytes(input: &[u8], original_size: usize) -> Result<Vec<u8>> {
+    let output = lz4_flex::block::decompress(input, original_size)
+        .map_err(|err| anyhow::Error::msg(format!("LZ4 数据格式错误: {}", err)))?;
+    if output.len() != original_size {
+        return Err(anyhow::Error::msg(format!(
+            "LZ4 解压长度不一致: expect={}, actual={}",
+            original_size,
+            output.len()
+        )));
+    }
+    Ok(output)
+}
+
+#[cfg(not(feature = "lz4"))]
+pub fn lz4_uncomp_bytes(_input: &[u8], _original_size: usize) -> Result<Vec<u8>> {
+    Err(anyhow::Error::msg("未启用 lz4 特性, 无法解压 LZ4 压缩页"))
+}
+
 pub fn align8(num: usize) -> usize {
     (num >> 3) + if (num & 0x7) > 0 { 1 } else { 0 }
 }
@@ -266,6 +613,41 @@ pub fn u64_val(buf: &[u8], addr: usize) -> u64 {
     u64::from_be_bytes(buf[addr..addr + 8].try_into().expect("ERR_READ_VALUE_u64"))
 }
 
+// little-endian readers, for debugging only, InnoDB is big-endian on disk,
+// see `--assume-le`
+
+pub fn i16_val_le(buf: &[u8], addr: usize) -> i16 {
+    i16::from_le_bytes(buf[addr..addr + 2].try_into().expect("ERR_READ_VALUE_i16"))
+}
+
+pub fn i32_val_le(buf: &[u8], addr: usize) -> i32 {
+    i32::from_le_bytes(buf[addr..addr + 4].try_into().expect("ERR_READ_VALUE_i32"))
+}
+
+pub fn u16_val_le(buf: &[u8], addr: usize) -> u16 {
+    u16::from_le_bytes(buf[addr..addr + 2].try_into().expect("ERR_READ_VALUE_u16"))
+}
+
+pub fn u32_val_le(buf: &[u8], addr: usize) -> u32 {
+    u32::from_le_bytes(buf[addr..addr + 4].try_into().expect("ERR_READ_VALUE_u32"))
+}
+
+pub fn u48_val_le(buf: &[u8], addr: usize) -> u64 {
+    let mut arr = [0u8; 8];
+    arr[..6].copy_from_slice(&buf[addr..addr + 6]);
+    u64::from_le_bytes(arr)
+}
+
+pub fn u56_val_le(buf: &[u8], addr: usize) -> u64 {
+    let mut arr = [0u8; 8];
+    arr[..7].copy_from_slice(&buf[addr..addr + 7]);
+    u64::from_le_bytes(arr)
+}
+
+pub fn u64_val_le(buf: &[u8], addr: usize) -> u64 {
+    u64::from_le_bytes(buf[addr..addr + 8].try_into().expect("ERR_READ_VALUE_u64"))
+}
+
 pub fn str_val(buf: &[u8], addr: usize, len: usize) -> String {
     assert!(addr + len <= buf.len());
     let bytes = (addr..addr + len)
@@ -276,6 +658,19 @@ pub fn str_val(buf: &[u8], addr: usize, len: usize) -> String {
 }
 
 // https://dev.mysql.com/doc/refman/8.0/en/storage-requirements.html
+pub fn unpack_i8_val(buf: &[u8]) -> i8 {
+    (buf[0] ^ 0x80) as i8
+}
+
+pub fn unpack_i16_val(buf: &[u8]) -> i16 {
+    i16::from_be_bytes([buf[0] ^ 0x80, buf[1]])
+}
+
+pub fn unpack_i24_val(buf: &[u8]) -> i32 {
+    // sign extend the 3 bytes by shifting to the high bits and back
+    i32::from_be_bytes([buf[0] ^ 0x80, buf[1], buf[2], 0]) >> 8
+}
+
 pub fn unpack_i32_val(buf: &[u8]) -> i32 {
     let signed = (buf[0] & 0x80) > 0;
     if signed {
@@ -316,6 +711,16 @@ pub fn unpack_i64_val(buf: &[u8]) -> i64 {
     }
 }
 
+/// unsigned integer of 1~4 bytes, stored as is in big-endian
+pub fn unpack_u32_val(buf: &[u8]) -> u32 {
+    buf.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32)
+}
+
+/// unsigned integer of 8 bytes, stored as is in big-endian
+pub fn unpack_u64_val(buf: &[u8]) -> u64 {
+    u64_val(buf, 0)
+}
+
 /// enumeration value
 pub fn unpack_enum_val(buf: &[u8]) -> u16 {
     match buf.len() {
@@ -337,11 +742,29 @@ pub fn unpack_newdate_val(b: &Bytes) -> Option<NaiveDate> {
     NaiveDate::from_ymd_opt(year as i32, month, day)
 }
 
-/// u32 => unix timestamp
-pub fn unpack_timestamp2_val(b: &Bytes) -> DateTime<Local> {
+/// u32 => unix timestamp, stored as UTC seconds
+pub fn unpack_timestamp2_val(b: &Bytes) -> DateTime<Utc> {
     let arr = [b[0], b[1], b[2], b[3]];
     let val = u32::from_be_bytes(arr);
-    DateTime::from_timestamp(val.into(), 0).unwrap().into()
+    DateTime::from_timestamp(val.into(), 0).unwrap()
+}
+
+/// parse timezone offset string, like +08:00, -05:00
+pub fn parse_tz_offset(s: &str) -> Result<FixedOffset> {
+    s.parse::<FixedOffset>()
+        .map_err(|e| anyhow::Error::msg(format!("时区格式错误: {}, {}", s, e)))
+}
+
+/// parse page range string, like 3-7, both ends are inclusive
+pub fn parse_page_range(s: &str) -> Result<(usize, usize)> {
+    let err = || anyhow::Error::msg(format!("页码范围格式错误: {}", s));
+    let (a, b) = s.split_once('-').ok_or_else(err)?;
+    let from = a.trim().parse::<usize>().map_err(|_| err())?;
+    let to = b.trim().parse::<usize>().map_err(|_| err())?;
+    if from > to {
+        return Err(err());
+    }
+    Ok((from, to))
 }
 
 /// signed(1), year_month(17), day(5), hour(5), minute(6), second(6)
@@ -480,25 +903,25 @@ pub fn mach_get_compressed_size(n: u32) -> usize {
         1
     } else if n < 0x4000 {
         /* 10nnnnnn nnnnnnnn (14 bits) */
-        return 2;
+        2
     } else if n < 0x200000 {
         /* 110nnnnn nnnnnnnn nnnnnnnn (21 bits) */
-        return 3;
+        3
     } else if n < 0x10000000 {
         /* 1110nnnn nnnnnnnn nnnnnnnn nnnnnnnn (28 bits) */
-        return 4;
+        4
     } else if n >= 0xFFFFFC00 {
         /* 111110nn nnnnnnnn (10 bits) (extended) */
-        return 2;
+        2
     } else if n >= 0xFFFE0000 {
         /* 1111110n nnnnnnnn nnnnnnnn (17 bits) (extended) */
-        return 3;
+        3
     } else if n >= 0xFF000000 {
         /* 11111110 nnnnnnnn nnnnnnnn nnnnnnnn (24 bits) (extended) */
-        return 4;
+        4
     } else {
         /* 11110000 nnnnnnnn nnnnnnnn nnnnnnnn nnnnnnnn (32 bits) */
-        return 5;
+        5
     }
 }
 
@@ -545,11 +968,97 @@ mod util_tests {
     use log::info;
 
     use super::*;
+    use crate::ibd::page::{FilePageHeader, PageTypes};
 
     fn newbuf(data: &[u8]) -> Arc<Bytes> {
         Arc::new(Bytes::copy_from_slice(data))
     }
 
+    #[test]
+    fn test_verbose_level() {
+        use clap::Parser;
+
+        let args = crate::Args::try_parse_from(["ibr", "-vv", "a.ibd", "info"]).unwrap();
+        assert_eq!(args.verbose, 2);
+        assert_eq!(verbose_level(args.verbose), Some(LevelFilter::Debug));
+        assert!(verbose_level(args.verbose).unwrap() >= log::Level::Debug);
+
+        let args = crate::Args::try_parse_from(["ibr", "a.ibd", "info"]).unwrap();
+        assert_eq!(verbose_level(args.verbose), None);
+        assert_eq!(verbose_level(1), Some(LevelFilter::Info));
+        assert_eq!(verbose_level(5), Some(LevelFilter::Trace));
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn lz4_block_uncomp() {
+        // literals "abcd", match offset=4 len=8, last literals "e"
+        let input = [0x44, b'a', b'b', b'c', b'd', 0x04, 0x00, 0x10, b'e'];
+        let output = lz4_uncomp_bytes(&input, 13).unwrap();
+        assert_eq!(output, b"abcdabcdabcde");
+        assert!(lz4_uncomp_bytes(&[0x04, 0x00, 0x01], 8).is_err());
+        assert!(lz4_uncomp_bytes(&[0x50, b'a'], 5).is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "lz4"))]
+    fn lz4_feature_disabled() {
+        init_unit_test();
+        let input = std::fs::read("data/lz4_overlap.lz4").unwrap();
+        assert!(lz4_uncomp_bytes(&input, 3000).is_err());
+    }
+
+    /// pseudo random bytes, the LZ4 fixtures are compressed from them
+    #[cfg(feature = "lz4")]
+    fn lcg_bytes(seed: u64, n: usize) -> Vec<u8> {
+        let mut x = seed;
+        (0..n)
+            .map(|_| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn lz4_block_fixtures() {
+        use crate::ibd::page::UNIV_PAGE_SIZE;
+
+        init_unit_test();
+        // the fixtures are compressed by LZ4_compress_default of liblz4
+        let literals = lcg_bytes(1, 600);
+        let long_offset = [lcg_bytes(2, 4000), lcg_bytes(3, 2000), lcg_bytes(2, 4000)].concat();
+        let overlap = b"abc".repeat(1000);
+        let page = std::fs::read("data/departments.ibd").unwrap();
+        let page = page[UNIV_PAGE_SIZE * 4 + 38..UNIV_PAGE_SIZE * 5].to_vec();
+        for (name, expected) in [
+            ("literals", literals),
+            ("long_offset", long_offset),
+            ("overlap", overlap),
+            ("page_04", page),
+        ] {
+            let input = std::fs::read(format!("data/lz4_{}.lz4", name)).unwrap();
+            let output = lz4_uncomp_bytes(&input, expected.len()).unwrap();
+            assert!(output == expected, "lz4 fixture {}", name);
+
+            // truncated input and wrong original size are reported
+            assert!(lz4_uncomp_bytes(&input[..input.len() - 1], expected.len()).is_err());
+            assert!(lz4_uncomp_bytes(&input, expected.len() + 1).is_err());
+        }
+
+        // literal-only block with the extended literal length bytes
+        let input = std::fs::read("data/lz4_literals.lz4").unwrap();
+        assert_eq!(&input[..4], &[0xf0, 0xff, 0xff, 75]);
+
+        // corrupted match offset beyond the output
+        let mut input = std::fs::read("data/lz4_overlap.lz4").unwrap();
+        input[4..6].copy_from_slice(&100u16.to_le_bytes());
+        assert!(lz4_uncomp_bytes(&input, 3000).is_err());
+    }
+
     #[test]
     fn mach_read_from_bytes_array() {
         init_unit_test();
@@ -576,6 +1085,24 @@ mod util_tests {
         assert_eq!(mach_read_compressed(0, newbuf(&[88])), 88);
     }
 
+    #[test]
+    fn test_assume_le() {
+        init_unit_test();
+        let buf = std::fs::read("data/departments.ibd").unwrap();
+        // FIL_PAGE_TYPE of page 0 is FSP_HDR
+        assert_eq!(u16_val(&buf, 24), 8);
+        assert_eq!(u16_val_le(&buf, 24), 0x0800);
+        assert_eq!(u32_val_le(&buf, 34), u32_val(&buf, 34).swap_bytes());
+        assert_eq!(u48_val_le(&buf, 18), u48_val(&buf, 18).swap_bytes() >> 16);
+
+        // the FIL header is read in little-endian with `--assume-le`
+        let buf = Arc::new(Bytes::from(buf));
+        let fil_hdr = FilePageHeader::new_le(0, buf.clone());
+        assert_eq!(fil_hdr.page_type, PageTypes::from(0x0800u16));
+        assert_eq!(fil_hdr.lsn, u64_val(&buf, 16).swap_bytes());
+        assert_eq!(FilePageHeader::new(0, buf).page_type, PageTypes::FSP_HDR);
+    }
+
     #[test]
     fn it_works() {
         init_unit_test();
@@ -627,6 +1154,119 @@ mod util_tests {
         assert!(ans.is_some());
     }
 
+    #[test]
+    fn test_conv_small_int() {
+        init_unit_test();
+        assert_eq!(unpack_i8_val(&[0x00]), i8::MIN);
+        assert_eq!(unpack_i8_val(&[0x7f]), -1);
+        assert_eq!(unpack_i8_val(&[0x80]), 0);
+        assert_eq!(unpack_i8_val(&[0x85]), 5);
+        assert_eq!(unpack_i8_val(&[0xff]), i8::MAX);
+
+        assert_eq!(unpack_i16_val(&[0x00, 0x00]), i16::MIN);
+        assert_eq!(unpack_i16_val(&[0x7f, 0xfe]), -2);
+        assert_eq!(unpack_i16_val(&[0x80, 0x00]), 0);
+        assert_eq!(unpack_i16_val(&[0x81, 0x00]), 256);
+        assert_eq!(unpack_i16_val(&[0xff, 0xff]), i16::MAX);
+
+        assert_eq!(unpack_i24_val(&[0x00, 0x00, 0x00]), -0x800000);
+        assert_eq!(unpack_i24_val(&[0x7f, 0xff, 0x9c]), -100);
+        assert_eq!(unpack_i24_val(&[0x80, 0x00, 0x00]), 0);
+        assert_eq!(unpack_i24_val(&[0x80, 0x00, 0x64]), 100);
+        assert_eq!(unpack_i24_val(&[0xff, 0xff, 0xff]), 0x7fffff);
+    }
+
+    #[test]
+    fn test_raw_bytes_str() {
+        assert_eq!(raw_bytes_str(&[0x0a, 0x0b, 0xff]), "Raw(3 bytes: 0a 0b ff)");
+        assert_eq!(raw_bytes_str(&[]), "Raw(0 bytes: )");
+    }
+
+    #[test]
+    fn test_humanize_bytes() {
+        init_unit_test();
+        let size = std::fs::metadata("data/departments.ibd").unwrap().len();
+        assert_eq!(humanize_bytes(size as usize), "128.00 KiB");
+        assert_eq!(humanize_bytes(100), "100 B");
+        assert_eq!(humanize_bytes(96 * 1024 * 1024), "96.00 MiB");
+        assert_eq!(humanize_bytes(3 << 29), "1.50 GiB");
+    }
+
+    #[test]
+    fn test_fmt_page_ranges() {
+        assert_eq!(fmt_page_ranges(&[]), "");
+        assert_eq!(fmt_page_ranges(&[4]), "4");
+        assert_eq!(fmt_page_ranges(&[4, 5, 6, 9, 11, 12]), "4-6, 9, 11-12");
+    }
+
+    #[test]
+    fn test_progress_bar_hidden() {
+        init_unit_test();
+        let pb = progress_bar(PROGRESS_BAR_MIN_PAGES - 1);
+        assert!(pb.is_hidden());
+        pb.inc(1);
+        pb.finish_and_clear();
+    }
+
+    #[test]
+    fn test_crc32c_vectors() {
+        let vectors: [(&[u8], u32); 4] = [
+            (b"", 0x0000_0000),
+            (b"a", 0xc1d0_4330),
+            (b"123456789", 0xe306_9283),
+            (&[0u8; 32], 0x8a91_36aa),
+        ];
+        for (data, crc) in vectors {
+            assert_eq!(crc32c(data), crc);
+            assert_eq!(crc32c_sw(data), crc);
+        }
+        let data: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+        assert_eq!(crc32c_sw(&data), crc32c::crc32c(&data));
+    }
+
+    #[test]
+    fn test_crc32c_fixtures() {
+        let data = std::fs::read("data/departments.ibd").unwrap();
+        let page = &data[..16384];
+        assert_eq!(page_crc32c(page), u32_val(page, 0));
+
+        let data = std::fs::read("data/redo_block_01").unwrap();
+        let block = &data[512 * 4..512 * 5];
+        assert_eq!(log_block_crc32c(block), u32_val(block, 508));
+    }
+
+    #[test]
+    fn test_fnv1a64_vectors() {
+        assert_eq!(fnv1a64(&[b""]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a64(&[b"a"]), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a64(&[b"foo", b"bar"]), fnv1a64(&[b"foobar"]));
+    }
+
+    #[test]
+    fn test_parse_page_range() {
+        assert_eq!(parse_page_range("3-7").unwrap(), (3, 7));
+        assert_eq!(parse_page_range("4-4").unwrap(), (4, 4));
+        assert!(parse_page_range("7-3").is_err());
+        assert!(parse_page_range("abc").is_err());
+    }
+
+    #[test]
+    fn test_conv_timestamp_tz() {
+        init_unit_test();
+        // 2024-08-11 08:00:00 UTC
+        let buf = Bytes::from_static(&[0x66, 0xb8, 0x6f, 0x80]);
+        let ts = unpack_timestamp2_val(&buf);
+        info!("ts={:?}", ts);
+        let utc = ts.with_timezone(&parse_tz_offset("+00:00").unwrap());
+        let cst = ts.with_timezone(&parse_tz_offset("+08:00").unwrap());
+        assert_eq!(utc, cst);
+        assert_eq!(
+            cst.naive_local() - utc.naive_local(),
+            chrono::Duration::hours(8)
+        );
+        assert!(parse_tz_offset("8h").is_err());
+    }
+
     #[test]
     fn test_align_count() {
         init_unit_test();